@@ -34,7 +34,7 @@ macro_rules! eval_bench {
         fn $name(b: &mut Bencher) {
             let tokens = tokenize($input).unwrap();
             let expr = parse(&tokens).unwrap();
-            let mut i = Interpreter::default();
+            let mut i = Interpreter::<f64>::default();
             i.set_var(String::from("pad"), Variant::Num(5.0));
             i.set_var(String::from("x"), Variant::Num(2.0));
             i.set_var(String::from("applesauce"), Variant::Num(1.0));
@@ -0,0 +1,254 @@
+use crate::{Integer, InterpretError, Num, OpVal, Settings};
+use num::bigint::ParseBigIntError;
+use num::traits::{Pow, Signed, ToPrimitive, Zero};
+pub use num::BigInt;
+use num::BigRational;
+use std::fmt::{self, Display, Formatter};
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Rem, Sub, SubAssign};
+use std::str::FromStr;
+
+/// Big integers never overflow, but like the primitive integers, dividing by zero and raising
+/// to a negative power are errors.
+impl Num for BigInt {
+    fn zero() -> Self {
+        Zero::zero()
+    }
+    fn one() -> Self {
+        num::One::one()
+    }
+    fn is_whole(&self) -> bool {
+        true
+    }
+    fn parse_literal(s: &str) -> Option<Self> {
+        s.parse().ok()
+    }
+    /// A negative exponent gives 0 unless the number is 1 or -1, like `1 / self.pow(-other)`
+    /// would.
+    fn pow(self, other: Self) -> Self {
+        self.arithmetic(OpVal::Pow, other, &Settings::default())
+            .unwrap_or_else(|_| Num::zero())
+    }
+
+    fn arithmetic(
+        self,
+        op: OpVal,
+        other: Self,
+        _settings: &Settings,
+    ) -> Result<Self, InterpretError<'static>> {
+        if other.is_zero() && (op == OpVal::Div || op == OpVal::Mod) {
+            return Err(InterpretError::DivisionByZero);
+        }
+        Ok(match op {
+            OpVal::Add => self + other,
+            OpVal::Sub => self - other,
+            OpVal::Mul => self * other,
+            OpVal::Div => self / other,
+            OpVal::Mod => self % other,
+            OpVal::Pow => {
+                let odd = other.bit(0);
+                if other.is_negative() {
+                    return match self.to_i8() {
+                        Some(1) => Ok(self),
+                        Some(-1) => Ok(if odd { self } else { -self }),
+                        _ => Err(InterpretError::NegativeExponent),
+                    };
+                }
+                match (other.to_u32(), self.to_i8()) {
+                    (Some(exp), _) => Pow::pow(self, exp),
+                    // Only 0, 1 and -1 can be raised to exponents this large
+                    (None, Some(0)) | (None, Some(1)) => self,
+                    (None, Some(-1)) => {
+                        if odd {
+                            self
+                        } else {
+                            -self
+                        }
+                    }
+                    (None, _) => return Err(InterpretError::Overflow),
+                }
+            }
+            _ => unreachable!(),
+        })
+    }
+
+    fn negate(self, _: &Settings) -> Result<Self, InterpretError<'static>> {
+        Ok(-self)
+    }
+}
+
+impl Integer for BigInt {
+    fn shift_left(self, bits: Self) -> Option<Self> {
+        bits.to_usize().map(|bits| self << bits)
+    }
+    fn shift_right(self, bits: Self) -> Option<Self> {
+        bits.to_usize().map(|bits| self >> bits)
+    }
+}
+
+/// An exact fraction of big integers.
+///
+/// Decimal literals parse exactly, so `0.1` is one tenth, and results display as whole
+/// numbers or fractions such as `1/2`. Dividing by zero and raising to a fractional power,
+/// whose result is usually irrational, are errors.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Rational(pub BigRational);
+
+impl Rational {
+    pub fn new(numer: BigInt, denom: BigInt) -> Rational {
+        Rational(BigRational::new(numer, denom))
+    }
+
+    pub fn numer(&self) -> &BigInt {
+        self.0.numer()
+    }
+
+    pub fn denom(&self) -> &BigInt {
+        self.0.denom()
+    }
+
+    pub fn floor(&self) -> Rational {
+        Rational(self.0.floor())
+    }
+
+    pub fn ceil(&self) -> Rational {
+        Rational(self.0.ceil())
+    }
+
+    /// Rounds half-way cases away from zero.
+    pub fn round(&self) -> Rational {
+        Rational(self.0.round())
+    }
+
+    pub fn trunc(&self) -> Rational {
+        Rational(self.0.trunc())
+    }
+}
+
+impl From<BigInt> for Rational {
+    fn from(n: BigInt) -> Self {
+        Rational(BigRational::from_integer(n))
+    }
+}
+
+impl Num for Rational {
+    fn zero() -> Self {
+        Rational(Zero::zero())
+    }
+    fn one() -> Self {
+        Rational(num::One::one())
+    }
+    fn is_atomic(&self) -> bool {
+        self.0.is_integer()
+    }
+    fn is_whole(&self) -> bool {
+        self.0.is_integer()
+    }
+    fn parse_literal(s: &str) -> Option<Self> {
+        s.parse().ok()
+    }
+    /// Gives 0 where `arithmetic` returns an error.
+    fn pow(self, other: Self) -> Self {
+        self.arithmetic(OpVal::Pow, other, &Settings::default())
+            .unwrap_or_else(|_| Num::zero())
+    }
+
+    fn arithmetic(
+        self,
+        op: OpVal,
+        other: Self,
+        _settings: &Settings,
+    ) -> Result<Self, InterpretError<'static>> {
+        if other.0.is_zero() && (op == OpVal::Div || op == OpVal::Mod) {
+            return Err(InterpretError::DivisionByZero);
+        }
+        Ok(match op {
+            OpVal::Add => self + other,
+            OpVal::Sub => self - other,
+            OpVal::Mul => self * other,
+            OpVal::Div => self / other,
+            OpVal::Mod => self % other,
+            OpVal::Pow => {
+                if !other.is_whole() {
+                    return Err(InterpretError::OutOfDomain("^"));
+                }
+                if self.0.is_zero() && other.0.is_negative() {
+                    return Err(InterpretError::DivisionByZero);
+                }
+                match other.numer().to_i32() {
+                    Some(exp) => Rational(Pow::pow(self.0, exp)),
+                    None if self.0.is_zero() || self.0.abs() == num::One::one() => {
+                        Rational::from(Num::pow(self.numer().clone(), other.numer().clone()))
+                    }
+                    None => return Err(InterpretError::Overflow),
+                }
+            }
+            _ => unreachable!(),
+        })
+    }
+
+    fn negate(self, _: &Settings) -> Result<Self, InterpretError<'static>> {
+        Ok(-self)
+    }
+}
+
+/// Parses whole numbers and decimals like `12`, `0.5`, `.5` or `5.` exactly.
+impl FromStr for Rational {
+    type Err = ParseBigIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (whole, fraction) = s.split_once('.').unwrap_or((s, ""));
+        if fraction.is_empty() {
+            return Ok(Rational::from(whole.parse::<BigInt>()?));
+        }
+        // "123.45" is 12345/100. A fraction with a sign is parsed with the point left in, which
+        // fails
+        let digits = if fraction.bytes().all(|b| b.is_ascii_digit()) {
+            format!("{}{}", whole, fraction)
+        } else {
+            s.to_owned()
+        };
+        let denom = Pow::pow(BigInt::from(10), fraction.len());
+        Ok(Rational::new(digits.parse()?, denom))
+    }
+}
+
+impl Display for Rational {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+macro_rules! impl_rational_op {
+    ($op:ident, $method:ident, $assign_op:ident, $assign_method:ident) => {
+        impl $op for Rational {
+            type Output = Rational;
+            fn $method(self, other: Rational) -> Rational {
+                Rational(self.0.$method(other.0))
+            }
+        }
+
+        impl $assign_op for Rational {
+            fn $assign_method(&mut self, other: Rational) {
+                self.0.$assign_method(other.0);
+            }
+        }
+    };
+}
+impl_rational_op!(Add, add, AddAssign, add_assign);
+impl_rational_op!(Sub, sub, SubAssign, sub_assign);
+impl_rational_op!(Mul, mul, MulAssign, mul_assign);
+impl_rational_op!(Div, div, DivAssign, div_assign);
+
+impl Rem for Rational {
+    type Output = Rational;
+    fn rem(self, other: Rational) -> Rational {
+        Rational(self.0 % other.0)
+    }
+}
+
+impl Neg for Rational {
+    type Output = Rational;
+    fn neg(self) -> Rational {
+        Rational(-self.0)
+    }
+}
@@ -3,11 +3,13 @@ use std::io::prelude::*;
 use structopt::StructOpt;
 
 use rsc::{
-    parse, tokenize, InterpretError, Interpreter, Num, ParseError, ParseErrorCode, TokenizeError,
-    Variant,
+    best_fraction, diagnose, parse, tokenize, AngleMode, ArithmeticMode, BigInt, Complex, Decimal,
+    Diagnostic, Dual, Interpreter, Interval, Notation, Num, NumberFormat, Quantity, Rational,
+    Uncertain, Value, Variant,
 };
 use std::fmt::Display;
-use std::ops::Range;
+use std::num::{Saturating, Wrapping};
+use std::str::FromStr;
 
 #[derive(StructOpt)]
 #[structopt(about = "A scientific calculator for the terminal.")]
@@ -22,33 +24,285 @@ struct Opt {
     vars: bool,
     #[structopt(long = "no-color", help = "Prevents colored text")]
     no_color: bool,
+    #[structopt(
+        long = "type",
+        value_name = "type",
+        conflicts_with_all = &["complex", "integer"],
+        help = "Calculates with a number type, e.g. i64, u8, wrapping-u8 or interval, instead of f64. See the type command"
+    )]
+    number_type: Option<NumberType>,
+    #[structopt(
+        long = "mode",
+        value_name = "mode",
+        default_value = "checked",
+        parse(try_from_str = parse_arithmetic_mode),
+        help = "Handles integer overflow: checked makes it an error, wrapping wraps around and saturating clamps"
+    )]
+    arithmetic_mode: ArithmeticMode,
+    #[structopt(
+        short = "c",
+        long = "complex",
+        help = "Calculates with complex numbers, e.g. sqrt(-4) = 2i. Short for --type complex"
+    )]
+    complex: bool,
+    #[structopt(
+        short = "i",
+        long = "integer",
+        conflicts_with = "complex",
+        help = "Calculates with 64-bit integers, e.g. factor(91) = 7. Short for --type i64"
+    )]
+    integer: bool,
+    #[structopt(
+        short = "f",
+        long = "fraction",
+        help = "Prints results as fractions when one is close, e.g. 0.125 = 1/8"
+    )]
+    fraction: bool,
+    #[structopt(
+        long = "format",
+        default_value = "plain",
+        help = "Writes results in a notation: plain, fix<digits>, sig<digits>, sci[<digits>] or eng[<digits>]"
+    )]
+    notation: Notation,
+    #[structopt(
+        long = "group",
+        value_name = "separator",
+        help = "Separates groups of three digits, e.g. with ,"
+    )]
+    group_separator: Option<char>,
+    #[structopt(
+        long = "decimal-mark",
+        value_name = "mark",
+        default_value = ".",
+        help = "Writes results with a decimal mark, e.g. ,"
+    )]
+    decimal_mark: char,
+}
+
+/// The largest denominator and the distance of fractions shown for results.
+const FRACTION_MAX_DENOMINATOR: u64 = 1000;
+const FRACTION_TOLERANCE: f64 = 1e-6;
+
+/// How results are printed, set by flags and REPL commands.
+struct Output {
+    fraction: bool,
+    number: NumberFormat,
+}
+
+impl Output {
+    fn format<N: Display>(&self, result: &N) -> String {
+        if self.fraction {
+            // Only number types that display plain decimals have fractions
+            let fraction = result
+                .to_string()
+                .parse()
+                .ok()
+                .and_then(|x| best_fraction(x, FRACTION_MAX_DENOMINATOR, FRACTION_TOLERANCE));
+            match fraction {
+                Some(fraction) if fraction.denom > 1 && fraction.exact => {
+                    return fraction.to_string()
+                }
+                Some(fraction) if fraction.denom > 1 => return format!("{} (approx.)", fraction),
+                _ => {}
+            }
+        }
+        self.number.format(result)
+    }
+
+    /// Formats a result that may be a list or matrix. Its numbers are separated by ";" instead
+    /// of "," when "," is the decimal mark. A matrix has a line for each row, with its columns
+    /// aligned.
+    fn format_value<N: Display>(&self, value: &Value<N>) -> String {
+        let separator = if self.number.decimal_mark == ',' {
+            "; "
+        } else {
+            ", "
+        };
+        match value {
+            Value::Num(n) => self.format(n),
+            Value::List(list) => {
+                let items: Vec<String> = list.iter().map(|n| self.format(n)).collect();
+                format!("[{}]", items.join(separator))
+            }
+            Value::Matrix(matrix) => {
+                let cells: Vec<String> = matrix.elements().iter().map(|n| self.format(n)).collect();
+                let width = |col: usize| {
+                    (0..matrix.rows())
+                        .map(|row| cells[row * matrix.cols() + col].chars().count())
+                        .max()
+                        .unwrap_or(0)
+                };
+                let widths: Vec<usize> = (0..matrix.cols()).map(width).collect();
+                let rows: Vec<String> = cells
+                    .chunks(matrix.cols())
+                    .map(|row| {
+                        let items: Vec<String> = row
+                            .iter()
+                            .zip(&widths)
+                            .map(|(cell, width)| format!("{:>1$}", cell, width))
+                            .collect();
+                        format!("[{}]", items.join(separator))
+                    })
+                    .collect();
+                format!("[{}]", rows.join(&format!("{}\n ", separator.trim_end())))
+            }
+        }
+    }
+}
+
+/// Defines `NumberType` with a variant for each number type the executable calculates with,
+/// its name and the type whose default interpreter it runs.
+macro_rules! number_types {
+    ($($variant:ident $name:literal $ntype:ty,)*) => {
+        /// A number type to calculate with, chosen by name with `--type` or the `type` command.
+        #[derive(Copy, Clone, PartialEq)]
+        enum NumberType {
+            $($variant,)*
+        }
+
+        impl NumberType {
+            fn all() -> Vec<NumberType> {
+                vec![$(NumberType::$variant),*]
+            }
+
+            fn name(self) -> &'static str {
+                match self {
+                    $(NumberType::$variant => $name,)*
+                }
+            }
+
+            /// Runs the default interpreter of the type, returning the type to change to if
+            /// the `type` command asks for one.
+            fn run(self, opt: &Opt, output: &mut Output) -> Option<NumberType> {
+                match self {
+                    $(NumberType::$variant => {
+                        run(Interpreter::<$ntype>::default(), self, opt, output)
+                    })*
+                }
+            }
+        }
+    };
+}
+number_types! {
+    F64 "f64" f64,
+    F32 "f32" f32,
+    I8 "i8" i8,
+    I16 "i16" i16,
+    I32 "i32" i32,
+    I64 "i64" i64,
+    I128 "i128" i128,
+    Isize "isize" isize,
+    U8 "u8" u8,
+    U16 "u16" u16,
+    U32 "u32" u32,
+    U64 "u64" u64,
+    U128 "u128" u128,
+    Usize "usize" usize,
+    WrappingI8 "wrapping-i8" Wrapping<i8>,
+    WrappingI16 "wrapping-i16" Wrapping<i16>,
+    WrappingI32 "wrapping-i32" Wrapping<i32>,
+    WrappingI64 "wrapping-i64" Wrapping<i64>,
+    WrappingI128 "wrapping-i128" Wrapping<i128>,
+    WrappingIsize "wrapping-isize" Wrapping<isize>,
+    WrappingU8 "wrapping-u8" Wrapping<u8>,
+    WrappingU16 "wrapping-u16" Wrapping<u16>,
+    WrappingU32 "wrapping-u32" Wrapping<u32>,
+    WrappingU64 "wrapping-u64" Wrapping<u64>,
+    WrappingU128 "wrapping-u128" Wrapping<u128>,
+    WrappingUsize "wrapping-usize" Wrapping<usize>,
+    SaturatingI8 "saturating-i8" Saturating<i8>,
+    SaturatingI16 "saturating-i16" Saturating<i16>,
+    SaturatingI32 "saturating-i32" Saturating<i32>,
+    SaturatingI64 "saturating-i64" Saturating<i64>,
+    SaturatingI128 "saturating-i128" Saturating<i128>,
+    SaturatingIsize "saturating-isize" Saturating<isize>,
+    SaturatingU8 "saturating-u8" Saturating<u8>,
+    SaturatingU16 "saturating-u16" Saturating<u16>,
+    SaturatingU32 "saturating-u32" Saturating<u32>,
+    SaturatingU64 "saturating-u64" Saturating<u64>,
+    SaturatingU128 "saturating-u128" Saturating<u128>,
+    SaturatingUsize "saturating-usize" Saturating<usize>,
+    Complex "complex" Complex,
+    Interval "interval" Interval,
+    Uncertain "uncertain" Uncertain,
+    Dual "dual" Dual,
+    Quantity "quantity" Quantity,
+    BigInt "bigint" BigInt,
+    Rational "rational" Rational,
+    Decimal "decimal" Decimal,
+}
+
+impl FromStr for NumberType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        NumberType::all()
+            .into_iter()
+            .find(|number_type| number_type.name() == s)
+            .ok_or_else(|| format!("Unknown number type {:?}. Try {}.", s, type_names()))
+    }
+}
+
+/// The names of every number type, for messages.
+fn type_names() -> String {
+    let names: Vec<&str> = NumberType::all()
+        .into_iter()
+        .map(NumberType::name)
+        .collect();
+    names.join(", ")
 }
 
 fn main() {
     let opt = Opt::from_args();
+    let mut output = Output {
+        fraction: opt.fraction,
+        number: NumberFormat {
+            notation: opt.notation,
+            group_separator: opt.group_separator,
+            decimal_mark: opt.decimal_mark,
+        },
+    };
 
-    let mut interpreter = Interpreter::default();
+    let mut number_type = if opt.complex {
+        NumberType::Complex
+    } else if opt.integer {
+        NumberType::I64
+    } else {
+        opt.number_type.unwrap_or(NumberType::F64)
+    };
+    if opt.expr.is_none() {
+        println!("RSC interactive expression interpreter.");
+        println!("Try \"help\" for commands and examples.");
+    }
+    while let Some(next) = number_type.run(&opt, &mut output) {
+        number_type = next;
+        println!(
+            "Calculating with {}. Variables start over.",
+            number_type.name()
+        );
+    }
+}
 
-    if let Some(expr) = opt.expr {
-        match tokenize(&expr) {
-            Ok(tokens) => match parse(&tokens) {
-                Ok(expr) => match interpreter.eval(&expr) {
-                    Ok(result) => {
-                        println!("{}", result);
-                        return;
-                    }
-                    Err(e) => eprintln!("{:?}", e),
-                },
-                Err(ParseError { code, span }) => eprintln!("{:?} at {:?}", code, span),
-            },
-            Err(TokenizeError { code, span }) => eprintln!("{:?} at {:?}", code, span),
+/// Evaluates the expression given as an argument, or else runs the interactive interpreter
+/// until it is closed or the `type` command changes the number type, which is returned.
+fn run<N: Num + Display>(
+    mut interpreter: Interpreter<N>,
+    number_type: NumberType,
+    opt: &Opt,
+    output: &mut Output,
+) -> Option<NumberType> {
+    interpreter.settings.arithmetic_mode = opt.arithmetic_mode;
+    if let Some(expr) = &opt.expr {
+        match interpreter.eval_value_str(expr) {
+            Ok(result) => println!("{}", output.format_value(&result)),
+            Err(e) => {
+                eprintln!("{}", Diagnostic::from(&e).render(expr, !opt.no_color));
+                std::process::exit(1);
+            }
         }
-        std::process::exit(1);
+        return None;
     }
 
-    println!("RSC interactive expression interpreter.");
-    println!("Try \"help\" for commands and examples.");
-
     loop {
         print!(
             "{}",
@@ -65,11 +319,34 @@ fn main() {
         buffer = buffer.trim().to_owned();
 
         if &buffer[..] == "quit" || &buffer[..] == "exit" {
-            break;
+            return None;
         } else if &buffer[..] == "help" {
             print_help(opt.no_color);
         } else if &buffer[..] == "vars" {
             print_vars(&interpreter, opt.no_color);
+        } else if buffer == "angle" || buffer.starts_with("angle ") {
+            angle_command(&mut interpreter, buffer["angle".len()..].trim());
+        } else if buffer == "mode" || buffer.starts_with("mode ") {
+            mode_command(&mut interpreter, buffer["mode".len()..].trim());
+        } else if buffer == "fraction" || buffer.starts_with("fraction ") {
+            fraction_command(output, buffer["fraction".len()..].trim());
+        } else if buffer == "format" || buffer.starts_with("format ") {
+            format_command(output, buffer["format".len()..].trim());
+        } else if buffer == "group" || buffer.starts_with("group ") {
+            group_command(output, &buffer["group".len()..]);
+        } else if buffer == "decimal" || buffer.starts_with("decimal ") {
+            decimal_command(output, buffer["decimal".len()..].trim());
+        } else if buffer == "type" || buffer.starts_with("type ") {
+            match buffer["type".len()..].trim() {
+                "" => {
+                    println!("Calculating with {}.", number_type.name());
+                    println!("Types: {}", type_names());
+                }
+                name => match name.parse() {
+                    Ok(number_type) => return Some(number_type),
+                    Err(err) => println!("{}", err),
+                },
+            }
         } else if &buffer[..] == "clear" {
             for _ in 0..100 {
                 println!();
@@ -85,16 +362,38 @@ fn main() {
                 opt.bexpr,
                 opt.vars,
                 opt.no_color,
-                ":",
+                output,
             );
         }
     }
 }
 
-const COMMANDS: [(&str, &str); 5] = [
+const COMMANDS: [(&str, &str); 12] = [
     ("quit|exit", "Close RSC"),
     ("help", "Show this help information"),
     ("vars", "Display all of the active variables"),
+    (
+        "angle [rad|deg|grad]",
+        "Show or set the angle unit of trigonometry",
+    ),
+    (
+        "mode [checked|wrapping|saturating]",
+        "Show or set how integer overflow is handled",
+    ),
+    (
+        "fraction [on|off]",
+        "Show or set whether results print as fractions",
+    ),
+    (
+        "format [notation]",
+        "Show or set the notation: plain, fix4, sig6, sci, sci3, eng, ...",
+    ),
+    ("group [sep|off]", "Show or set the digit group separator"),
+    ("decimal [mark]", "Show or set the decimal mark"),
+    (
+        "type [name]",
+        "Show the number types or calculate with another, e.g. i64",
+    ),
     ("clear", "Clear prior output"),
     (":", "Write notes"),
 ];
@@ -103,7 +402,7 @@ fn print_help(no_color: bool) {
     println!("Commands");
     for (name, desc) in COMMANDS {
         println!(
-            "{:<10} {}",
+            "{:<20} {}",
             if no_color {
                 name.green().clear()
             } else {
@@ -117,12 +416,141 @@ fn print_help(no_color: bool) {
     println!("\t|-9| + 3!");
     println!("\tx = abs(5)");
     println!("\t-x^4");
+    println!("\tsin(30°)");
+    println!("\tv = [1, 2, 3]");
+    println!("\tsum(v * 2)");
+    println!("\tdet([[1, 2], [3, 4]])");
+}
+
+/// Prints the angle mode, or sets it to the unit named by `arg`.
+fn angle_command<N: Num>(interpreter: &mut Interpreter<N>, arg: &str) {
+    let mode = match arg {
+        "" => {
+            let unit = match interpreter.settings.angle_mode {
+                AngleMode::Radians => "radians",
+                AngleMode::Degrees => "degrees",
+                AngleMode::Gradians => "gradians",
+            };
+            println!("Angles are in {}.", unit);
+            return;
+        }
+        "rad" | "radians" => AngleMode::Radians,
+        "deg" | "degrees" => AngleMode::Degrees,
+        "grad" | "gradians" => AngleMode::Gradians,
+        _ => {
+            println!("Unknown angle unit {:?}. Try rad, deg or grad.", arg);
+            return;
+        }
+    };
+    interpreter.settings.angle_mode = mode;
+}
+
+/// Reads the name of an arithmetic mode, for `--mode` and the `mode` command.
+fn parse_arithmetic_mode(s: &str) -> Result<ArithmeticMode, String> {
+    match s {
+        "checked" => Ok(ArithmeticMode::Checked),
+        "wrapping" => Ok(ArithmeticMode::Wrapping),
+        "saturating" => Ok(ArithmeticMode::Saturating),
+        _ => Err(format!(
+            "Unknown arithmetic mode {:?}. Try checked, wrapping or saturating.",
+            s
+        )),
+    }
+}
+
+/// Prints how integer overflow is handled, or sets the arithmetic mode named by `arg`.
+fn mode_command<N: Num>(interpreter: &mut Interpreter<N>, arg: &str) {
+    if arg.is_empty() {
+        let mode = match interpreter.settings.arithmetic_mode {
+            ArithmeticMode::Checked => "checked",
+            ArithmeticMode::Wrapping => "wrapping",
+            ArithmeticMode::Saturating => "saturating",
+        };
+        println!("Integer arithmetic is {}.", mode);
+        return;
+    }
+    match parse_arithmetic_mode(arg) {
+        Ok(mode) => interpreter.settings.arithmetic_mode = mode,
+        Err(err) => println!("{}", err),
+    }
+}
+
+/// Prints whether results print as fractions, or turns it on or off.
+fn fraction_command(output: &mut Output, arg: &str) {
+    output.fraction = match arg {
+        "" => {
+            let state = if output.fraction { "on" } else { "off" };
+            println!("Fractions are {}.", state);
+            return;
+        }
+        "on" => true,
+        "off" => false,
+        _ => {
+            println!("Unknown fraction setting {:?}. Try on or off.", arg);
+            return;
+        }
+    };
+}
+
+/// Prints the notation of results, or sets it to the one named by `arg`.
+fn format_command(output: &mut Output, arg: &str) {
+    if arg.is_empty() {
+        println!(
+            "Results are written in {} notation.",
+            output.number.notation
+        );
+        return;
+    }
+    match arg.parse() {
+        Ok(notation) => output.number.notation = notation,
+        Err(err) => println!("{}", err),
+    }
+}
+
+/// Prints the separator between groups of digits, or sets it to `arg`, which is a character
+/// after the command, `space` or `off`.
+fn group_command(output: &mut Output, arg: &str) {
+    let separator = match arg.strip_prefix(' ').unwrap_or(arg) {
+        "" => {
+            match output.number.group_separator {
+                Some(separator) => println!("Digits are grouped with {:?}.", separator),
+                None => println!("Digits are not grouped."),
+            }
+            return;
+        }
+        "off" => None,
+        "space" => Some(' '),
+        arg => {
+            let mut chars = arg.chars();
+            match (chars.next(), chars.next()) {
+                (Some(separator), None) => Some(separator),
+                _ => {
+                    println!(
+                        "Unknown separator {:?}. Try a character, space or off.",
+                        arg
+                    );
+                    return;
+                }
+            }
+        }
+    };
+    output.number.group_separator = separator;
+}
+
+/// Prints the decimal mark, or sets it to `arg`.
+fn decimal_command(output: &mut Output, arg: &str) {
+    let mut chars = arg.chars();
+    match (chars.next(), chars.next()) {
+        (None, _) => println!("The decimal mark is {:?}.", output.number.decimal_mark),
+        (Some(mark), None) => output.number.decimal_mark = mark,
+        _ => println!("Unknown decimal mark {:?}. Try a character.", arg),
+    }
 }
 
 fn get_variant_ord<N: Num>(v: &Variant<N>) -> usize {
     match v {
-        Variant::Num(_) => 1,
-        Variant::Function(_) => 0,
+        Variant::Num(_) | Variant::List(_) | Variant::Matrix(_) => 1,
+        Variant::Function(_) | Variant::ValueFunction(_) => 0,
     }
 }
 
@@ -135,11 +563,12 @@ fn print_vars<N: Num + Display>(interpreter: &Interpreter<N>, no_color: bool) {
         v1_val.cmp(&v2_val)
     });
     for (id, val) in vars {
-        let fmt;
-        match val {
-            Variant::Num(n) => fmt = format!("{} = {}", &id.green(), n.clone()),
-            Variant::Function(_) => fmt = format!("{}(..)", &id.green()),
-        }
+        let fmt = match val {
+            Variant::Num(n) => format!("{} = {}", id.green(), n.clone()),
+            Variant::List(list) => format!("{} = {}", id.green(), Value::List(list.clone())),
+            Variant::Matrix(matrix) => format!("{} = {}", id.green(), matrix),
+            Variant::Function(_) | Variant::ValueFunction(_) => format!("{}(..)", id.green()),
+        };
         println!(
             "{}",
             if no_color {
@@ -151,13 +580,11 @@ fn print_vars<N: Num + Display>(interpreter: &Interpreter<N>, no_color: bool) {
     }
 }
 
-fn format_error(span: Range<usize>, message: &str) -> String {
-    format!(
-        " {}{} {}",
-        " ".repeat(span.start),
-        "^".repeat(span.len()).red(),
-        message.red()
-    )
+/// Prints every problem in the input at once, rather than only the first.
+fn print_diagnostics<N: Num + Display>(input: &str, no_color: bool) {
+    for diagnostic in diagnose::<N>(input) {
+        println!("{}", diagnostic.render(input, !no_color));
+    }
 }
 
 fn evaluate<N: Num + Display>(
@@ -167,7 +594,7 @@ fn evaluate<N: Num + Display>(
     bexpr: bool,
     bvars: bool,
     bno_color: bool,
-    success_prefix: &str,
+    output: &Output,
 ) {
     match tokenize(input) {
         Ok(tokens) => {
@@ -196,47 +623,30 @@ fn evaluate<N: Num + Display>(
                         );
                     }
 
-                    match interpreter.eval(&expr) {
+                    match interpreter.eval_value(&expr) {
                         Ok(result) => {
-                            println!("{}{}", success_prefix, result);
+                            // Indented past the ":" so the rows of a matrix line up
+                            let result = output.format_value(&result).replace('\n', "\n ");
+                            println!(":{}", result);
                         }
                         Err(err) => {
-                            let fmt = format!("{}", display_interpret_error(&err));
-                            println!(
-                                "{}",
-                                if bno_color {
-                                    fmt
-                                } else {
-                                    fmt.red().to_string()
-                                }
-                            );
+                            println!("{}", Diagnostic::from(&err).render(input, !bno_color));
                         }
                     }
                 }
-                Err(ParseError { code, span }) => {
-                    if code == ParseErrorCode::UnexpectedEOF {
-                        println!(
-                            "{}",
-                            format_error(input.len()..input.len() + 1, &format!("{:?}", code))
-                        );
-                    } else {
-                        println!("{}", format_error(span, &format!("{:?}", code)));
-                    }
-                }
+                Err(_) => print_diagnostics::<N>(input, bno_color),
             }
         }
-        Err(TokenizeError { code, span }) => {
-            println!("{}", format_error(span, &format!("{:?}", code)));
-        }
+        Err(_) => print_diagnostics::<N>(input, bno_color),
     }
     if bvars {
         for (id, variant) in &interpreter.vars {
-            let fmt;
-            if let Variant::Num(n) = variant {
-                fmt = format!("{} = {}", id, n);
-            } else {
-                fmt = format!("{}(..)", id);
-            }
+            let fmt = match variant {
+                Variant::Num(n) => format!("{} = {}", id, n),
+                Variant::List(list) => format!("{} = {}", id, Value::List(list.clone())),
+                Variant::Matrix(matrix) => format!("{} = {}", id, matrix),
+                Variant::Function(_) | Variant::ValueFunction(_) => format!("{}(..)", id),
+            };
             println!(
                 "{}",
                 if bno_color {
@@ -248,37 +658,3 @@ fn evaluate<N: Num + Display>(
         }
     }
 }
-
-#[inline(always)]
-fn s_if(b: bool) -> &'static str {
-    if b {
-        "s"
-    } else {
-        ""
-    }
-}
-
-fn display_interpret_error(err: &InterpretError) -> String {
-    match err {
-        InterpretError::TooFewArgs(id, n) => format!(
-            "Function {:?} did not receive minimum of {} argument{}.",
-            id,
-            n,
-            s_if(*n != 1)
-        ),
-        InterpretError::TooManyArgs(id, n) => format!(
-            "Function {:?} received more than the maximum {} argument{}.",
-            id,
-            n,
-            s_if(*n != 1)
-        ),
-        InterpretError::VarDoesNotExist(id) => format!("No variable or function {:?} exists.", id),
-        InterpretError::VarIsNotFunction(id) => format!(
-            "The variable {:?} cannot be used like a function with arguments.",
-            id
-        ),
-        InterpretError::FunctionNameUsedLikeVar(id) => {
-            format!("The function {:?} cannot be used without arguments.", id)
-        }
-    }
-}
@@ -0,0 +1,334 @@
+use crate::{InterpretError, Num, Settings};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt::{self, Display, Formatter};
+use std::num::ParseFloatError;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Rem, Sub, SubAssign};
+use std::str::FromStr;
+
+/// A complex number `re + im·i` of two `f64`s.
+///
+/// Complex numbers have no natural order, so only real numbers (those with an imaginary part
+/// of zero) are ordered, like `f64`s. Comparing anything else with `<` or `>` is false, and
+/// `partial_cmp` returns `None` unless the numbers are equal. A complex number `is_whole` if it
+/// is real and whole.
+///
+/// Complex numbers parse from and display as real numbers, or imaginary numbers with an `i` or
+/// `j` suffix, e.g. `2.5` or `4i`. The tokenizer reads such a suffix as part of a number, so
+/// `3+4i` is a sum of literals.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Complex {
+    pub re: f64,
+    pub im: f64,
+}
+
+impl Complex {
+    /// The imaginary unit.
+    pub const I: Complex = Complex { re: 0.0, im: 1.0 };
+
+    pub fn new(re: f64, im: f64) -> Complex {
+        Complex { re, im }
+    }
+
+    /// Returns the complex number with magnitude `r` at an angle of `theta` radians.
+    pub fn from_polar(r: f64, theta: f64) -> Complex {
+        Complex::new(r * theta.cos(), r * theta.sin())
+    }
+
+    pub fn is_real(&self) -> bool {
+        self.im == 0.0
+    }
+
+    /// The magnitude, or absolute value.
+    pub fn abs(self) -> f64 {
+        self.re.hypot(self.im)
+    }
+
+    /// The angle from the positive real axis in radians, from -π to π.
+    pub fn arg(self) -> f64 {
+        // Adding 0.0 turns -0.0 into 0.0, so negative real numbers have an angle of π, not -π
+        (self.im + 0.0).atan2(self.re)
+    }
+
+    pub fn conj(self) -> Complex {
+        Complex::new(self.re, -self.im)
+    }
+
+    /// The principal square root, e.g. `i` for -1.
+    pub fn sqrt(self) -> Complex {
+        if self.is_real() && self.re >= 0.0 {
+            return Complex::from(self.re.sqrt());
+        }
+        let r = self.abs();
+        let re = ((r + self.re) / 2.0).sqrt();
+        let im = ((r - self.re) / 2.0).sqrt();
+        Complex::new(re, if self.im < 0.0 { -im } else { im })
+    }
+
+    pub fn exp(self) -> Complex {
+        Complex::from_polar(self.re.exp(), self.im)
+    }
+
+    /// The principal natural logarithm.
+    pub fn ln(self) -> Complex {
+        Complex::new(self.abs().ln(), self.arg())
+    }
+
+    pub fn sin(self) -> Complex {
+        Complex::new(
+            self.re.sin() * self.im.cosh(),
+            self.re.cos() * self.im.sinh(),
+        )
+    }
+
+    pub fn cos(self) -> Complex {
+        Complex::new(
+            self.re.cos() * self.im.cosh(),
+            -self.re.sin() * self.im.sinh(),
+        )
+    }
+
+    pub fn tan(self) -> Complex {
+        self.sin() / self.cos()
+    }
+
+    pub fn asin(self) -> Complex {
+        if self.is_real() && self.re.abs() <= 1.0 {
+            return Complex::from(self.re.asin());
+        }
+        // -i ln(iz + sqrt(1 - z^2))
+        -Complex::I * (Complex::I * self + (Complex::from(1.0) - self * self).sqrt()).ln()
+    }
+
+    pub fn acos(self) -> Complex {
+        if self.is_real() && self.re.abs() <= 1.0 {
+            return Complex::from(self.re.acos());
+        }
+        Complex::from(std::f64::consts::FRAC_PI_2) - self.asin()
+    }
+
+    pub fn atan(self) -> Complex {
+        if self.is_real() {
+            return Complex::from(self.re.atan());
+        }
+        // i/2 (ln(1 - iz) - ln(1 + iz))
+        let iz = Complex::I * self;
+        Complex::new(0.0, 0.5) * ((Complex::from(1.0) - iz).ln() - (Complex::from(1.0) + iz).ln())
+    }
+
+    pub fn sinh(self) -> Complex {
+        Complex::new(
+            self.re.sinh() * self.im.cos(),
+            self.re.cosh() * self.im.sin(),
+        )
+    }
+
+    pub fn cosh(self) -> Complex {
+        Complex::new(
+            self.re.cosh() * self.im.cos(),
+            self.re.sinh() * self.im.sin(),
+        )
+    }
+
+    pub fn tanh(self) -> Complex {
+        self.sinh() / self.cosh()
+    }
+}
+
+impl From<f64> for Complex {
+    fn from(re: f64) -> Self {
+        Complex::new(re, 0.0)
+    }
+}
+
+impl Num for Complex {
+    fn zero() -> Self {
+        Complex::from(0.0)
+    }
+    fn one() -> Self {
+        Complex::from(1.0)
+    }
+    fn is_whole(&self) -> bool {
+        self.is_real() && self.re.fract() == 0.0
+    }
+    fn parse_literal(s: &str) -> Option<Self> {
+        s.parse().ok()
+    }
+    /// Whole exponents are computed by repeated multiplication, so that e.g. `i^2` is exactly
+    /// -1. Other exponents use the principal logarithm.
+    fn pow(self, other: Self) -> Self {
+        if other.is_whole() && other.re.abs() <= 1024.0 {
+            let (mut base, mut exp, mut result) = (self, other.re.abs() as u32, Complex::one());
+            while exp > 0 {
+                if exp % 2 == 1 {
+                    result *= base;
+                }
+                base *= base;
+                exp /= 2;
+            }
+            if other.re < 0.0 {
+                Complex::one() / result
+            } else {
+                result
+            }
+        } else if self == Complex::zero() {
+            if other.re > 0.0 {
+                Complex::zero()
+            } else {
+                Complex::new(f64::NAN, f64::NAN)
+            }
+        } else if self.is_real() && other.is_real() && self.re > 0.0 {
+            Complex::from(self.re.powf(other.re))
+        } else {
+            (other * self.ln()).exp()
+        }
+    }
+    fn is_finite(&self) -> bool {
+        self.re.is_finite() && self.im.is_finite()
+    }
+
+    fn is_atomic(&self) -> bool {
+        self.is_real()
+    }
+
+    fn negate(self, _: &Settings) -> Result<Self, InterpretError<'static>> {
+        Ok(-self)
+    }
+
+    fn degrees(self, settings: &Settings) -> Result<Self, InterpretError<'static>> {
+        Ok(self * Complex::from(settings.angle_mode.from_degrees(1.0)))
+    }
+}
+
+impl PartialOrd for Complex {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        if self.is_real() && other.is_real() {
+            self.re.partial_cmp(&other.re)
+        } else if self == other {
+            Some(Ordering::Equal)
+        } else {
+            None
+        }
+    }
+}
+
+impl FromStr for Complex {
+    type Err = ParseFloatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.strip_suffix(|c| c == 'i' || c == 'j') {
+            Some(im) => Ok(Complex::new(0.0, im.parse()?)),
+            None => Ok(Complex::from(s.parse::<f64>()?)),
+        }
+    }
+}
+
+impl Display for Complex {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let imaginary = |f: &mut Formatter, im: f64| {
+            if im == 1.0 {
+                write!(f, "i")
+            } else {
+                write!(f, "{}i", im)
+            }
+        };
+        if self.is_real() {
+            write!(f, "{}", self.re)
+        } else if self.re == 0.0 {
+            if self.im == -1.0 {
+                write!(f, "-i")
+            } else {
+                imaginary(f, self.im)
+            }
+        } else {
+            write!(f, "{}{}", self.re, if self.im < 0.0 { "-" } else { "+" })?;
+            imaginary(f, self.im.abs())
+        }
+    }
+}
+
+impl Add for Complex {
+    type Output = Complex;
+    fn add(self, other: Complex) -> Complex {
+        Complex::new(self.re + other.re, self.im + other.im)
+    }
+}
+
+impl Sub for Complex {
+    type Output = Complex;
+    fn sub(self, other: Complex) -> Complex {
+        Complex::new(self.re - other.re, self.im - other.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Complex;
+    fn mul(self, other: Complex) -> Complex {
+        Complex::new(
+            self.re * other.re - self.im * other.im,
+            self.re * other.im + self.im * other.re,
+        )
+    }
+}
+
+impl Div for Complex {
+    type Output = Complex;
+    fn div(self, other: Complex) -> Complex {
+        if self.is_real() && other.is_real() {
+            return Complex::from(self.re / other.re);
+        } else if other.is_real() {
+            return Complex::new(self.re / other.re, self.im / other.re);
+        }
+        let denominator = other.re * other.re + other.im * other.im;
+        Complex::new(
+            (self.re * other.re + self.im * other.im) / denominator,
+            (self.im * other.re - self.re * other.im) / denominator,
+        )
+    }
+}
+
+/// The remainder after dividing and truncating both parts of the quotient, which is `%` of
+/// `f64` for real numbers.
+impl Rem for Complex {
+    type Output = Complex;
+    fn rem(self, other: Complex) -> Complex {
+        if self.is_real() && other.is_real() {
+            return Complex::from(self.re % other.re);
+        }
+        let quotient = self / other;
+        self - other * Complex::new(quotient.re.trunc(), quotient.im.trunc())
+    }
+}
+
+impl Neg for Complex {
+    type Output = Complex;
+    fn neg(self) -> Complex {
+        Complex::new(-self.re, -self.im)
+    }
+}
+
+impl AddAssign for Complex {
+    fn add_assign(&mut self, other: Complex) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Complex {
+    fn sub_assign(&mut self, other: Complex) {
+        *self = *self - other;
+    }
+}
+
+impl MulAssign for Complex {
+    fn mul_assign(&mut self, other: Complex) {
+        *self = *self * other;
+    }
+}
+
+impl DivAssign for Complex {
+    fn div_assign(&mut self, other: Complex) {
+        *self = *self / other;
+    }
+}
@@ -0,0 +1,538 @@
+use crate::{BigInt, DecimalContext, InterpretError, Num, OpVal, RoundingMode, Settings};
+use num::bigint::ParseBigIntError;
+use num::integer::Integer as _;
+use num::traits::{Pow, Signed, ToPrimitive, Zero};
+use std::cmp::Ordering;
+use std::fmt::{self, Display, Formatter};
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Rem, Sub, SubAssign};
+use std::str::FromStr;
+
+/// Extra digits carried through the steps of functions like `exp` and `ln`, so that only the
+/// final rounding is visible in the result.
+const GUARD_DIGITS: usize = 10;
+
+/// Beyond this many zeros, a number displays in scientific notation.
+const MAX_DISPLAY_ZEROS: i64 = 20;
+
+/// An arbitrary-precision decimal floating point number, `coeff × 10^exp`.
+///
+/// Decimal fractions are exact, so `0.1 + 0.2` is `0.3`, and literals of any length parse
+/// without going through `f64`. The interpreter rounds the result of every operator to the
+/// precision and rounding mode of `Settings::decimal`. The plain operators `+`, `-`, `*` and
+/// `%` are exact, while `/` rounds to the default context and panics when dividing by zero.
+///
+/// Numbers are kept without trailing zeros, so `1.50` and `1.5` are the same and display as
+/// `1.5`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Decimal {
+    coeff: BigInt,
+    exp: i64,
+}
+
+fn pow10(n: u64) -> BigInt {
+    Pow::pow(BigInt::from(10), n)
+}
+
+/// The number of decimal digits of `n`, or 0 for 0.
+fn digits(n: &BigInt) -> u64 {
+    if n.is_zero() {
+        0
+    } else {
+        n.magnitude().to_string().len() as u64
+    }
+}
+
+impl Decimal {
+    pub fn new(coeff: BigInt, exp: i64) -> Decimal {
+        if coeff.is_zero() {
+            return Decimal { coeff, exp: 0 };
+        }
+        let (mut coeff, mut exp) = (coeff, exp);
+        let ten = BigInt::from(10);
+        loop {
+            let (quotient, remainder) = coeff.div_rem(&ten);
+            if !remainder.is_zero() {
+                break;
+            }
+            coeff = quotient;
+            exp += 1;
+        }
+        Decimal { coeff, exp }
+    }
+
+    /// The coefficient, which has no trailing zeros.
+    pub fn coeff(&self) -> &BigInt {
+        &self.coeff
+    }
+
+    pub fn exponent(&self) -> i64 {
+        self.exp
+    }
+
+    /// The number as a `BigInt`, if it is whole.
+    pub fn to_integer(&self) -> Option<BigInt> {
+        if self.exp < 0 {
+            None
+        } else {
+            Some(&self.coeff * pow10(self.exp as u64))
+        }
+    }
+
+    fn abs_value(self) -> Decimal {
+        Decimal {
+            coeff: self.coeff.abs(),
+            exp: self.exp,
+        }
+    }
+
+    /// The exponent just above the leading digit, e.g. 3 for 123.4 and -1 for 0.05.
+    fn magnitude(&self) -> i64 {
+        self.exp + digits(&self.coeff) as i64
+    }
+
+    /// `coeff × 10^exp`, rounded up in the last place if it is `inexact`, so that rounding
+    /// sees that something was cut off below.
+    fn with_sticky_digit(coeff: BigInt, exp: i64, inexact: bool) -> Decimal {
+        if inexact {
+            let sticky = coeff.signum();
+            Decimal::new(coeff * 10 + sticky, exp - 1)
+        } else {
+            Decimal::new(coeff, exp)
+        }
+    }
+
+    /// Rounds to the precision of `context`.
+    pub fn round(&self, context: &DecimalContext) -> Decimal {
+        let excess = digits(&self.coeff) as i64 - context.precision.max(1) as i64;
+        if excess <= 0 {
+            self.clone()
+        } else {
+            self.round_at(self.exp + excess, context.rounding)
+        }
+    }
+
+    /// Rounds to a multiple of `10^exp`, e.g. to 2 places after the point with an `exp` of -2.
+    pub fn round_at(&self, exp: i64, mode: RoundingMode) -> Decimal {
+        if self.exp >= exp {
+            return self.clone();
+        }
+        let drop = (exp - self.exp) as u64;
+        if drop > digits(&self.coeff) + 1 {
+            // Smaller than half a unit, so only the direction matters
+            return Decimal::new(self.coeff.signum(), exp - 2).round_at(exp, mode);
+        }
+        let divisor = pow10(drop);
+        let (quotient, remainder) = self.coeff.abs().div_rem(&divisor);
+        let negative = self.coeff.is_negative();
+        let half = (&remainder * 2u32).cmp(&divisor);
+        let up = match mode {
+            RoundingMode::HalfEven => {
+                half == Ordering::Greater || (half == Ordering::Equal && quotient.is_odd())
+            }
+            RoundingMode::HalfUp => half != Ordering::Less,
+            RoundingMode::HalfDown => half == Ordering::Greater,
+            RoundingMode::Up => !remainder.is_zero(),
+            RoundingMode::Down => false,
+            RoundingMode::Ceiling => !remainder.is_zero() && !negative,
+            RoundingMode::Floor => !remainder.is_zero() && negative,
+        };
+        let quotient = if up { quotient + 1 } else { quotient };
+        Decimal::new(if negative { -quotient } else { quotient }, exp)
+    }
+
+    /// Divides, rounding to the precision of `context`, or returns `None` when dividing by
+    /// zero.
+    pub fn div_with(&self, other: &Decimal, context: &DecimalContext) -> Option<Decimal> {
+        if other.coeff.is_zero() {
+            return None;
+        }
+        // Enough digits in the quotient for a rounding digit
+        let shift = (context.precision as i64 + 2 + digits(&other.coeff) as i64
+            - digits(&self.coeff) as i64)
+            .max(0);
+        let (quotient, remainder) = (&self.coeff * pow10(shift as u64)).div_rem(&other.coeff);
+        let exp = self.exp - other.exp - shift;
+        Some(Decimal::with_sticky_digit(quotient, exp, !remainder.is_zero()).round(context))
+    }
+
+    /// The square root rounded to the precision of `context`, or `None` for negative numbers.
+    pub fn sqrt(&self, context: &DecimalContext) -> Option<Decimal> {
+        if self.coeff.is_negative() {
+            return None;
+        }
+        if self.coeff.is_zero() {
+            return Some(self.clone());
+        }
+        // The root of a number with 2n digits has n digits, and the exponent must be even
+        let wanted = 2 * (context.precision as i64 + 2);
+        let mut shift = (wanted - digits(&self.coeff) as i64).max(0);
+        if (self.exp - shift) % 2 != 0 {
+            shift += 1;
+        }
+        let n = &self.coeff * pow10(shift as u64);
+        let root = n.sqrt();
+        let inexact = &root * &root != n;
+        let exp = (self.exp - shift) / 2;
+        Some(Decimal::with_sticky_digit(root, exp, inexact).round(context))
+    }
+
+    /// `e` raised to the number, rounded to the precision of `context`. Fails with
+    /// `InterpretError::Overflow` if the exponent of the result would be too large to store.
+    pub fn exp(&self, context: &DecimalContext) -> Result<Decimal, InterpretError<'static>> {
+        if self.coeff.is_zero() {
+            return Ok(Decimal::one());
+        }
+        if self.magnitude() > 15 {
+            return if self.coeff.is_negative() {
+                Ok(Decimal::zero())
+            } else {
+                Err(InterpretError::Overflow)
+            };
+        }
+        // Squaring the result once for every halving below multiplies its error by about x
+        let working = DecimalContext {
+            precision: context.precision + GUARD_DIGITS + self.magnitude().max(0) as usize,
+            rounding: RoundingMode::HalfEven,
+        };
+        if self.coeff.is_negative() {
+            let reciprocal = (-self.clone()).exp(&working)?;
+            return Ok(Decimal::one().div_with(&reciprocal, context).unwrap());
+        }
+
+        // e^x = (e^(x / 2^n))^(2^n), where the series converges quickly for x / 2^n < 1
+        let mut x = self.clone();
+        let mut halvings = 0;
+        let half = Decimal::new(BigInt::from(5), -1);
+        while x >= Decimal::one() {
+            x = (x * half.clone()).round(&working);
+            halvings += 1;
+        }
+        let mut sum = Decimal::one();
+        let mut term = Decimal::one();
+        for k in 1i64.. {
+            term = (term * x.clone())
+                .div_with(&Decimal::from(k), &working)
+                .unwrap();
+            if term.coeff.is_zero() || term.magnitude() < sum.magnitude() - working.precision as i64
+            {
+                break;
+            }
+            sum = (sum + term.clone()).round(&working);
+        }
+        for _ in 0..halvings {
+            sum = (sum.clone() * sum).round(&working);
+        }
+        Ok(sum.round(context))
+    }
+
+    /// The natural logarithm rounded to the precision of `context`, or `None` for numbers
+    /// that are not positive.
+    pub fn ln(&self, context: &DecimalContext) -> Option<Decimal> {
+        if !self.coeff.is_positive() {
+            return None;
+        }
+        let working = DecimalContext {
+            precision: context.precision + 2 * GUARD_DIGITS,
+            rounding: RoundingMode::HalfEven,
+        };
+        // ln(m × 10^n) = ln(m) + n ln(10), with m between 0.1 and 1
+        let n = self.magnitude();
+        let m = Decimal::new(self.coeff.clone(), self.exp - n);
+        let mut result = ln_reduced(m, &working);
+        if n != 0 {
+            let ln10 = -ln_reduced(Decimal::new(BigInt::from(1), -1), &working);
+            result = (result + ln10 * Decimal::from(n)).round(&working);
+        }
+        Some(result.round(context))
+    }
+
+    /// Raises the number to `exponent`, rounding to the precision of `context`. Whole
+    /// exponents work by repeated multiplication, and fractional ones through `exp` and `ln`,
+    /// so they need a number that is not negative.
+    pub fn pow_with(
+        &self,
+        exponent: &Decimal,
+        context: &DecimalContext,
+    ) -> Result<Decimal, InterpretError<'static>> {
+        if self.coeff.is_zero() {
+            return match exponent.coeff.signum().to_i8() {
+                Some(1) => Ok(Decimal::zero()),
+                Some(0) => Ok(Decimal::one()),
+                _ => Err(InterpretError::DivisionByZero),
+            };
+        }
+        if exponent.is_whole() {
+            // Larger exponents don't fit in an i64 anyway
+            let n = if exponent.exp > 18 {
+                None
+            } else {
+                exponent.to_integer().and_then(|n| n.to_i64())
+            };
+            let n = match n {
+                Some(n) if n.unsigned_abs() <= u32::MAX as u64 => n,
+                // Only 1 and -1 can be raised to exponents this large
+                _ if self.coeff.abs() == BigInt::from(1) && self.exp == 0 => {
+                    let odd = exponent.exp == 0 && exponent.coeff.is_odd();
+                    return Ok(if odd { self.clone() } else { Decimal::one() });
+                }
+                _ => return Err(InterpretError::Overflow),
+            };
+            let working = DecimalContext {
+                precision: context.precision + GUARD_DIGITS + digits(&BigInt::from(n)) as usize,
+                rounding: RoundingMode::HalfEven,
+            };
+            let (mut base, mut remaining, mut result) =
+                (self.clone(), n.unsigned_abs(), Decimal::one());
+            while remaining > 0 {
+                if remaining % 2 == 1 {
+                    result = (result * base.clone()).round(&working);
+                }
+                base = (base.clone() * base).round(&working);
+                remaining /= 2;
+            }
+            if n < 0 {
+                Ok(Decimal::one().div_with(&result, context).unwrap())
+            } else {
+                Ok(result.round(context))
+            }
+        } else if self.coeff.is_negative() {
+            Err(InterpretError::OutOfDomain("^"))
+        } else {
+            // An error in the logarithm is multiplied by the exponent, and grows in exp
+            let mut working = with_guard_digits(context);
+            let mut product = exponent.clone() * self.ln(&working).unwrap();
+            let extra = product.magnitude().max(0) as usize;
+            if extra > 0 {
+                working.precision += extra;
+                product = exponent.clone() * self.ln(&working).unwrap();
+            }
+            Ok(product.exp(&working)?.round(context))
+        }
+    }
+}
+
+fn with_guard_digits(context: &DecimalContext) -> DecimalContext {
+    DecimalContext {
+        precision: context.precision + GUARD_DIGITS,
+        rounding: RoundingMode::HalfEven,
+    }
+}
+
+/// The natural logarithm of `m`, which is between 0.1 and 1.
+fn ln_reduced(m: Decimal, context: &DecimalContext) -> Decimal {
+    // ln(m) = 2^k ln(m^(1 / 2^k)), where taking square roots brings m close to 1
+    let mut m = m;
+    let mut roots = 0;
+    let near = Decimal::new(BigInt::from(1), -3);
+    while (m.clone() - Decimal::one()).abs_value() > near {
+        m = m.sqrt(context).unwrap();
+        roots += 1;
+    }
+    // ln(m) = 2 atanh(z) = 2 (z + z^3/3 + z^5/5 + ...), where z = (m - 1) / (m + 1)
+    let z = (m.clone() - Decimal::one())
+        .div_with(&(m + Decimal::one()), context)
+        .unwrap();
+    let z_squared = (z.clone() * z.clone()).round(context);
+    let mut sum = z.clone();
+    let mut power = z;
+    for k in (3i64..).step_by(2) {
+        power = (power * z_squared.clone()).round(context);
+        let term = power.div_with(&Decimal::from(k), context).unwrap();
+        if term.coeff.is_zero() || term.magnitude() < sum.magnitude() - context.precision as i64 {
+            break;
+        }
+        sum = (sum + term).round(context);
+    }
+    (sum * Decimal::from(2) * Decimal::from(Pow::pow(BigInt::from(2), roots as u32))).round(context)
+}
+
+impl From<BigInt> for Decimal {
+    fn from(n: BigInt) -> Self {
+        Decimal::new(n, 0)
+    }
+}
+
+impl From<i64> for Decimal {
+    fn from(n: i64) -> Self {
+        Decimal::new(BigInt::from(n), 0)
+    }
+}
+
+impl Num for Decimal {
+    fn zero() -> Self {
+        Decimal::new(BigInt::from(0), 0)
+    }
+    fn one() -> Self {
+        Decimal::new(BigInt::from(1), 0)
+    }
+    fn is_whole(&self) -> bool {
+        self.exp >= 0
+    }
+    fn parse_literal(s: &str) -> Option<Self> {
+        s.parse().ok()
+    }
+    /// Rounds to the default `DecimalContext`, and gives 0 where `pow_with` returns an error.
+    fn pow(self, other: Self) -> Self {
+        self.pow_with(&other, &DecimalContext::default())
+            .unwrap_or_else(|_| Decimal::zero())
+    }
+
+    fn arithmetic(
+        self,
+        op: OpVal,
+        other: Self,
+        settings: &Settings,
+    ) -> Result<Self, InterpretError<'static>> {
+        let context = &settings.decimal;
+        if other.coeff.is_zero() && (op == OpVal::Div || op == OpVal::Mod) {
+            return Err(InterpretError::DivisionByZero);
+        }
+        Ok(match op {
+            OpVal::Add => (self + other).round(context),
+            OpVal::Sub => (self - other).round(context),
+            OpVal::Mul => (self * other).round(context),
+            OpVal::Div => self.div_with(&other, context).unwrap(),
+            OpVal::Mod => (self % other).round(context),
+            OpVal::Pow => self.pow_with(&other, context)?,
+            _ => unreachable!(),
+        })
+    }
+
+    fn negate(self, _: &Settings) -> Result<Self, InterpretError<'static>> {
+        Ok(-self)
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.clone() - other.clone()).coeff.cmp(&BigInt::from(0))
+    }
+}
+
+/// Parses whole numbers and decimals like `12`, `0.5`, `.5` or `5.` exactly.
+impl FromStr for Decimal {
+    type Err = ParseBigIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (whole, fraction) = s.split_once('.').unwrap_or((s, ""));
+        // "123.45" is 12345 × 10^-2. A fraction with a sign is parsed with the point left in,
+        // which fails
+        let digits = if fraction.bytes().all(|b| b.is_ascii_digit()) {
+            format!("{}{}", whole, fraction)
+        } else {
+            s.to_owned()
+        };
+        Ok(Decimal::new(digits.parse()?, -(fraction.len() as i64)))
+    }
+}
+
+/// Displays like `123.45`, or in scientific notation like `1.2e+30` if that would take many
+/// zeros.
+impl Display for Decimal {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let sign = if self.coeff.is_negative() { "-" } else { "" };
+        let digits = self.coeff.magnitude().to_string();
+        // Where the point goes in the digits
+        let point = digits.len() as i64 + self.exp;
+        if self.exp > MAX_DISPLAY_ZEROS || point < -MAX_DISPLAY_ZEROS {
+            let (first, rest) = digits.split_at(1);
+            let point = if rest.is_empty() { "" } else { "." };
+            write!(
+                f,
+                "{}{}{}{}e{:+}",
+                sign,
+                first,
+                point,
+                rest,
+                self.magnitude() - 1
+            )
+        } else if self.exp >= 0 {
+            write!(f, "{}{}{}", sign, digits, "0".repeat(self.exp as usize))
+        } else if point > 0 {
+            let (whole, fraction) = digits.split_at(point as usize);
+            write!(f, "{}{}.{}", sign, whole, fraction)
+        } else {
+            write!(f, "{}0.{}{}", sign, "0".repeat(-point as usize), digits)
+        }
+    }
+}
+
+/// The coefficients of `a` and `b` scaled to the smaller of their exponents, and that exponent.
+fn align(a: Decimal, b: Decimal) -> (BigInt, BigInt, i64) {
+    let exp = a.exp.min(b.exp);
+    (
+        a.coeff * pow10((a.exp - exp) as u64),
+        b.coeff * pow10((b.exp - exp) as u64),
+        exp,
+    )
+}
+
+impl Add for Decimal {
+    type Output = Decimal;
+    fn add(self, other: Decimal) -> Decimal {
+        let (a, b, exp) = align(self, other);
+        Decimal::new(a + b, exp)
+    }
+}
+
+impl Sub for Decimal {
+    type Output = Decimal;
+    fn sub(self, other: Decimal) -> Decimal {
+        let (a, b, exp) = align(self, other);
+        Decimal::new(a - b, exp)
+    }
+}
+
+impl Mul for Decimal {
+    type Output = Decimal;
+    fn mul(self, other: Decimal) -> Decimal {
+        Decimal::new(self.coeff * other.coeff, self.exp + other.exp)
+    }
+}
+
+impl Div for Decimal {
+    type Output = Decimal;
+    fn div(self, other: Decimal) -> Decimal {
+        self.div_with(&other, &DecimalContext::default())
+            .expect("division by zero")
+    }
+}
+
+/// The remainder of truncating division, with the sign of the dividend like `%` of `f64`.
+impl Rem for Decimal {
+    type Output = Decimal;
+    fn rem(self, other: Decimal) -> Decimal {
+        let (a, b, exp) = align(self, other);
+        Decimal::new(a % b, exp)
+    }
+}
+
+impl Neg for Decimal {
+    type Output = Decimal;
+    fn neg(self) -> Decimal {
+        Decimal {
+            coeff: -self.coeff,
+            exp: self.exp,
+        }
+    }
+}
+
+macro_rules! impl_decimal_assign_op {
+    ($assign_op:ident, $assign_method:ident, $op:tt) => {
+        impl $assign_op for Decimal {
+            fn $assign_method(&mut self, other: Decimal) {
+                *self = self.clone() $op other;
+            }
+        }
+    };
+}
+impl_decimal_assign_op!(AddAssign, add_assign, +);
+impl_decimal_assign_op!(SubAssign, sub_assign, -);
+impl_decimal_assign_op!(MulAssign, mul_assign, *);
+impl_decimal_assign_op!(DivAssign, div_assign, /);
@@ -0,0 +1,219 @@
+use crate::{
+    parse_recovering, tokenize_recovering, Error, InterpretError, Num, OwnedParseErrorCode,
+    OwnedTokenizeErrorCode, ParseError, TokenizeError, TokenizeOptions,
+};
+use std::fmt::{Display, Write};
+use std::ops::Range;
+
+const RED: &str = "\x1b[1;31m";
+const BLUE: &str = "\x1b[1;34m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+/// A secondary span in a `Diagnostic`, underlined with `-` and described by `message`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Label {
+    pub span: Range<usize>,
+    pub message: String,
+}
+
+/// An error prepared for display to a user, pointing into the input it came from. Spans are
+/// counted in characters, like the spans of tokens.
+///
+/// Every error type converts into a `Diagnostic`, and `render` draws it with the offending
+/// input underlined:
+/// ```text
+/// error: Unexpected ")".
+///   |
+/// 1 | 2 + 3)
+///   |      ^
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub message: String,
+    /// Underlined with `^`. Most errors found while interpreting have no span.
+    pub span: Option<Range<usize>>,
+    pub labels: Vec<Label>,
+    pub help: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn new(message: String, span: Option<Range<usize>>) -> Diagnostic {
+        Diagnostic {
+            message,
+            span,
+            labels: Vec::new(),
+            help: None,
+        }
+    }
+
+    pub fn with_label(mut self, span: Range<usize>, message: String) -> Diagnostic {
+        self.labels.push(Label { span, message });
+        self
+    }
+
+    pub fn with_help(mut self, help: String) -> Diagnostic {
+        self.help = Some(help);
+        self
+    }
+
+    /// Renders the diagnostic with the lines of `input` it points at. `color` adds ANSI escape
+    /// codes for terminals.
+    pub fn render(&self, input: &str, color: bool) -> String {
+        let paint = |code: &'static str| if color { code } else { "" };
+        let mut out = String::new();
+        let _ = write!(
+            out,
+            "{}error{}: {}{}{}",
+            paint(RED),
+            paint(RESET),
+            paint(BOLD),
+            self.message,
+            paint(RESET)
+        );
+
+        // Every underline to draw: (span, character, color, message)
+        let mut marks: Vec<(&Range<usize>, char, &'static str, &str)> = Vec::new();
+        if let Some(span) = &self.span {
+            marks.push((span, '^', RED, ""));
+        }
+        for label in &self.labels {
+            marks.push((&label.span, '-', BLUE, &label.message));
+        }
+
+        // Character offset where each line starts, and its contents
+        let mut lines = Vec::new();
+        let mut start = 0;
+        for line in input.split('\n') {
+            lines.push((start, line.trim_end_matches('\r')));
+            start += line.chars().count() + 1;
+        }
+
+        let marked_lines: Vec<usize> = (0..lines.len())
+            .filter(|&i| {
+                marks
+                    .iter()
+                    .any(|(span, ..)| line_segment(span, lines[i], i + 1 == lines.len()).is_some())
+            })
+            .collect();
+
+        let gutter = marked_lines.last().map_or(0, |i| (i + 1).to_string().len());
+        let pad = " ".repeat(gutter);
+        if !marked_lines.is_empty() {
+            let _ = write!(out, "\n{} {}|{}", pad, paint(BLUE), paint(RESET));
+        }
+        for &i in &marked_lines {
+            let _ = write!(
+                out,
+                "\n{}{:>w$} |{} {}",
+                paint(BLUE),
+                i + 1,
+                paint(RESET),
+                lines[i].1,
+                w = gutter
+            );
+            for (span, c, code, message) in &marks {
+                if let Some(segment) = line_segment(span, lines[i], i + 1 == lines.len()) {
+                    let _ = write!(
+                        out,
+                        "\n{} {}|{} {}{}{}{}{}",
+                        pad,
+                        paint(BLUE),
+                        paint(RESET),
+                        " ".repeat(segment.start),
+                        paint(code),
+                        c.to_string().repeat(segment.len().max(1)),
+                        if message.is_empty() { "" } else { " " },
+                        message
+                    );
+                    out.push_str(paint(RESET));
+                }
+            }
+        }
+        if let Some(help) = &self.help {
+            let _ = write!(
+                out,
+                "\n{} {}={} {}help{}: {}",
+                pad,
+                paint(BLUE),
+                paint(RESET),
+                paint(BOLD),
+                paint(RESET),
+                help
+            );
+        }
+        out
+    }
+}
+
+/// Checks `input` for every tokenize and parse error at once, without evaluating it. The
+/// diagnostics are ordered by where they occur in the input.
+pub fn diagnose<N: Num + Display>(input: &str) -> Vec<Diagnostic> {
+    let (tokens, tokenize_errors) = tokenize_recovering::<N>(input, TokenizeOptions::default());
+    let (_, parse_errors) = parse_recovering(&tokens);
+    let mut diagnostics: Vec<Diagnostic> = tokenize_errors
+        .iter()
+        .map(Diagnostic::from)
+        .chain(parse_errors.iter().map(Diagnostic::from))
+        .collect();
+    diagnostics.sort_by_key(|d| d.span.as_ref().map(|span| span.start));
+    diagnostics
+}
+
+/// Returns the columns of `span` that fall on `line`. A span may point one character past the
+/// end of a line, e.g. at the end of the input.
+fn line_segment(span: &Range<usize>, line: (usize, &str), last: bool) -> Option<Range<usize>> {
+    let (line_start, text) = line;
+    let line_end = line_start + text.chars().count();
+    // Even an empty span marks one character
+    let end = span.end.max(span.start + 1);
+    if end <= line_start || (span.start > line_end && !last) {
+        return None;
+    }
+    let start = span.start.max(line_start).min(line_end);
+    // A span continuing onto the next line is cut at the end of this one
+    let end = if end > line_end + 1 { line_end } else { end };
+    Some((start - line_start)..(end.max(start + 1) - line_start))
+}
+
+impl<'input> From<&TokenizeError<'input>> for Diagnostic {
+    fn from(err: &TokenizeError<'input>) -> Self {
+        Diagnostic::from(&Error::from(err.clone()))
+    }
+}
+
+impl<'t, N: Num + Display> From<&ParseError<'t, N>> for Diagnostic {
+    fn from(err: &ParseError<'t, N>) -> Self {
+        Diagnostic::from(&Error::from(err.clone()))
+    }
+}
+
+impl<'expr> From<&InterpretError<'expr>> for Diagnostic {
+    fn from(err: &InterpretError<'expr>) -> Self {
+        Diagnostic::from(&Error::from(err.clone()))
+    }
+}
+
+impl From<&Error> for Diagnostic {
+    fn from(err: &Error) -> Self {
+        let diagnostic = Diagnostic::new(err.to_string(), err.span());
+        match err {
+            Error::Tokenize(err) => match err.code {
+                OwnedTokenizeErrorCode::InvalidNumber(_) => {
+                    diagnostic.with_help(String::from("Numbers are written like 12, 0.5 or .5."))
+                }
+                OwnedTokenizeErrorCode::UnrecognizedChar(_) => diagnostic,
+            },
+            Error::Parse(err) => match err.code {
+                OwnedParseErrorCode::ExpectedClosingParen => {
+                    diagnostic.with_help(String::from("Close the parentheses with \")\"."))
+                }
+                OwnedParseErrorCode::UnexpectedEOF => {
+                    diagnostic.with_help(String::from("The expression is incomplete."))
+                }
+                _ => diagnostic,
+            },
+            Error::Interpret(_) => diagnostic,
+        }
+    }
+}
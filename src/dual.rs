@@ -0,0 +1,284 @@
+use crate::{
+    real_via_derivatives, Expr, InterpretError, Interpreter, Num, Real, Settings, Variant,
+};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt::{self, Display, Formatter};
+use std::num::ParseFloatError;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Rem, Sub, SubAssign};
+use std::str::FromStr;
+
+/// A dual number for forward-mode automatic differentiation: a value along with its partial
+/// derivatives with respect to any number of variables, which every operator and `Real`
+/// function carries through by the chain rule. Evaluating an expression once gives its value
+/// and gradient, even through functions registered in Rust, as long as they are built from
+/// `Num` and `Real` operations. `Interpreter::gradient` seeds the variables.
+///
+/// Missing partial derivatives are zero, so constants have an empty `grad`. Numbers compare by
+/// value, and are equal if their values and derivatives are. A number `is_whole` if its value
+/// is. Numbers with derivatives display as `value + [derivatives]ε`.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Dual {
+    pub value: f64,
+    pub grad: Vec<f64>,
+}
+
+impl Dual {
+    pub fn constant(value: f64) -> Dual {
+        Dual {
+            value,
+            grad: Vec::new(),
+        }
+    }
+
+    /// The variable `index` of `count` variables, whose derivative with respect to itself is 1.
+    pub fn variable(value: f64, index: usize, count: usize) -> Dual {
+        let mut grad = vec![0.0; count.max(index + 1)];
+        grad[index] = 1.0;
+        Dual { value, grad }
+    }
+
+    /// The partial derivative with respect to the variable `index`.
+    pub fn derivative(&self, index: usize) -> f64 {
+        self.grad.get(index).cloned().unwrap_or(0.0)
+    }
+
+    fn is_constant(&self) -> bool {
+        self.grad.iter().all(|d| *d == 0.0)
+    }
+
+    /// The number `value`, which depends on `self` with the derivative `d_self` and on `other`
+    /// with the derivative `d_other`.
+    fn combine(&self, d_self: f64, other: &Dual, d_other: f64, value: f64) -> Dual {
+        // Zero derivatives stay zero, even where the derivative of the function is infinite
+        let scale = |d: f64, partial: f64| if partial == 0.0 { 0.0 } else { d * partial };
+        let grad = (0..self.grad.len().max(other.grad.len()))
+            .map(|i| scale(d_self, self.derivative(i)) + scale(d_other, other.derivative(i)))
+            .collect();
+        Dual { value, grad }
+    }
+
+    /// Applies the function `f` with the derivative `derivative`.
+    fn apply(&self, f: fn(f64) -> f64, derivative: fn(f64) -> f64) -> Dual {
+        let d = if self.is_constant() {
+            0.0
+        } else {
+            derivative(self.value)
+        };
+        self.combine(d, &Dual::default(), 0.0, f(self.value))
+    }
+}
+
+impl From<f64> for Dual {
+    fn from(value: f64) -> Self {
+        Dual::constant(value)
+    }
+}
+
+impl Num for Dual {
+    fn zero() -> Self {
+        Dual::constant(0.0)
+    }
+    fn one() -> Self {
+        Dual::constant(1.0)
+    }
+    fn is_whole(&self) -> bool {
+        self.value.fract() == 0.0
+    }
+    fn parse_literal(s: &str) -> Option<Self> {
+        s.parse().ok()
+    }
+    fn pow(self, other: Self) -> Self {
+        let value = self.value.powf(other.value);
+        // The derivatives are only needed, and only defined, where there are derivatives
+        let d_self = if self.is_constant() {
+            0.0
+        } else {
+            other.value * self.value.powf(other.value - 1.0)
+        };
+        let d_other = if other.is_constant() {
+            0.0
+        } else {
+            value * self.value.ln()
+        };
+        self.combine(d_self, &other, d_other, value)
+    }
+    fn is_finite(&self) -> bool {
+        self.value.is_finite() && self.grad.iter().all(|d| d.is_finite())
+    }
+
+    fn is_atomic(&self) -> bool {
+        self.is_constant()
+    }
+
+    fn negate(self, _: &Settings) -> Result<Self, InterpretError<'static>> {
+        Ok(-self)
+    }
+
+    fn degrees(self, settings: &Settings) -> Result<Self, InterpretError<'static>> {
+        Ok(settings.angle_mode.from_degrees(self))
+    }
+}
+
+impl Real for Dual {
+    fn from_f64(x: f64) -> Self {
+        Dual::constant(x)
+    }
+    fn to_f64(&self) -> f64 {
+        self.value
+    }
+
+    real_via_derivatives!();
+}
+
+impl PartialEq for Dual {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+            && (0..self.grad.len().max(other.grad.len()))
+                .all(|i| self.derivative(i) == other.derivative(i))
+    }
+}
+
+impl PartialOrd for Dual {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.value.partial_cmp(&other.value)
+    }
+}
+
+impl FromStr for Dual {
+    type Err = ParseFloatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Dual::constant(s.parse()?))
+    }
+}
+
+impl Display for Dual {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.value)?;
+        if !self.is_constant() {
+            write!(f, " + [")?;
+            for (i, d) in self.grad.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{}", d)?;
+            }
+            write!(f, "]ε")?;
+        }
+        Ok(())
+    }
+}
+
+impl Add for Dual {
+    type Output = Dual;
+    fn add(self, other: Dual) -> Dual {
+        self.combine(1.0, &other, 1.0, self.value + other.value)
+    }
+}
+
+impl Sub for Dual {
+    type Output = Dual;
+    fn sub(self, other: Dual) -> Dual {
+        self.combine(1.0, &other, -1.0, self.value - other.value)
+    }
+}
+
+impl Mul for Dual {
+    type Output = Dual;
+    fn mul(self, other: Dual) -> Dual {
+        self.combine(other.value, &other, self.value, self.value * other.value)
+    }
+}
+
+impl Div for Dual {
+    type Output = Dual;
+    fn div(self, other: Dual) -> Dual {
+        let quotient = self.value / other.value;
+        self.combine(1.0 / other.value, &other, -quotient / other.value, quotient)
+    }
+}
+
+/// The remainder of truncating division, like `%` of `f64`.
+impl Rem for Dual {
+    type Output = Dual;
+    fn rem(self, other: Dual) -> Dual {
+        let quotient = (self.value / other.value).trunc();
+        self.combine(1.0, &other, -quotient, self.value % other.value)
+    }
+}
+
+impl Neg for Dual {
+    type Output = Dual;
+    fn neg(self) -> Dual {
+        self.apply(|x| -x, |_| -1.0)
+    }
+}
+
+macro_rules! impl_dual_assign_op {
+    ($assign_op:ident, $assign_method:ident, $op:tt) => {
+        impl $assign_op for Dual {
+            fn $assign_method(&mut self, other: Dual) {
+                *self = self.clone() $op other;
+            }
+        }
+    };
+}
+impl_dual_assign_op!(AddAssign, add_assign, +);
+impl_dual_assign_op!(SubAssign, sub_assign, -);
+impl_dual_assign_op!(MulAssign, mul_assign, *);
+impl_dual_assign_op!(DivAssign, div_assign, /);
+
+impl Interpreter<Dual> {
+    /// Evaluates `expr` at the current values of the variables `vars`, returning its value and
+    /// its gradient with respect to them, in order. The variables are seeded with derivatives
+    /// for the evaluation, and restored afterwards. Naming a variable twice is a `TypeMismatch`.
+    pub fn gradient<'expr>(
+        &mut self,
+        expr: &'expr Expr<Dual>,
+        vars: &[&'expr str],
+    ) -> Result<Dual, InterpretError<'expr>> {
+        let mut originals = Vec::with_capacity(vars.len());
+        for (i, id) in vars.iter().enumerate() {
+            if vars[..i].contains(id) {
+                return Err(InterpretError::TypeMismatch(
+                    "gradient",
+                    String::from("distinct variables"),
+                    format!("{:?} twice", id),
+                    0..0,
+                ));
+            }
+            match self.vars.get(*id) {
+                Some(Variant::Num(n)) => originals.push(n.clone()),
+                Some(var @ (Variant::List(_) | Variant::Matrix(_))) => {
+                    let found = match var {
+                        Variant::List(list) => format!("a list of {}", list.len()),
+                        Variant::Matrix(matrix) => matrix.describe(),
+                        _ => unreachable!(),
+                    };
+                    return Err(InterpretError::TypeMismatch(
+                        id,
+                        String::from("a number"),
+                        found,
+                        0..0,
+                    ));
+                }
+                Some(_) => return Err(InterpretError::FunctionNameUsedLikeVar(id)),
+                None => return Err(InterpretError::VarDoesNotExist(id)),
+            }
+        }
+        for (i, (id, original)) in vars.iter().zip(&originals).enumerate() {
+            let seeded = Dual::variable(original.value, i, vars.len());
+            self.set_var(id.to_string(), Variant::Num(seeded));
+        }
+        let result = self.eval(expr);
+        for (id, original) in vars.iter().zip(originals) {
+            self.set_var(id.to_string(), Variant::Num(original));
+        }
+        let mut result = result?;
+        result.grad.resize(vars.len(), 0.0);
+        Ok(result)
+    }
+}
@@ -0,0 +1,275 @@
+use crate::{InterpretError, Num, ParseError, ParseErrorCode, TokenizeError, TokenizeErrorCode};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::fmt::{self, Display, Formatter};
+use std::ops::Range;
+
+/// An error from any stage of evaluating an input string. Unlike `TokenizeError`, `ParseError`
+/// and `InterpretError`, it owns its data and does not borrow from the input, so it can be
+/// returned with `?` and stored freely. It displays as the error of the stage that failed, so it
+/// has no `source()` of its own, and error chains print the message once.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Error {
+    Tokenize(OwnedTokenizeError),
+    Parse(OwnedParseError),
+    Interpret(OwnedInterpretError),
+}
+
+impl Error {
+    /// Returns where in the input the error occurred, if known.
+    pub fn span(&self) -> Option<Range<usize>> {
+        match self {
+            Error::Tokenize(err) => Some(err.span.clone()),
+            Error::Parse(err) => Some(err.span.clone()),
+            Error::Interpret(err) => err.borrowed().span(),
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Error::Tokenize(err) => write!(f, "{}", err),
+            Error::Parse(err) => write!(f, "{}", err),
+            Error::Interpret(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// An owned `TokenizeError`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct OwnedTokenizeError {
+    pub code: OwnedTokenizeErrorCode,
+    pub span: Range<usize>,
+}
+
+/// An owned `TokenizeErrorCode`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum OwnedTokenizeErrorCode {
+    InvalidNumber(String),
+    UnrecognizedChar(char),
+}
+
+impl OwnedTokenizeErrorCode {
+    fn borrowed(&self) -> TokenizeErrorCode<'_> {
+        match self {
+            OwnedTokenizeErrorCode::InvalidNumber(s) => {
+                TokenizeErrorCode::InvalidNumber(Cow::Borrowed(s))
+            }
+            OwnedTokenizeErrorCode::UnrecognizedChar(c) => TokenizeErrorCode::UnrecognizedChar(*c),
+        }
+    }
+}
+
+impl Display for OwnedTokenizeError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.code.borrowed())
+    }
+}
+
+impl std::error::Error for OwnedTokenizeError {}
+
+impl<'input> From<TokenizeError<'input>> for OwnedTokenizeError {
+    fn from(err: TokenizeError<'input>) -> Self {
+        OwnedTokenizeError {
+            code: match err.code {
+                TokenizeErrorCode::InvalidNumber(s) => {
+                    OwnedTokenizeErrorCode::InvalidNumber(s.to_string())
+                }
+                TokenizeErrorCode::UnrecognizedChar(c) => {
+                    OwnedTokenizeErrorCode::UnrecognizedChar(c)
+                }
+            },
+            span: err.span,
+        }
+    }
+}
+
+/// An owned `ParseError`. An unexpected token is kept as the text it was displayed as.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct OwnedParseError {
+    pub code: OwnedParseErrorCode,
+    pub span: Range<usize>,
+}
+
+/// An owned `ParseErrorCode`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum OwnedParseErrorCode {
+    ExpectedValue,
+    ExpectedClosingParen,
+    UnexpectedToken(String),
+    UnexpectedEOF,
+}
+
+impl Display for OwnedParseErrorCode {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            OwnedParseErrorCode::ExpectedValue => write!(f, "Expected a value."),
+            OwnedParseErrorCode::ExpectedClosingParen => {
+                write!(f, "Expected a closing parenthesis.")
+            }
+            OwnedParseErrorCode::UnexpectedToken(tok) => write!(f, "Unexpected {:?}.", tok),
+            OwnedParseErrorCode::UnexpectedEOF => write!(f, "Unexpected end of input."),
+        }
+    }
+}
+
+impl Display for OwnedParseError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.code)
+    }
+}
+
+impl std::error::Error for OwnedParseError {}
+
+impl<'t, N: Num + Display> From<&ParseErrorCode<'t, N>> for OwnedParseErrorCode {
+    fn from(code: &ParseErrorCode<'t, N>) -> Self {
+        match code {
+            ParseErrorCode::ExpectedValue => OwnedParseErrorCode::ExpectedValue,
+            ParseErrorCode::ExpectedClosingParen => OwnedParseErrorCode::ExpectedClosingParen,
+            ParseErrorCode::UnexpectedToken(tok) => {
+                OwnedParseErrorCode::UnexpectedToken(tok.value.to_string())
+            }
+            ParseErrorCode::UnexpectedEOF => OwnedParseErrorCode::UnexpectedEOF,
+        }
+    }
+}
+
+impl<'t, N: Num + Display> From<ParseError<'t, N>> for OwnedParseError {
+    fn from(err: ParseError<'t, N>) -> Self {
+        OwnedParseError {
+            code: (&err.code).into(),
+            span: err.span,
+        }
+    }
+}
+
+/// An owned `InterpretError`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum OwnedInterpretError {
+    TooFewArgs(String, usize),
+    TooManyArgs(String, usize),
+    VarDoesNotExist(String),
+    VarIsNotFunction(String),
+    FunctionNameUsedLikeVar(String),
+    InvalidExpr,
+    DivisionByZero,
+    Overflow,
+    NegativeExponent,
+    NonFinite(String, Range<usize>),
+    OutOfDomain(String),
+    DimensionMismatch(String, String, String, Range<usize>),
+    ShapeMismatch(String, String, String, Range<usize>),
+    TypeMismatch(String, String, String, Range<usize>),
+    IndexOutOfRange(usize, Range<usize>),
+    NotANumber(String),
+}
+
+impl OwnedInterpretError {
+    fn borrowed(&self) -> InterpretError<'_> {
+        match self {
+            OwnedInterpretError::TooFewArgs(id, n) => InterpretError::TooFewArgs(id, *n),
+            OwnedInterpretError::TooManyArgs(id, n) => InterpretError::TooManyArgs(id, *n),
+            OwnedInterpretError::VarDoesNotExist(id) => InterpretError::VarDoesNotExist(id),
+            OwnedInterpretError::VarIsNotFunction(id) => InterpretError::VarIsNotFunction(id),
+            OwnedInterpretError::FunctionNameUsedLikeVar(id) => {
+                InterpretError::FunctionNameUsedLikeVar(id)
+            }
+            OwnedInterpretError::InvalidExpr => InterpretError::InvalidExpr,
+            OwnedInterpretError::DivisionByZero => InterpretError::DivisionByZero,
+            OwnedInterpretError::Overflow => InterpretError::Overflow,
+            OwnedInterpretError::NegativeExponent => InterpretError::NegativeExponent,
+            OwnedInterpretError::NonFinite(op, span) => InterpretError::NonFinite(op, span.clone()),
+            OwnedInterpretError::OutOfDomain(id) => InterpretError::OutOfDomain(id),
+            OwnedInterpretError::DimensionMismatch(op, lhs, rhs, span) => {
+                InterpretError::DimensionMismatch(op, lhs.clone(), rhs.clone(), span.clone())
+            }
+            OwnedInterpretError::ShapeMismatch(op, lhs, rhs, span) => {
+                InterpretError::ShapeMismatch(op, lhs.clone(), rhs.clone(), span.clone())
+            }
+            OwnedInterpretError::TypeMismatch(op, expected, found, span) => {
+                InterpretError::TypeMismatch(op, expected.clone(), found.clone(), span.clone())
+            }
+            OwnedInterpretError::IndexOutOfRange(len, span) => {
+                InterpretError::IndexOutOfRange(*len, span.clone())
+            }
+            OwnedInterpretError::NotANumber(found) => InterpretError::NotANumber(found.clone()),
+        }
+    }
+}
+
+impl Display for OwnedInterpretError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.borrowed())
+    }
+}
+
+impl std::error::Error for OwnedInterpretError {}
+
+impl<'expr> From<InterpretError<'expr>> for OwnedInterpretError {
+    fn from(err: InterpretError<'expr>) -> Self {
+        match err {
+            InterpretError::TooFewArgs(id, n) => OwnedInterpretError::TooFewArgs(id.to_owned(), n),
+            InterpretError::TooManyArgs(id, n) => {
+                OwnedInterpretError::TooManyArgs(id.to_owned(), n)
+            }
+            InterpretError::VarDoesNotExist(id) => {
+                OwnedInterpretError::VarDoesNotExist(id.to_owned())
+            }
+            InterpretError::VarIsNotFunction(id) => {
+                OwnedInterpretError::VarIsNotFunction(id.to_owned())
+            }
+            InterpretError::FunctionNameUsedLikeVar(id) => {
+                OwnedInterpretError::FunctionNameUsedLikeVar(id.to_owned())
+            }
+            InterpretError::InvalidExpr => OwnedInterpretError::InvalidExpr,
+            InterpretError::DivisionByZero => OwnedInterpretError::DivisionByZero,
+            InterpretError::Overflow => OwnedInterpretError::Overflow,
+            InterpretError::NegativeExponent => OwnedInterpretError::NegativeExponent,
+            InterpretError::NonFinite(op, span) => {
+                OwnedInterpretError::NonFinite(op.to_owned(), span)
+            }
+            InterpretError::OutOfDomain(id) => OwnedInterpretError::OutOfDomain(id.to_owned()),
+            InterpretError::DimensionMismatch(op, lhs, rhs, span) => {
+                OwnedInterpretError::DimensionMismatch(op.to_owned(), lhs, rhs, span)
+            }
+            InterpretError::ShapeMismatch(op, lhs, rhs, span) => {
+                OwnedInterpretError::ShapeMismatch(op.to_owned(), lhs, rhs, span)
+            }
+            InterpretError::TypeMismatch(op, expected, found, span) => {
+                OwnedInterpretError::TypeMismatch(op.to_owned(), expected, found, span)
+            }
+            InterpretError::IndexOutOfRange(len, span) => {
+                OwnedInterpretError::IndexOutOfRange(len, span)
+            }
+            InterpretError::NotANumber(found) => OwnedInterpretError::NotANumber(found),
+        }
+    }
+}
+
+impl<'input> From<TokenizeError<'input>> for Error {
+    fn from(err: TokenizeError<'input>) -> Self {
+        Error::Tokenize(err.into())
+    }
+}
+
+impl<'t, N: Num + Display> From<ParseError<'t, N>> for Error {
+    fn from(err: ParseError<'t, N>) -> Self {
+        Error::Parse(err.into())
+    }
+}
+
+impl<'expr> From<InterpretError<'expr>> for Error {
+    fn from(err: InterpretError<'expr>) -> Self {
+        Error::Interpret(err.into())
+    }
+}
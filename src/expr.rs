@@ -1,11 +1,139 @@
 use crate::{Num, OpVal};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::fmt::{self, Display, Formatter};
+use std::ops::Range;
 
+/// The spans of `FuncOrVarMul`, `Neg`, `Op` and `Degrees` locate the call, the `-`, the operator
+/// and the `°` in the input, so errors from evaluating them can point there. The spans of `List`
+/// and `Index` cover the brackets and what is between them.
+///
+/// Names are borrowed from the tokens when parsing, and owned when deserialized, so an
+/// `Expr<'static, N>` can be read from any source, like a file.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Expr<'input, N: Num> {
     Eq(Box<Expr<'input, N>>, Box<Expr<'input, N>>),
-    FuncOrVarMul(&'input str, Vec<Expr<'input, N>>),
-    Neg(Box<Expr<'input, N>>),
-    Num(&'input N),
-    Op(OpVal, Box<Expr<'input, N>>, Box<Expr<'input, N>>),
-    Var(&'input str),
+    FuncOrVarMul(Cow<'input, str>, Vec<Expr<'input, N>>, Range<usize>),
+    /// The list indexed by `list[index]`, and the index.
+    Index(Box<Expr<'input, N>>, Box<Expr<'input, N>>, Range<usize>),
+    /// A list literal like `[1, 2, 3]`.
+    List(Vec<Expr<'input, N>>, Range<usize>),
+    Neg(Box<Expr<'input, N>>, Range<usize>),
+    Num(N),
+    Op(
+        OpVal,
+        Box<Expr<'input, N>>,
+        Box<Expr<'input, N>>,
+        Range<usize>,
+    ),
+    Var(Cow<'input, str>),
+    /// An angle in degrees, like `30°`, which is converted to the angle mode when evaluated.
+    Degrees(Box<Expr<'input, N>>, Range<usize>),
+    /// A part of the input that could not be parsed, left by `parse_recovering`.
+    Error,
+}
+
+impl<'input, N: Num + Display> Expr<'input, N> {
+    /// How tightly the expression binds when printed as an operand. Operands with a lower
+    /// precedence than their position requires are wrapped in parentheses.
+    fn precedence(&self) -> u8 {
+        match self {
+            // A negation swallows the rest of the expression when parsed, so it is always
+            // parenthesized as an operand. The same goes for negative numbers, and numbers
+            // that display as several terms.
+            Expr::Neg(..) => 0,
+            Expr::Num(n) if !n.is_atomic() || n.to_string().starts_with('-') => 0,
+            Expr::Eq(..) => 1,
+            Expr::Op(OpVal::To, ..) => 2,
+            Expr::Op(OpVal::Add, ..)
+            | Expr::Op(OpVal::Sub, ..)
+            | Expr::Op(OpVal::PlusMinus, ..) => 3,
+            Expr::Op(OpVal::Pow, ..) => 5,
+            Expr::Op(..) => 4,
+            Expr::Degrees(..)
+            | Expr::FuncOrVarMul(..)
+            | Expr::Index(..)
+            | Expr::List(..)
+            | Expr::Num(_)
+            | Expr::Var(_)
+            | Expr::Error => 6,
+        }
+    }
+
+    fn fmt_operand(&self, f: &mut Formatter, min_precedence: u8) -> fmt::Result {
+        if self.precedence() < min_precedence {
+            write!(f, "({})", self)
+        } else {
+            write!(f, "{}", self)
+        }
+    }
+}
+
+/// Prints the expression in a form that parses back into the same tree, e.g. `3*x^2 + 2*x + 1`.
+impl<'input, N: Num + Display> Display for Expr<'input, N> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Expr::Eq(lhs, rhs) => {
+                lhs.fmt_operand(f, 1)?;
+                write!(f, " = ")?;
+                rhs.fmt_operand(f, 2)
+            }
+            Expr::FuncOrVarMul(id, args, _) => {
+                write!(f, "{}(", id)?;
+                fmt_list(f, args)?;
+                write!(f, ")")
+            }
+            Expr::Index(list, index, _) => {
+                list.fmt_operand(f, 6)?;
+                write!(f, "[{}]", index)
+            }
+            Expr::List(items, _) => {
+                write!(f, "[")?;
+                fmt_list(f, items)?;
+                write!(f, "]")
+            }
+            Expr::Neg(expr, _) => {
+                write!(f, "-")?;
+                expr.fmt_operand(f, 6)
+            }
+            Expr::Num(n) => write!(f, "{}", n),
+            Expr::Op(op, lhs, rhs, _) => {
+                let (symbol, precedence) = match op {
+                    OpVal::Add => (" + ", 3),
+                    OpVal::Sub => (" - ", 3),
+                    OpVal::Mul => ("*", 4),
+                    OpVal::Div => ("/", 4),
+                    OpVal::Mod => ("%", 4),
+                    OpVal::Pow => ("^", 5),
+                    OpVal::Eq => (" = ", 1),
+                    OpVal::Exclaim => ("!", 6),
+                    OpVal::Degree => ("°", 6),
+                    OpVal::PlusMinus => (" ± ", 3),
+                    OpVal::To => (" to ", 2),
+                };
+                lhs.fmt_operand(f, precedence)?;
+                write!(f, "{}", symbol)?;
+                rhs.fmt_operand(f, precedence + 1)
+            }
+            Expr::Var(id) => write!(f, "{}", id),
+            Expr::Degrees(expr, _) => {
+                expr.fmt_operand(f, 6)?;
+                write!(f, "°")
+            }
+            Expr::Error => write!(f, "?"),
+        }
+    }
+}
+
+/// Prints expressions separated by commas.
+fn fmt_list<N: Num + Display>(f: &mut Formatter, exprs: &[Expr<N>]) -> fmt::Result {
+    for (i, expr) in exprs.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{}", expr)?;
+    }
+    Ok(())
 }
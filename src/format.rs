@@ -0,0 +1,373 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+/// A fraction approximating a number, as found by `best_fraction`. The denominator is positive
+/// and the fraction is in lowest terms.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Fraction {
+    pub numer: i64,
+    pub denom: u64,
+    /// Whether the fraction is exactly the number it approximates, as an `f64`.
+    pub exact: bool,
+}
+
+impl Fraction {
+    pub fn to_f64(self) -> f64 {
+        self.numer as f64 / self.denom as f64
+    }
+}
+
+/// Displays `numer/denom`, or only the numerator of a whole number.
+impl Display for Fraction {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if self.denom == 1 {
+            write!(f, "{}", self.numer)
+        } else {
+            write!(f, "{}/{}", self.numer, self.denom)
+        }
+    }
+}
+
+/// Approximates `x` by a fraction with a denominator of at most `max_denominator` that is
+/// within `tolerance` of it, or returns `None` if there is none. The fraction is the first
+/// convergent or semiconvergent of the continued fraction of `x` that is close enough, so it is
+/// the simplest in most cases: `0.333333` gives `1/3` with a tolerance of `1e-6`, and `0.125`
+/// gives `1/8` exactly.
+pub fn best_fraction(x: f64, max_denominator: u64, tolerance: f64) -> Option<Fraction> {
+    if !x.is_finite() || max_denominator == 0 {
+        return None;
+    }
+    let y = x.abs();
+    let close_enough = |h: u64, k: u64| (h as f64 / k as f64 - y).abs() <= tolerance;
+    // The last two convergents h/k, starting with 0/1 and 1/0
+    let (mut h0, mut h1, mut k0, mut k1) = (0u64, 1u64, 1u64, 0u64);
+    let mut r = y;
+    loop {
+        if r >= u64::MAX as f64 {
+            return None;
+        }
+        let a = r.floor() as u64;
+        // The semiconvergents (t h1 + h0) / (t k1 + k0) approach x as t goes up to a, so the
+        // first one close enough is found by bisection, among those with small denominators
+        let max_t = match (max_denominator - k0).checked_div(k1) {
+            Some(limit) => a.min(limit),
+            None => a,
+        };
+        let semiconvergent = |t: u64| {
+            let h = t.checked_mul(h1)?.checked_add(h0)?;
+            let k = t.checked_mul(k1)?.checked_add(k0)?;
+            Some((h, k))
+        };
+        let (h, k) = semiconvergent(max_t)?;
+        if close_enough(h, k) {
+            let (mut lo, mut hi) = (0, max_t);
+            while lo < hi {
+                let mid = lo + (hi - lo) / 2;
+                let (h, k) = semiconvergent(mid)?;
+                if close_enough(h, k) {
+                    hi = mid;
+                } else {
+                    lo = mid + 1;
+                }
+            }
+            let (h, k) = semiconvergent(lo)?;
+            let numer = i64::try_from(h).ok()?;
+            return Some(Fraction {
+                numer: if x < 0.0 { -numer } else { numer },
+                denom: k,
+                exact: h as f64 / k as f64 == y,
+            });
+        } else if max_t < a {
+            // The next convergent's denominator is too large
+            return None;
+        }
+        (h0, h1, k0, k1) = (h1, h, k1, k);
+        let fract = r - a as f64;
+        if fract == 0.0 {
+            return None;
+        }
+        r = 1.0 / fract;
+    }
+}
+
+/// How `NumberFormat` writes the digits of a number.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Notation {
+    /// The digits the number type displays, e.g. `1234.5`.
+    #[default]
+    Plain,
+    /// A fixed number of digits after the decimal mark, e.g. `1234.50` with 2.
+    Fixed(usize),
+    /// A number of significant digits, e.g. `1200` with 2.
+    Significant(usize),
+    /// One digit before the decimal mark and an exponent, e.g. `1.2345e3`, with every digit or
+    /// the given number of digits after the decimal mark.
+    Scientific(Option<usize>),
+    /// An exponent that is a multiple of 3, written as an SI prefix where there is one, e.g.
+    /// `1.2345k` or `12.5µ`, with every digit or the given number of digits after the decimal
+    /// mark.
+    Engineering(Option<usize>),
+}
+
+/// Reads the names `plain`, `fix<digits>`, `sig<digits>`, `sci` or `eng`, optionally followed
+/// by digits, which `Display` writes as well.
+impl FromStr for Notation {
+    type Err = ParseNotationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let split = s.find(|c: char| c.is_ascii_digit()).unwrap_or(s.len());
+        let digits = match &s[split..] {
+            "" => None,
+            digits => Some(
+                digits
+                    .parse()
+                    .map_err(|_| ParseNotationError(s.to_owned()))?,
+            ),
+        };
+        Ok(match (&s[..split], digits) {
+            ("plain", None) => Notation::Plain,
+            ("fix", Some(digits)) => Notation::Fixed(digits),
+            ("sig", Some(digits)) if digits > 0 => Notation::Significant(digits),
+            ("sci", digits) => Notation::Scientific(digits),
+            ("eng", digits) => Notation::Engineering(digits),
+            _ => return Err(ParseNotationError(s.to_owned())),
+        })
+    }
+}
+
+impl Display for Notation {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Notation::Plain => write!(f, "plain"),
+            Notation::Fixed(digits) => write!(f, "fix{}", digits),
+            Notation::Significant(digits) => write!(f, "sig{}", digits),
+            Notation::Scientific(None) => write!(f, "sci"),
+            Notation::Scientific(Some(digits)) => write!(f, "sci{}", digits),
+            Notation::Engineering(None) => write!(f, "eng"),
+            Notation::Engineering(Some(digits)) => write!(f, "eng{}", digits),
+        }
+    }
+}
+
+/// A string that does not name a `Notation`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ParseNotationError(pub String);
+
+impl Display for ParseNotationError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Unknown notation {:?}. Try plain, fix<digits>, sig<digits>, sci or eng.",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ParseNotationError {}
+
+/// How to write numbers for people to read: the notation, a separator between groups of three
+/// digits before the decimal mark, and the decimal mark, so `1234567.5` can be written as
+/// `1,234,567.50`, `1.234.567,5` or `1.2345675M`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NumberFormat {
+    pub notation: Notation,
+    pub group_separator: Option<char>,
+    pub decimal_mark: char,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        NumberFormat {
+            notation: Notation::Plain,
+            group_separator: None,
+            decimal_mark: '.',
+        }
+    }
+}
+
+impl NumberFormat {
+    /// Writes a number in this format. Numbers are rounded half away from zero from the
+    /// digits they display, so big integers and decimals lose no precision. Numbers that do
+    /// not display as a decimal, like `inf` or complex numbers, are written as they display.
+    pub fn format<N: Display>(&self, n: &N) -> String {
+        let plain = n.to_string();
+        match DecimalText::parse(&plain) {
+            Some(decimal) => self.format_decimal(decimal),
+            None => plain,
+        }
+    }
+
+    fn format_decimal(&self, mut decimal: DecimalText) -> String {
+        let (exponent, frac_digits) = match self.notation {
+            Notation::Plain if decimal.scientific => (Some(decimal.exp - 1), 0),
+            Notation::Plain => (None, 0),
+            Notation::Fixed(digits) => {
+                decimal.round(decimal.exp + digits as i64);
+                (None, digits)
+            }
+            Notation::Significant(digits) => {
+                decimal.round(digits as i64);
+                let frac_digits = if decimal.digits.is_empty() {
+                    digits - 1
+                } else {
+                    (digits as i64 - decimal.exp).max(0) as usize
+                };
+                (None, frac_digits)
+            }
+            Notation::Scientific(digits) => {
+                if let Some(digits) = digits {
+                    decimal.round(digits as i64 + 1);
+                }
+                (Some(decimal.exp - 1), digits.unwrap_or(0))
+            }
+            Notation::Engineering(digits) => {
+                let exponent = |exp: i64| (exp - 1).div_euclid(3) * 3;
+                if let Some(digits) = digits {
+                    decimal.round(decimal.exp - exponent(decimal.exp) + digits as i64);
+                }
+                (Some(exponent(decimal.exp)), digits.unwrap_or(0))
+            }
+        };
+        if let Some(exponent) = exponent {
+            decimal.exp -= exponent;
+        }
+
+        let (int, frac) = decimal.positional(frac_digits);
+        let mut result = String::new();
+        if decimal.negative && !decimal.digits.is_empty() {
+            result.push('-');
+        }
+        for (i, digit) in int.chars().enumerate() {
+            if i > 0 && (int.len() - i) % 3 == 0 {
+                result.extend(self.group_separator);
+            }
+            result.push(digit);
+        }
+        if !frac.is_empty() {
+            result.push(self.decimal_mark);
+            result.push_str(&frac);
+        }
+        match (self.notation, exponent) {
+            (Notation::Engineering(_), Some(exponent)) => match si_prefix(exponent) {
+                Some(prefix) => result.push_str(prefix),
+                None => result.push_str(&format!("e{}", exponent)),
+            },
+            (_, Some(exponent)) => result.push_str(&format!("e{}", exponent)),
+            (_, None) => {}
+        }
+        result
+    }
+}
+
+/// The SI prefix of a power of ten.
+fn si_prefix(exponent: i64) -> Option<&'static str> {
+    const PREFIXES: [&str; 21] = [
+        "q", "r", "y", "z", "a", "f", "p", "n", "µ", "m", "", "k", "M", "G", "T", "P", "E", "Z",
+        "Y", "R", "Q",
+    ];
+    if exponent % 3 != 0 {
+        return None;
+    }
+    usize::try_from(exponent / 3 + 10)
+        .ok()
+        .and_then(|i| PREFIXES.get(i).copied())
+}
+
+/// The digits of a displayed decimal number, which is `0.DIGITS * 10^exp`.
+struct DecimalText {
+    negative: bool,
+    /// Without leading or trailing zeros, so empty for zero.
+    digits: Vec<u8>,
+    exp: i64,
+    /// Whether the number was displayed with an exponent.
+    scientific: bool,
+}
+
+impl DecimalText {
+    /// Reads numbers like `-12.5`, `.5` or `1.5e-7`.
+    fn parse(s: &str) -> Option<DecimalText> {
+        let (negative, s) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s),
+        };
+        let (mantissa, exponent) = match s.find(['e', 'E']) {
+            Some(i) => (&s[..i], Some(s[i + 1..].parse::<i64>().ok()?)),
+            None => (s, None),
+        };
+        let (int, frac) = mantissa.split_at(mantissa.find('.').unwrap_or(mantissa.len()));
+        let frac = frac.strip_prefix('.').unwrap_or(frac);
+        let all_digits = int.bytes().chain(frac.bytes()).all(|b| b.is_ascii_digit());
+        if (int.is_empty() && frac.is_empty()) || !all_digits {
+            return None;
+        }
+
+        let mut digits: Vec<u8> = int.bytes().chain(frac.bytes()).map(|b| b - b'0').collect();
+        let leading_zeros = digits.iter().take_while(|&&d| d == 0).count();
+        digits.drain(..leading_zeros);
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+        let exp = int.len() as i64 - leading_zeros as i64 + exponent.unwrap_or(0);
+        Some(DecimalText {
+            negative,
+            exp: if digits.is_empty() { 1 } else { exp },
+            digits,
+            scientific: exponent.is_some(),
+        })
+    }
+
+    /// Rounds half away from zero to the first `keep` digits.
+    fn round(&mut self, keep: i64) {
+        let keep = match usize::try_from(keep) {
+            Ok(keep) if keep >= self.digits.len() => return,
+            Ok(keep) => keep,
+            Err(_) => {
+                self.digits.clear();
+                self.exp = 1;
+                return;
+            }
+        };
+        let round_up = self.digits[keep] >= 5;
+        self.digits.truncate(keep);
+        if round_up {
+            while self.digits.last() == Some(&9) {
+                self.digits.pop();
+            }
+            match self.digits.last_mut() {
+                Some(last) => *last += 1,
+                None => {
+                    self.digits.push(1);
+                    self.exp += 1;
+                }
+            }
+        }
+        while self.digits.last() == Some(&0) {
+            self.digits.pop();
+        }
+        if self.digits.is_empty() {
+            self.exp = 1;
+        }
+    }
+
+    /// The digits before and after the decimal mark, with at least `frac_digits` after it.
+    fn positional(&self, frac_digits: usize) -> (String, String) {
+        let digit = |i: i64| match usize::try_from(i) {
+            Ok(i) if i < self.digits.len() => char::from(b'0' + self.digits[i]),
+            _ => '0',
+        };
+        let int: String = if self.exp <= 0 || self.digits.is_empty() {
+            String::from("0")
+        } else {
+            (0..self.exp).map(digit).collect()
+        };
+        let frac_len = (self.digits.len() as i64 - self.exp).max(frac_digits as i64);
+        let frac = (0..frac_len).map(|i| digit(self.exp + i)).collect();
+        (int, frac)
+    }
+}
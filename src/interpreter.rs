@@ -1,25 +1,269 @@
-use crate::{Expr, Num, OpVal};
-use std::collections::HashMap;
-use std::ops::Deref;
+use crate::value::{broadcast, make_list, matrix_product};
+use crate::{
+    parse, tokenize, ArithmeticMode, DecimalContext, Error, Expr, Matrix, Num, OpVal, Real, Value,
+};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{self, Display, Formatter};
+use std::ops::{Deref, Range};
+
+/// A function receives its name, its arguments and the settings of the interpreter calling it.
+/// Called with lists or matrices, it is applied to each of their elements.
+pub type Function<N> =
+    for<'expr> fn(&'expr str, &[N], &Settings) -> Result<N, InterpretError<'expr>>;
+
+/// A function of whole values, like `len` or `det`, which receives lists and matrices as they
+/// are.
+pub type ValueFunction<N> =
+    for<'expr> fn(&'expr str, &[Value<N>], &Settings) -> Result<Value<N>, InterpretError<'expr>>;
 
 #[derive(Clone)]
 pub enum Variant<N: Num> {
     Num(N),
-    Function(for<'expr> fn(&'expr str, &[N]) -> Result<N, InterpretError<'expr>>),
+    List(Vec<N>),
+    Matrix(Matrix<N>),
+    Function(Function<N>),
+    ValueFunction(ValueFunction<N>),
+}
+
+impl<N: Num> From<Value<N>> for Variant<N> {
+    fn from(value: Value<N>) -> Self {
+        match value {
+            Value::Num(n) => Variant::Num(n),
+            Value::List(list) => Variant::List(list),
+            Value::Matrix(matrix) => Variant::Matrix(matrix),
+        }
+    }
+}
+
+/// The unit of angles taken and returned by trigonometric functions.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum AngleMode {
+    #[default]
+    Radians,
+    Degrees,
+    /// A full turn is 400 gradians.
+    Gradians,
+}
+
+impl AngleMode {
+    /// The size of a full turn in this unit.
+    fn full_turn(self) -> f64 {
+        match self {
+            AngleMode::Radians => std::f64::consts::TAU,
+            AngleMode::Degrees => 360.0,
+            AngleMode::Gradians => 400.0,
+        }
+    }
+
+    /// Converts `angle` in this unit to radians.
+    pub fn to_radians<N: Real>(self, angle: N) -> N {
+        match self {
+            AngleMode::Radians => angle,
+            _ => angle * N::from_f64(std::f64::consts::TAU / self.full_turn()),
+        }
+    }
+
+    /// Converts `radians` to this unit.
+    pub fn from_radians<N: Real>(self, radians: N) -> N {
+        match self {
+            AngleMode::Radians => radians,
+            _ => radians * N::from_f64(self.full_turn() / std::f64::consts::TAU),
+        }
+    }
+
+    /// Converts `degrees` to this unit, without going through radians, so degrees stay exact.
+    pub fn from_degrees<N: Real>(self, degrees: N) -> N {
+        match self {
+            AngleMode::Degrees => degrees,
+            _ => degrees * N::from_f64(self.full_turn() / 360.0),
+        }
+    }
+}
+
+/// Settings that change how an `Interpreter` evaluates. Functions receive them, too.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Settings {
+    /// How integer overflow is handled. Checked by default.
+    pub arithmetic_mode: ArithmeticMode,
+    /// Makes every operator, negation and function call that produces NaN or an infinity an
+    /// `InterpretError::NonFinite`. Off by default.
+    pub strict_ieee: bool,
+    /// The unit of angles for trigonometric functions. Radians by default.
+    pub angle_mode: AngleMode,
+    /// The precision and rounding of `Decimal` arithmetic.
+    pub decimal: DecimalContext,
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum InterpretError<'expr> {
     TooFewArgs(&'expr str, usize),  // Id of function, min args
     TooManyArgs(&'expr str, usize), // Id of function, max args
     VarDoesNotExist(&'expr str),
     VarIsNotFunction(&'expr str),
     FunctionNameUsedLikeVar(&'expr str),
+    /// The expression contains an `Expr::Error` from `parse_recovering`.
+    InvalidExpr,
+    DivisionByZero,
+    /// The result does not fit in the number type.
+    Overflow,
+    /// An integer was raised to a negative power.
+    NegativeExponent,
+    /// In strict IEEE mode, the named operator or function produced NaN or an infinity at the
+    /// span.
+    NonFinite(&'expr str, Range<usize>),
+    /// The function is not defined for its arguments, e.g. the factorial of a negative integer.
+    OutOfDomain(&'expr str),
+    /// The named operator was applied to quantities of dimensions it cannot combine, like
+    /// adding metres to seconds. Holds the dimensions of both sides in SI base units and the
+    /// span of the operator.
+    DimensionMismatch(&'expr str, String, String, Range<usize>),
+    /// The named operator or function was applied to lists or matrices of shapes it cannot
+    /// combine. Holds descriptions of both sides, like "a list of 3" or "a 2×2 matrix", and the
+    /// span of the operator or call.
+    ShapeMismatch(&'expr str, String, String, Range<usize>),
+    /// The named operator or function received the wrong kind of value, like a number where it
+    /// needs a list. Holds what it expected, what it got and the span.
+    TypeMismatch(&'expr str, String, String, Range<usize>),
+    /// The index at the span is not a whole number below the length of the list, or the number
+    /// of rows of the matrix, which it holds.
+    IndexOutOfRange(usize, Range<usize>),
+    /// `Interpreter::eval` only returns numbers, but the result is the described value.
+    /// `Interpreter::eval_value` returns lists and matrices, too.
+    NotANumber(String),
+}
+
+impl<'expr> InterpretError<'expr> {
+    /// Returns where in the input the error occurred, if known.
+    pub fn span(&self) -> Option<Range<usize>> {
+        match self {
+            InterpretError::NonFinite(_, span)
+            | InterpretError::DimensionMismatch(.., span)
+            | InterpretError::ShapeMismatch(.., span)
+            | InterpretError::TypeMismatch(.., span)
+            | InterpretError::IndexOutOfRange(_, span) => Some(span.clone()),
+            _ => None,
+        }
+    }
+
+    /// Places an error that values report without knowing where they are at `span`.
+    fn located(self, span: &Range<usize>) -> Self {
+        match self {
+            InterpretError::DimensionMismatch(op, lhs, rhs, _) => {
+                InterpretError::DimensionMismatch(op, lhs, rhs, span.clone())
+            }
+            InterpretError::ShapeMismatch(op, lhs, rhs, _) => {
+                InterpretError::ShapeMismatch(op, lhs, rhs, span.clone())
+            }
+            InterpretError::TypeMismatch(op, expected, found, _) => {
+                InterpretError::TypeMismatch(op, expected, found, span.clone())
+            }
+            InterpretError::IndexOutOfRange(len, _) => {
+                InterpretError::IndexOutOfRange(len, span.clone())
+            }
+            err => err,
+        }
+    }
+}
+
+#[inline(always)]
+fn s_if(b: bool) -> &'static str {
+    if b {
+        "s"
+    } else {
+        ""
+    }
+}
+
+impl<'expr> Display for InterpretError<'expr> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            InterpretError::TooFewArgs(id, n) => write!(
+                f,
+                "Function {:?} did not receive minimum of {} argument{}.",
+                id,
+                n,
+                s_if(*n != 1)
+            ),
+            InterpretError::TooManyArgs(id, n) => write!(
+                f,
+                "Function {:?} received more than the maximum {} argument{}.",
+                id,
+                n,
+                s_if(*n != 1)
+            ),
+            InterpretError::VarDoesNotExist(id) => {
+                write!(f, "No variable or function {:?} exists.", id)
+            }
+            InterpretError::VarIsNotFunction(id) => write!(
+                f,
+                "The variable {:?} cannot be used like a function with arguments.",
+                id
+            ),
+            InterpretError::FunctionNameUsedLikeVar(id) => {
+                write!(f, "The function {:?} cannot be used without arguments.", id)
+            }
+            InterpretError::InvalidExpr => {
+                write!(f, "The expression contains errors and cannot be evaluated.")
+            }
+            InterpretError::DivisionByZero => write!(f, "Division by zero."),
+            InterpretError::Overflow => write!(f, "The result is too large for the number type."),
+            InterpretError::NegativeExponent => {
+                write!(f, "Integers cannot be raised to a negative power.")
+            }
+            InterpretError::NonFinite(op, _) => write!(f, "{:?} produced NaN or infinity.", op),
+            InterpretError::OutOfDomain(id) => {
+                write!(f, "Function {:?} is not defined for these arguments.", id)
+            }
+            InterpretError::DimensionMismatch(op, lhs, rhs, _) => write!(
+                f,
+                "{:?} cannot combine the dimensions {} and {}.",
+                op, lhs, rhs
+            ),
+            InterpretError::ShapeMismatch(op, lhs, rhs, _) => {
+                write!(f, "{:?} cannot combine {} and {}.", op, lhs, rhs)
+            }
+            InterpretError::TypeMismatch(op, expected, found, _) => {
+                write!(f, "{:?} expected {}, not {}.", op, expected, found)
+            }
+            InterpretError::IndexOutOfRange(0, _) => write!(f, "The list is empty."),
+            InterpretError::IndexOutOfRange(len, _) => {
+                write!(f, "The index must be a whole number from 0 to {}.", len - 1)
+            }
+            InterpretError::NotANumber(found) => {
+                write!(f, "The result is {}, not a number.", found)
+            }
+        }
+    }
 }
 
+impl<'expr> std::error::Error for InterpretError<'expr> {}
+
 #[derive(Clone)]
 pub struct Interpreter<N: Num> {
     pub vars: HashMap<String, Variant<N>>,
+    pub settings: Settings,
+}
+
+/// A snapshot of the variables of an `Interpreter`, which can be serialized with the `serde`
+/// feature.
+///
+/// Functions are Rust function pointers and cannot be serialized. They are recorded by name
+/// only, and resolved again against a registry interpreter (e.g. `Interpreter::default()`) by
+/// `Interpreter::from_state`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct InterpreterState<N> {
+    pub nums: BTreeMap<String, N>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub lists: BTreeMap<String, Vec<N>>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub matrices: BTreeMap<String, Matrix<N>>,
+    pub functions: Vec<String>,
 }
 
 impl<N: Num> Interpreter<N> {
@@ -27,6 +271,7 @@ impl<N: Num> Interpreter<N> {
     pub fn new() -> Interpreter<N> {
         Interpreter {
             vars: HashMap::new(),
+            settings: Settings::default(),
         }
     }
 
@@ -40,71 +285,448 @@ impl<N: Num> Interpreter<N> {
         self.vars.remove(name)
     }
 
+    /// Takes a snapshot of the variables, recording functions by name.
+    pub fn state(&self) -> InterpreterState<N> {
+        let mut nums = BTreeMap::new();
+        let mut lists = BTreeMap::new();
+        let mut matrices = BTreeMap::new();
+        let mut functions = Vec::new();
+        for (id, var) in &self.vars {
+            match var {
+                Variant::Num(n) => {
+                    nums.insert(id.clone(), n.clone());
+                }
+                Variant::List(list) => {
+                    lists.insert(id.clone(), list.clone());
+                }
+                Variant::Matrix(matrix) => {
+                    matrices.insert(id.clone(), matrix.clone());
+                }
+                Variant::Function(_) | Variant::ValueFunction(_) => functions.push(id.clone()),
+            }
+        }
+        functions.sort();
+        InterpreterState {
+            nums,
+            lists,
+            matrices,
+            functions,
+        }
+    }
+
+    /// Builds an interpreter from a snapshot. Each function name in the state is looked up in
+    /// `registry`, and a `VarDoesNotExist` error is returned for any that is not a function
+    /// there. The settings are taken from `registry`.
+    pub fn from_state<'s>(
+        state: &'s InterpreterState<N>,
+        registry: &Interpreter<N>,
+    ) -> Result<Interpreter<N>, InterpretError<'s>> {
+        let mut vars = HashMap::with_capacity(
+            state.nums.len() + state.lists.len() + state.matrices.len() + state.functions.len(),
+        );
+        for id in &state.functions {
+            match registry.vars.get(id) {
+                Some(func @ (Variant::Function(_) | Variant::ValueFunction(_))) => {
+                    vars.insert(id.clone(), func.clone());
+                }
+                _ => return Err(InterpretError::VarDoesNotExist(id)),
+            }
+        }
+        for (id, n) in &state.nums {
+            vars.insert(id.clone(), Variant::Num(n.clone()));
+        }
+        for (id, list) in &state.lists {
+            vars.insert(id.clone(), Variant::List(list.clone()));
+        }
+        for (id, matrix) in &state.matrices {
+            vars.insert(id.clone(), Variant::Matrix(matrix.clone()));
+        }
+        Ok(Interpreter {
+            vars,
+            settings: registry.settings.clone(),
+        })
+    }
+
+    /// Evaluates `expr` to a number. A result that is a list or matrix is a `NotANumber` error,
+    /// though assignments in the expression are still made. Use `eval_value` for those.
     pub fn eval<'expr>(&mut self, expr: &'expr Expr<N>) -> Result<N, InterpretError<'expr>> {
+        match self.eval_value(expr)? {
+            Value::Num(n) => Ok(n),
+            value => Err(InterpretError::NotANumber(value.describe())),
+        }
+    }
+
+    /// Evaluates `expr` to a number or a list.
+    ///
+    /// Operators and functions of numbers apply to each element of a list, with numbers
+    /// repeated for every element, so `[1, 2] * 2` is `[2, 4]` and `[1, 2] + [3, 4]` is
+    /// `[4, 6]`. Lists of different lengths are a `ShapeMismatch` error. Value functions like
+    /// `sum` receive lists whole.
+    pub fn eval_value<'expr>(
+        &mut self,
+        expr: &'expr Expr<N>,
+    ) -> Result<Value<N>, InterpretError<'expr>> {
         // simple, naive recursive tree walk
         match expr {
             Expr::Eq(lhs, rhs) => match lhs.deref() {
                 Expr::Var(id) => {
-                    let result = self.eval(rhs)?;
-                    if let Some(val) = self.vars.get_mut(*id) {
-                        *val = Variant::Num(result.clone());
+                    let result = self.eval_value(rhs)?;
+                    if let Some(val) = self.vars.get_mut(id.as_ref()) {
+                        *val = result.clone().into();
                     } else {
-                        self.vars
-                            .insert(id.to_string(), Variant::Num(result.clone()));
+                        self.vars.insert(id.to_string(), result.clone().into());
                     }
                     Ok(result)
                 }
                 _ => todo!("implement algebra solving"),
             },
-            Expr::FuncOrVarMul(id, exprs) => {
+            Expr::FuncOrVarMul(id, exprs, span) => {
+                let id: &str = id;
                 let mut args = Vec::with_capacity(exprs.len());
                 for expr in exprs {
-                    args.push(self.eval(expr)?);
+                    args.push(self.eval_value(expr)?);
                 }
 
-                if let Some(var) = self.vars.get(*id) {
+                if let Some(var) = self.vars.get(id) {
                     match var {
-                        Variant::Num(n) => {
+                        Variant::Num(_) | Variant::List(_) | Variant::Matrix(_) => {
                             if args.len() == 1 {
+                                let lhs = var_value(var).unwrap();
                                 let arg = args.remove(0);
-                                Ok(n.clone().mul(arg))
+                                self.operate(OpVal::Mul, lhs, arg, span)
                             } else {
                                 Err(InterpretError::VarIsNotFunction(id))
                             }
                         }
-                        Variant::Function(func) => func(id, &args),
+                        Variant::Function(func) => self.call(id, *func, &args, span),
+                        Variant::ValueFunction(func) => {
+                            let result =
+                                func(id, &args, &self.settings).map_err(|err| err.located(span))?;
+                            self.check_finite(result, id, span)
+                        }
                     }
                 } else {
                     Err(InterpretError::VarDoesNotExist(id))
                 }
             }
-            Expr::Neg(expr) => Ok(-self.eval(expr)?),
-            Expr::Num(n) => Ok((**n).clone()),
-            Expr::Op(op, lhs, rhs) => {
-                let lhs = self.eval(lhs)?;
-                let rhs = self.eval(rhs)?;
-                Ok(match op {
-                    OpVal::Add => lhs + rhs,
-                    OpVal::Sub => lhs - rhs,
-                    OpVal::Mul => lhs * rhs,
-                    OpVal::Div => lhs / rhs,
-                    OpVal::Mod => lhs % rhs,
-                    OpVal::Pow => lhs.pow(rhs),
-                    _ => unreachable!(),
-                })
+            Expr::Index(list, index, span) => {
+                let list = self.eval_value(list)?;
+                let index = self.eval_value(index)?;
+                list.index(index).map_err(|err| err.located(span))
+            }
+            Expr::List(items, span) => {
+                let mut values = Vec::with_capacity(items.len());
+                for item in items {
+                    values.push(self.eval_value(item)?);
+                }
+                make_list(values).map_err(|err| err.located(span))
+            }
+            Expr::Neg(expr, span) => {
+                let result = self.eval_value(expr)?.map(|n| n.negate(&self.settings))?;
+                self.check_finite(result, OpVal::Sub.symbol(), span)
+            }
+            Expr::Degrees(expr, span) => {
+                let result = self.eval_value(expr)?.map(|n| n.degrees(&self.settings))?;
+                self.check_finite(result, OpVal::Degree.symbol(), span)
+            }
+            Expr::Num(n) => Ok(Value::Num(n.clone())),
+            Expr::Op(op, lhs, rhs, span) => {
+                let lhs = self.eval_value(lhs)?;
+                let rhs = self.eval_value(rhs)?;
+                self.operate(*op, lhs, rhs, span)
             }
             Expr::Var(id) => {
-                if let Some(var) = self.vars.get(*id) {
-                    match var {
-                        Variant::Num(n) => Ok(n.clone()),
-                        Variant::Function(_) => Err(InterpretError::FunctionNameUsedLikeVar(id)),
-                    }
+                let id: &str = id;
+                if let Some(var) = self.vars.get(id) {
+                    var_value(var)
+                        .or_else(|| N::unit_named(id).map(Value::Num))
+                        .ok_or(InterpretError::FunctionNameUsedLikeVar(id))
                 } else {
                     Err(InterpretError::VarDoesNotExist(id))
                 }
             }
+            Expr::Error => Err(InterpretError::InvalidExpr),
         }
     }
+
+    /// Evaluates everything in `expr` that can be known from the current variables, and leaves
+    /// the rest symbolic. Given `a*x^2 + b*x + c` with `a`, `b` and `c` set to 3, 2 and 1, the
+    /// result is `3*x^2 + 2*x + 1`. An expression without unknowns is folded into a single
+    /// `Expr::Num`, or an `Expr::List` of numbers.
+    ///
+    /// Unlike `eval`, an assignment is only performed when its value is fully known. Otherwise
+    /// the assignment is returned with its residual right hand side.
+    pub fn partial_eval<'expr, 'input>(
+        &mut self,
+        expr: &'expr Expr<'input, N>,
+    ) -> Result<Expr<'input, N>, InterpretError<'expr>> {
+        match expr {
+            Expr::Eq(lhs, rhs) => {
+                let rhs = self.partial_eval(rhs)?;
+                match (lhs.deref(), known_value(&rhs)) {
+                    (Expr::Var(id), Some(result)) => {
+                        self.set_var(id.to_string(), result.into());
+                        Ok(rhs)
+                    }
+                    // The target of an assignment is a name, not its value
+                    (Expr::Var(id), None) => {
+                        Ok(Expr::Eq(Box::new(Expr::Var(id.clone())), Box::new(rhs)))
+                    }
+                    _ => Ok(Expr::Eq(Box::new(self.partial_eval(lhs)?), Box::new(rhs))),
+                }
+            }
+            Expr::FuncOrVarMul(name, exprs, span) => {
+                let id: &str = name;
+                let mut args = Vec::with_capacity(exprs.len());
+                for expr in exprs {
+                    args.push(self.partial_eval(expr)?);
+                }
+
+                match self.vars.get(id) {
+                    Some(var @ (Variant::Num(_) | Variant::List(_) | Variant::Matrix(_))) => {
+                        if args.len() == 1 {
+                            let lhs = var_value(var).unwrap();
+                            let arg = args.remove(0);
+                            if let Some(arg) = known_value(&arg) {
+                                let result = self.operate(OpVal::Mul, lhs, arg, span)?;
+                                Ok(value_expr(result, span))
+                            } else {
+                                Ok(Expr::Op(
+                                    OpVal::Mul,
+                                    Box::new(value_expr(lhs, span)),
+                                    Box::new(arg),
+                                    span.clone(),
+                                ))
+                            }
+                        } else {
+                            Err(InterpretError::VarIsNotFunction(id))
+                        }
+                    }
+                    Some(Variant::Function(func)) => match known_values(&args) {
+                        Some(values) => Ok(value_expr(self.call(id, *func, &values, span)?, span)),
+                        None => Ok(Expr::FuncOrVarMul(name.clone(), args, span.clone())),
+                    },
+                    Some(Variant::ValueFunction(func)) => match known_values(&args) {
+                        Some(values) => {
+                            let result = func(id, &values, &self.settings)
+                                .map_err(|err| err.located(span))?;
+                            Ok(value_expr(self.check_finite(result, id, span)?, span))
+                        }
+                        None => Ok(Expr::FuncOrVarMul(name.clone(), args, span.clone())),
+                    },
+                    // Unknown ids stay symbolic, since they may be bound later.
+                    None => Ok(Expr::FuncOrVarMul(name.clone(), args, span.clone())),
+                }
+            }
+            Expr::Index(list, index, span) => {
+                let (list, index) = (self.partial_eval(list)?, self.partial_eval(index)?);
+                match (known_value(&list), known_value(&index)) {
+                    (Some(list), Some(index)) => {
+                        let result = list.index(index).map_err(|err| err.located(span))?;
+                        Ok(value_expr(result, span))
+                    }
+                    _ => Ok(Expr::Index(Box::new(list), Box::new(index), span.clone())),
+                }
+            }
+            Expr::List(items, span) => {
+                let mut exprs = Vec::with_capacity(items.len());
+                for item in items {
+                    exprs.push(self.partial_eval(item)?);
+                }
+                match known_values(&exprs) {
+                    Some(values) => {
+                        let result = make_list(values).map_err(|err| err.located(span))?;
+                        Ok(value_expr(result, span))
+                    }
+                    None => Ok(Expr::List(exprs, span.clone())),
+                }
+            }
+            Expr::Neg(expr, span) => {
+                let expr = self.partial_eval(expr)?;
+                match known_value(&expr) {
+                    Some(value) => {
+                        let result = value.map(|n| n.negate(&self.settings))?;
+                        let result = self.check_finite(result, OpVal::Sub.symbol(), span)?;
+                        Ok(value_expr(result, span))
+                    }
+                    None => Ok(Expr::Neg(Box::new(expr), span.clone())),
+                }
+            }
+            Expr::Degrees(expr, span) => {
+                let expr = self.partial_eval(expr)?;
+                match known_value(&expr) {
+                    Some(value) => {
+                        let result = value.map(|n| n.degrees(&self.settings))?;
+                        let result = self.check_finite(result, OpVal::Degree.symbol(), span)?;
+                        Ok(value_expr(result, span))
+                    }
+                    None => Ok(Expr::Degrees(Box::new(expr), span.clone())),
+                }
+            }
+            Expr::Num(n) => Ok(Expr::Num(n.clone())),
+            Expr::Op(op, lhs, rhs, span) => {
+                let (lhs, rhs) = (self.partial_eval(lhs)?, self.partial_eval(rhs)?);
+                match (known_value(&lhs), known_value(&rhs)) {
+                    (Some(lhs), Some(rhs)) => {
+                        Ok(value_expr(self.operate(*op, lhs, rhs, span)?, span))
+                    }
+                    _ => Ok(Expr::Op(*op, Box::new(lhs), Box::new(rhs), span.clone())),
+                }
+            }
+            Expr::Var(id) => match self.vars.get(id.as_ref()) {
+                // The input has no brackets to locate a list from a variable
+                Some(var @ (Variant::Num(_) | Variant::List(_) | Variant::Matrix(_))) => {
+                    Ok(value_expr(var_value(var).unwrap(), &(0..0)))
+                }
+                Some(_) => N::unit_named(id)
+                    .map(Expr::Num)
+                    .ok_or(InterpretError::FunctionNameUsedLikeVar(id)),
+                None => Ok(Expr::Var(id.clone())),
+            },
+            Expr::Error => Ok(Expr::Error),
+        }
+    }
+
+    /// Applies the binary operator `op` at `span`, to each element of lists and matrices,
+    /// except that `*` multiplies matrices and `^` raises a matrix to a power. `±` is only
+    /// defined for number types with uncertainty, and `to` for number types with units.
+    fn operate(
+        &self,
+        op: OpVal,
+        lhs: Value<N>,
+        rhs: Value<N>,
+        span: &Range<usize>,
+    ) -> Result<Value<N>, InterpretError<'static>> {
+        let product = match op {
+            OpVal::Mul => matrix_product(&lhs, &rhs, &self.settings),
+            _ => None,
+        };
+        let result = match (product, lhs, rhs) {
+            (Some(product), ..) => product,
+            (None, Value::Matrix(matrix), Value::Num(exponent)) if op == OpVal::Pow => {
+                matrix.pow(exponent, &self.settings).map(Value::Matrix)
+            }
+            (None, lhs, rhs) => return self.operate_elements(op, lhs, rhs, span),
+        };
+        let result = result.and_then(|result| self.check_finite(result, op.symbol(), span));
+        // Number types don't know where the operator is
+        result.map_err(|err| err.located(span))
+    }
+
+    fn operate_elements(
+        &self,
+        op: OpVal,
+        lhs: Value<N>,
+        rhs: Value<N>,
+        span: &Range<usize>,
+    ) -> Result<Value<N>, InterpretError<'static>> {
+        let result = broadcast(op.symbol(), &[lhs, rhs], |args| {
+            let (lhs, rhs) = (args[0].clone(), args[1].clone());
+            let result = match op {
+                OpVal::PlusMinus => lhs
+                    .plus_minus(rhs)
+                    .ok_or(InterpretError::OutOfDomain(OpVal::PlusMinus.symbol())),
+                OpVal::To => lhs.convert(rhs),
+                _ => lhs.arithmetic(op, rhs, &self.settings),
+            };
+            result.and_then(|result| self.check_finite_num(result, op.symbol(), span))
+        });
+        result.map_err(|err| err.located(span))
+    }
+
+    /// Calls the function of numbers `func`, once for each element if there are lists or
+    /// matrices among the arguments.
+    fn call<'expr>(
+        &self,
+        id: &'expr str,
+        func: Function<N>,
+        args: &[Value<N>],
+        span: &Range<usize>,
+    ) -> Result<Value<N>, InterpretError<'expr>> {
+        broadcast(id, args, |nums| {
+            self.check_finite_num(func(id, nums, &self.settings)?, id, span)
+        })
+        .map_err(|err| err.located(span))
+    }
+
+    /// Returns `result`, or a `NonFinite` error for `op` at `span` if any number in it is NaN
+    /// or an infinity in strict IEEE mode.
+    fn check_finite<'expr>(
+        &self,
+        result: Value<N>,
+        op: &'expr str,
+        span: &Range<usize>,
+    ) -> Result<Value<N>, InterpretError<'expr>> {
+        result.map(|n| self.check_finite_num(n, op, span))
+    }
+
+    fn check_finite_num<'expr>(
+        &self,
+        result: N,
+        op: &'expr str,
+        span: &Range<usize>,
+    ) -> Result<N, InterpretError<'expr>> {
+        if self.settings.strict_ieee && !result.is_finite() {
+            Err(InterpretError::NonFinite(op, span.clone()))
+        } else {
+            Ok(result)
+        }
+    }
+}
+
+impl<N: Num + Display> Interpreter<N> {
+    /// Tokenizes, parses and evaluates `input` in one call. Errors from every stage are
+    /// converted into the owned `Error`, so they can be returned with `?`.
+    pub fn eval_str(&mut self, input: &str) -> Result<N, Error> {
+        let tokens = tokenize(input)?;
+        let expr = parse(&tokens)?;
+        Ok(self.eval(&expr)?)
+    }
+
+    /// Like `eval_str`, but the result may be a list.
+    pub fn eval_value_str(&mut self, input: &str) -> Result<Value<N>, Error> {
+        let tokens = tokenize(input)?;
+        let expr = parse(&tokens)?;
+        Ok(self.eval_value(&expr)?)
+    }
+}
+
+/// The value of a variable holding a number, list or matrix.
+fn var_value<N: Num>(var: &Variant<N>) -> Option<Value<N>> {
+    match var {
+        Variant::Num(n) => Some(Value::Num(n.clone())),
+        Variant::List(list) => Some(Value::List(list.clone())),
+        Variant::Matrix(matrix) => Some(Value::Matrix(matrix.clone())),
+        Variant::Function(_) | Variant::ValueFunction(_) => None,
+    }
+}
+
+/// The value of `expr` if it is a number, or a list or matrix of numbers.
+fn known_value<N: Num>(expr: &Expr<N>) -> Option<Value<N>> {
+    match expr {
+        Expr::Num(n) => Some(Value::Num(n.clone())),
+        Expr::List(items, _) => make_list(known_values(items)?).ok(),
+        _ => None,
+    }
+}
+
+/// Returns the values of `exprs` if every one of them is known.
+fn known_values<N: Num>(exprs: &[Expr<N>]) -> Option<Vec<Value<N>>> {
+    exprs.iter().map(known_value).collect()
+}
+
+/// The expression of a known value. A list or matrix is located at `span`.
+fn value_expr<'input, N: Num>(value: Value<N>, span: &Range<usize>) -> Expr<'input, N> {
+    match value {
+        Value::Num(n) => Expr::Num(n),
+        Value::List(list) => Expr::List(list.into_iter().map(Expr::Num).collect(), span.clone()),
+        Value::Matrix(matrix) => Expr::List(
+            (0..matrix.rows())
+                .map(|row| value_expr(Value::List(matrix.row(row).to_vec()), span))
+                .collect(),
+            span.clone(),
+        ),
+    }
 }
 
 #[inline]
@@ -113,7 +735,7 @@ pub fn ensure_arg_count(
     max: usize,
     args_len: usize,
     func_id: &str,
-) -> Result<(), InterpretError> {
+) -> Result<(), InterpretError<'_>> {
     if args_len < min {
         Err(InterpretError::TooFewArgs(func_id, min))
     } else if args_len > max {
@@ -122,39 +744,3 @@ pub fn ensure_arg_count(
         Ok(())
     }
 }
-
-impl Default for Interpreter<f64> {
-    fn default() -> Self {
-        let mut vars = HashMap::new();
-        vars.insert(String::from("pi"), Variant::Num(std::f64::consts::PI));
-        vars.insert(String::from("e"), Variant::Num(std::f64::consts::E));
-        vars.insert(String::from("tau"), Variant::Num(std::f64::consts::TAU));
-        vars.insert(
-            String::from("abs"),
-            Variant::Function(|id, args| {
-                ensure_arg_count(1, 1, args.len(), id)?;
-                Ok(args[0].abs())
-            }),
-        );
-        vars.insert(
-            String::from("sqrt"),
-            Variant::Function(|id, args| {
-                ensure_arg_count(1, 1, args.len(), id)?;
-                Ok(args[0].sqrt())
-            }),
-        );
-        vars.insert(
-            String::from("factorial"),
-            Variant::Function(|id, args| {
-                ensure_arg_count(1, 1, args.len(), id)?;
-                let n = args[0];
-                if n <= 1.0 {
-                    Ok(1.0)
-                } else {
-                    Ok((1..=n as u64).product::<u64>() as f64)
-                }
-            }),
-        );
-        Interpreter { vars }
-    }
-}
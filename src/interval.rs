@@ -0,0 +1,786 @@
+use crate::{stdlib, InterpretError, Num, OpVal, Real, Settings};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::f64::consts::{FRAC_PI_2, PI, TAU};
+use std::fmt::{self, Display, Formatter};
+use std::num::ParseFloatError;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Rem, Sub, SubAssign};
+use std::str::FromStr;
+
+/// A closed interval of real numbers `[lo, hi]`, which is guaranteed to contain the exact
+/// result of every calculation done with it.
+///
+/// Bounds are rounded outward: arithmetic and square roots round each bound in the right
+/// direction, and other functions widen their results by one unit in the last place, which
+/// covers the error of the platform's math functions, or more for the gamma function.
+/// Literals that `f64` cannot represent exactly, like `0.1`, become the interval between the
+/// floats on either side of them.
+///
+/// Dividing by an interval containing zero gives the smallest interval containing every
+/// quotient, which is unbounded, e.g. `1 / [-1, 1]` is `[-inf, inf]`. An interval is less
+/// than another only if all of its numbers are, so overlapping intervals are unordered. An
+/// interval `is_whole` if it is a single whole number.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Interval {
+    pub lo: f64,
+    pub hi: f64,
+}
+
+/// Where gamma of positive numbers has its minimum, and a lower bound of the minimum.
+const GAMMA_MIN: (f64, f64) = (1.461_632_144_968_362_3, 0.885_603_194_410_888);
+/// Where ln_gamma of positive numbers has its minimum, and a lower bound of the minimum.
+const LN_GAMMA_MIN: (f64, f64) = (GAMMA_MIN.0, -0.121_486_290_535_85);
+/// A bound of the relative error of `stdlib::gamma` and `stdlib::ln_gamma`.
+const GAMMA_ERROR: f64 = 1e-12;
+
+/// The error of `a + b` rounded to `sum`, so that `a + b = sum + error` exactly.
+fn sum_error(a: f64, b: f64, sum: f64) -> f64 {
+    let b_virtual = sum - a;
+    let a_virtual = sum - b_virtual;
+    (a - a_virtual) + (b - b_virtual)
+}
+
+/// Rounds `x`, the nearest float to a result which is `x + error` exactly, down.
+fn down(x: f64, error: f64) -> f64 {
+    if error < 0.0 {
+        x.next_down()
+    } else if x == f64::INFINITY && !error.is_nan() {
+        f64::MAX
+    } else {
+        x
+    }
+}
+
+/// Rounds `x`, the nearest float to a result which is `x + error` exactly, up.
+fn up(x: f64, error: f64) -> f64 {
+    if error > 0.0 {
+        x.next_up()
+    } else if x == f64::NEG_INFINITY && !error.is_nan() {
+        f64::MIN
+    } else {
+        x
+    }
+}
+
+/// The next float below `x`, or zero if `x` is zero.
+fn next_down(x: f64) -> f64 {
+    if x == 0.0 {
+        x
+    } else {
+        x.next_down()
+    }
+}
+
+/// The next float above `x`, or zero if `x` is zero.
+fn next_up(x: f64) -> f64 {
+    if x == 0.0 {
+        x
+    } else {
+        x.next_up()
+    }
+}
+
+/// `a + b` rounded down if `round_up` is false, or up.
+fn add(a: f64, b: f64, round_up: bool) -> f64 {
+    let sum = a + b;
+    let error = if sum.is_finite() {
+        sum_error(a, b, sum)
+    } else if a.is_finite() && b.is_finite() {
+        // Overflow, which is below infinity
+        -sum
+    } else {
+        0.0
+    };
+    if round_up {
+        up(sum, error)
+    } else {
+        down(sum, error)
+    }
+}
+
+/// `a * b` rounded down if `round_up` is false, or up. Zero times infinity is zero.
+fn mul(a: f64, b: f64, round_up: bool) -> f64 {
+    if a == 0.0 || b == 0.0 {
+        return 0.0;
+    }
+    let product = a * b;
+    let error = if product.is_finite() {
+        a.mul_add(b, -product)
+    } else if a.is_finite() && b.is_finite() {
+        -product
+    } else {
+        0.0
+    };
+    if round_up {
+        up(product, error)
+    } else {
+        down(product, error)
+    }
+}
+
+/// `a / b` rounded down if `round_up` is false, or up.
+fn div(a: f64, b: f64, round_up: bool) -> f64 {
+    let quotient = a / b;
+    let error = if quotient.is_finite() && b.is_finite() {
+        // a - quotient × b has the sign of the error times the sign of b
+        (-quotient).mul_add(b, a) * b.signum()
+    } else if a.is_finite() && b != 0.0 {
+        -quotient
+    } else {
+        0.0
+    };
+    if round_up {
+        up(quotient, error)
+    } else {
+        down(quotient, error)
+    }
+}
+
+/// The square root of `x` rounded down if `round_up` is false, or up.
+fn sqrt(x: f64, round_up: bool) -> f64 {
+    let root = x.sqrt();
+    let error = if root.is_finite() {
+        (-root).mul_add(root, x)
+    } else {
+        0.0
+    };
+    if round_up {
+        up(root, error)
+    } else {
+        down(root, error)
+    }
+}
+
+/// `x^n` for `x >= 0` rounded down if `round_up` is false, or up.
+fn powi(x: f64, n: u64, round_up: bool) -> f64 {
+    let (mut base, mut n, mut result) = (x, n, 1.0);
+    while n > 0 {
+        if n % 2 == 1 {
+            result = mul(result, base, round_up);
+        }
+        base = mul(base, base, round_up);
+        n /= 2;
+    }
+    result
+}
+
+/// Widens `x`, a result of `stdlib::gamma` or `stdlib::ln_gamma`, down if `round_up` is false,
+/// or up.
+fn widen_gamma(x: f64, round_up: bool) -> f64 {
+    let error = GAMMA_ERROR * x.abs().max(1.0);
+    if round_up {
+        x + error
+    } else {
+        x - error
+    }
+}
+
+/// Gamma of `x` rounded down if `round_up` is false, or up. Positive whole numbers multiply
+/// out their factorial.
+fn gamma(x: f64, round_up: bool) -> f64 {
+    if x > 0.0 && x.fract() == 0.0 && x <= 171.0 {
+        (2..x as u32).fold(1.0, |product, n| mul(product, n as f64, round_up))
+    } else {
+        widen_gamma(stdlib::gamma(x), round_up)
+    }
+}
+
+fn ln_gamma(x: f64, round_up: bool) -> f64 {
+    widen_gamma(stdlib::ln_gamma(x), round_up)
+}
+
+impl Interval {
+    /// The interval from `lo` to `hi`. Bounds in the wrong order are swapped.
+    pub fn new(lo: f64, hi: f64) -> Interval {
+        if lo > hi {
+            Interval { lo: hi, hi: lo }
+        } else {
+            Interval { lo, hi }
+        }
+    }
+
+    /// The interval containing only `x`.
+    pub fn point(x: f64) -> Interval {
+        Interval { lo: x, hi: x }
+    }
+
+    /// The interval between the floats on either side of `x`, for numbers that were rounded to
+    /// `x`, e.g. `std::f64::consts::PI`.
+    pub fn around(x: f64) -> Interval {
+        Interval {
+            lo: x.next_down(),
+            hi: x.next_up(),
+        }
+    }
+
+    /// Every real number.
+    pub fn entire() -> Interval {
+        Interval {
+            lo: f64::NEG_INFINITY,
+            hi: f64::INFINITY,
+        }
+    }
+
+    /// The interval of undefined results, like the square root of a negative interval.
+    pub fn nan() -> Interval {
+        Interval::point(f64::NAN)
+    }
+
+    pub fn is_nan(&self) -> bool {
+        self.lo.is_nan() || self.hi.is_nan()
+    }
+
+    pub fn contains(&self, x: f64) -> bool {
+        self.lo <= x && x <= self.hi
+    }
+
+    pub fn width(&self) -> f64 {
+        add(self.hi, -self.lo, true)
+    }
+
+    /// The midpoint, which is only approximate.
+    pub fn mid(&self) -> f64 {
+        if self.lo.is_infinite() || self.hi.is_infinite() {
+            (self.lo + self.hi) / 2.0
+        } else {
+            self.lo / 2.0 + self.hi / 2.0
+        }
+    }
+
+    /// The smallest interval containing both intervals.
+    pub fn hull(self, other: Interval) -> Interval {
+        Interval {
+            lo: self.lo.min(other.lo),
+            hi: self.hi.max(other.hi),
+        }
+    }
+
+    /// Applies the nondecreasing function `f` to the bounds, widening by one unit in the last
+    /// place. Results of zero are taken to be exact, which holds for every function used here
+    /// but `exp`.
+    fn increasing(self, f: fn(f64) -> f64) -> Interval {
+        Interval {
+            lo: next_down(f(self.lo)),
+            hi: next_up(f(self.hi)),
+        }
+    }
+
+    /// Like `increasing`, for the nonincreasing function `f`.
+    fn decreasing(self, f: fn(f64) -> f64) -> Interval {
+        Interval {
+            lo: next_down(f(self.hi)),
+            hi: next_up(f(self.lo)),
+        }
+    }
+
+    /// Applies `f`, which falls until the first of `min` and rises after it, where it is at
+    /// least the second of `min`. `f` rounds down if its second argument is false, or up.
+    fn unimodal(self, f: fn(f64, bool) -> f64, min: (f64, f64)) -> Interval {
+        if self.hi <= min.0 {
+            Interval::new(f(self.hi, false), f(self.lo, true))
+        } else if self.lo >= min.0 {
+            Interval::new(f(self.lo, false), f(self.hi, true))
+        } else {
+            Interval::new(min.1, f(self.lo, true).max(f(self.hi, true)))
+        }
+    }
+
+    /// The part of the interval from `lo` to `hi`, or NaN if there is none.
+    fn clamp(self, lo: f64, hi: f64) -> Interval {
+        if self.is_nan() || self.hi < lo || self.lo > hi {
+            Interval::nan()
+        } else {
+            Interval {
+                lo: self.lo.max(lo),
+                hi: self.hi.min(hi),
+            }
+        }
+    }
+
+    /// Whether the interval may contain `offset + k × period` for a whole `k`. Errs on the
+    /// side of true near the bounds.
+    fn may_contain_periodic(self, offset: f64, period: f64) -> bool {
+        let slack = 1e-9;
+        let first = ((self.lo - offset) / period - slack).ceil();
+        let last = ((self.hi - offset) / period + slack).floor();
+        first <= last
+    }
+
+    /// Applies the periodic function `f`, which is 1 at `max_at + 2kπ` and -1 at
+    /// `max_at + π + 2kπ`, and monotonic between.
+    fn periodic(self, f: fn(f64) -> f64, max_at: f64) -> Interval {
+        if self.is_nan() {
+            return Interval::nan();
+        }
+        if self.width() >= TAU {
+            return Interval::new(-1.0, 1.0);
+        }
+        let (a, b) = (f(self.lo), f(self.hi));
+        let lo = if self.may_contain_periodic(max_at + PI, TAU) {
+            -1.0
+        } else {
+            next_down(a.min(b)).max(-1.0)
+        };
+        let hi = if self.may_contain_periodic(max_at, TAU) {
+            1.0
+        } else {
+            next_up(a.max(b)).min(1.0)
+        };
+        Interval { lo, hi }
+    }
+
+    pub fn abs(self) -> Interval {
+        if self.lo >= 0.0 {
+            self
+        } else if self.hi <= 0.0 {
+            -self
+        } else {
+            Interval::new(0.0, self.hi.max(-self.lo))
+        }
+    }
+
+    pub fn min(self, other: Interval) -> Interval {
+        Interval::new(self.lo.min(other.lo), self.hi.min(other.hi))
+    }
+
+    pub fn max(self, other: Interval) -> Interval {
+        Interval::new(self.lo.max(other.lo), self.hi.max(other.hi))
+    }
+
+    /// Raises the interval to the whole power `n`.
+    fn powi(self, n: i64) -> Interval {
+        if n < 0 {
+            return Interval::one() / self.powi(-n);
+        }
+        let n = n as u64;
+        let magnitude = self.abs();
+        let (lo, hi) = (powi(magnitude.lo, n, false), powi(magnitude.hi, n, true));
+        if n.is_multiple_of(2) {
+            Interval { lo, hi }
+        } else if self.lo >= 0.0 {
+            Interval {
+                lo: powi(self.lo, n, false),
+                hi: powi(self.hi, n, true),
+            }
+        } else if self.hi <= 0.0 {
+            Interval {
+                lo: -powi(-self.lo, n, true),
+                hi: -powi(-self.hi, n, false),
+            }
+        } else {
+            Interval {
+                lo: -powi(-self.lo, n, true),
+                hi: powi(self.hi, n, true),
+            }
+        }
+    }
+}
+
+impl From<f64> for Interval {
+    fn from(x: f64) -> Self {
+        Interval::point(x)
+    }
+}
+
+impl Num for Interval {
+    fn zero() -> Self {
+        Interval::point(0.0)
+    }
+    fn one() -> Self {
+        Interval::point(1.0)
+    }
+    fn is_whole(&self) -> bool {
+        self.lo == self.hi && self.lo.fract() == 0.0
+    }
+    fn parse_literal(s: &str) -> Option<Self> {
+        s.parse().ok()
+    }
+    /// Whole exponents are exact up to rounding. Other exponents need a number that is not
+    /// negative, and use `exp` and `ln`.
+    fn pow(self, other: Self) -> Self {
+        if other.is_whole() && other.lo.abs() < i64::MAX as f64 {
+            self.powi(other.lo as i64)
+        } else {
+            (other * self.ln()).exp()
+        }
+    }
+    fn is_finite(&self) -> bool {
+        self.lo.is_finite() && self.hi.is_finite()
+    }
+
+    /// Like the plain operators, but dividing by exactly zero is an error.
+    fn arithmetic(
+        self,
+        op: OpVal,
+        other: Self,
+        _settings: &Settings,
+    ) -> Result<Self, InterpretError<'static>> {
+        if other == Interval::zero() && (op == OpVal::Div || op == OpVal::Mod) {
+            return Err(InterpretError::DivisionByZero);
+        }
+        Ok(match op {
+            OpVal::Add => self + other,
+            OpVal::Sub => self - other,
+            OpVal::Mul => self * other,
+            OpVal::Div => self / other,
+            OpVal::Mod => self % other,
+            OpVal::Pow => self.pow(other),
+            _ => unreachable!(),
+        })
+    }
+
+    fn negate(self, _: &Settings) -> Result<Self, InterpretError<'static>> {
+        Ok(-self)
+    }
+
+    fn degrees(self, settings: &Settings) -> Result<Self, InterpretError<'static>> {
+        Ok(settings.angle_mode.from_degrees(self))
+    }
+}
+
+/// Every function encloses its results. `from_f64` widens numbers that are not whole to the
+/// neighbouring floats, since they are usually rounded, like `std::f64::consts::PI`, and
+/// `to_f64` gives the midpoint.
+impl Real for Interval {
+    fn from_f64(x: f64) -> Self {
+        if x.fract() == 0.0 || !x.is_finite() {
+            Interval::point(x)
+        } else {
+            Interval::around(x)
+        }
+    }
+    fn to_f64(&self) -> f64 {
+        self.mid()
+    }
+
+    fn sqrt(self) -> Self {
+        let x = self.clamp(0.0, f64::INFINITY);
+        if x.is_nan() {
+            return x;
+        }
+        Interval {
+            lo: sqrt(x.lo, false),
+            hi: sqrt(x.hi, true),
+        }
+    }
+    fn cbrt(self) -> Self {
+        self.increasing(f64::cbrt)
+    }
+    fn exp(self) -> Self {
+        // Unlike other functions, exp gives zero for results too small for f64
+        Interval {
+            lo: self.lo.exp().next_down().max(0.0),
+            hi: self.hi.exp().next_up(),
+        }
+    }
+    fn ln(self) -> Self {
+        self.clamp(0.0, f64::INFINITY).increasing(f64::ln)
+    }
+    fn log2(self) -> Self {
+        self.clamp(0.0, f64::INFINITY).increasing(f64::log2)
+    }
+    fn log10(self) -> Self {
+        self.clamp(0.0, f64::INFINITY).increasing(f64::log10)
+    }
+    fn floor(self) -> Self {
+        Interval::new(self.lo.floor(), self.hi.floor())
+    }
+    fn ceil(self) -> Self {
+        Interval::new(self.lo.ceil(), self.hi.ceil())
+    }
+    fn round(self) -> Self {
+        Interval::new(self.lo.round(), self.hi.round())
+    }
+    fn trunc(self) -> Self {
+        Interval::new(self.lo.trunc(), self.hi.trunc())
+    }
+
+    fn sin(self) -> Self {
+        self.periodic(f64::sin, FRAC_PI_2)
+    }
+    fn cos(self) -> Self {
+        self.periodic(f64::cos, 0.0)
+    }
+    fn tan(self) -> Self {
+        if self.is_nan() {
+            Interval::nan()
+        } else if self.width() >= PI || self.may_contain_periodic(FRAC_PI_2, PI) {
+            // A pole, where tan goes from infinity to -infinity
+            Interval::entire()
+        } else {
+            self.increasing(f64::tan)
+        }
+    }
+    fn asin(self) -> Self {
+        self.clamp(-1.0, 1.0).increasing(f64::asin)
+    }
+    fn acos(self) -> Self {
+        self.clamp(-1.0, 1.0).decreasing(f64::acos)
+    }
+    fn atan(self) -> Self {
+        self.increasing(f64::atan)
+    }
+    fn sinh(self) -> Self {
+        self.increasing(f64::sinh)
+    }
+    fn cosh(self) -> Self {
+        let x = self.abs().increasing(f64::cosh);
+        Interval::new(x.lo.max(1.0), x.hi)
+    }
+    fn tanh(self) -> Self {
+        let x = self.increasing(f64::tanh);
+        Interval::new(x.lo.max(-1.0), x.hi.min(1.0))
+    }
+    fn asinh(self) -> Self {
+        self.increasing(f64::asinh)
+    }
+    fn acosh(self) -> Self {
+        let x = self.clamp(1.0, f64::INFINITY).increasing(f64::acosh);
+        Interval::new(x.lo.max(0.0), x.hi)
+    }
+    fn atanh(self) -> Self {
+        self.clamp(-1.0, 1.0).increasing(f64::atanh)
+    }
+
+    /// The angle of every point in the box of `other` (x) and `self` (y). A box that contains
+    /// the origin or crosses the negative x axis, where the angle jumps from π to -π, gives
+    /// `[-π, π]`.
+    fn atan2(self, other: Self) -> Self {
+        let (y, x) = (self, other);
+        if y.is_nan() || x.is_nan() {
+            return Interval::nan();
+        }
+        if x.lo <= 0.0 && y.contains(0.0) {
+            return Interval::new(-PI.next_up(), PI.next_up());
+        }
+        // Otherwise the angle is monotonic along each edge, so the corners are the extremes
+        let angles = [
+            y.lo.atan2(x.lo),
+            y.lo.atan2(x.hi),
+            y.hi.atan2(x.lo),
+            y.hi.atan2(x.hi),
+        ];
+        Interval {
+            lo: next_down(angles.iter().cloned().fold(f64::INFINITY, f64::min)),
+            hi: next_up(angles.iter().cloned().fold(f64::NEG_INFINITY, f64::max)),
+        }
+    }
+    fn hypot(self, other: Self) -> Self {
+        let (x, y) = (self.abs(), other.abs());
+        Interval {
+            lo: next_down(x.lo.hypot(y.lo)),
+            hi: next_up(x.hi.hypot(y.hi)),
+        }
+    }
+
+    /// Gamma is only bounded for intervals of positive numbers, or single numbers.
+    fn gamma(self) -> Self {
+        if self.lo > 0.0 {
+            self.unimodal(gamma, GAMMA_MIN)
+        } else if self.lo == self.hi {
+            Interval::new(gamma(self.lo, false), gamma(self.lo, true))
+        } else {
+            Interval::entire()
+        }
+    }
+    /// Like `gamma`, only bounded for intervals of positive numbers, or single numbers.
+    fn ln_gamma(self) -> Self {
+        if self.lo > 0.0 {
+            self.unimodal(ln_gamma, LN_GAMMA_MIN)
+        } else if self.lo == self.hi {
+            Interval::new(ln_gamma(self.lo, false), ln_gamma(self.lo, true))
+        } else {
+            Interval::entire()
+        }
+    }
+}
+
+impl PartialOrd for Interval {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        if self.hi < other.lo {
+            Some(Ordering::Less)
+        } else if self.lo > other.hi {
+            Some(Ordering::Greater)
+        } else if self == other && self.lo == self.hi {
+            Some(Ordering::Equal)
+        } else {
+            None
+        }
+    }
+}
+
+/// Parses a number into a single number if it is exact, or the interval around it.
+impl FromStr for Interval {
+    type Err = ParseFloatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let x: f64 = s.parse()?;
+        if !x.is_finite() || is_exact(s, x) {
+            Ok(Interval::point(x))
+        } else {
+            Ok(Interval::around(x))
+        }
+    }
+}
+
+/// Whether the decimal number `s` is exactly `x`.
+fn is_exact(s: &str, x: f64) -> bool {
+    // The number without leading and trailing zeros, and where its point is
+    fn normalize(s: &str) -> (String, usize) {
+        let (whole, fraction) = s.split_once('.').unwrap_or((s, ""));
+        let whole = whole.trim_start_matches('0');
+        let digits = format!("{}{}", whole, fraction);
+        let significant = digits.trim_matches('0');
+        if significant.is_empty() {
+            return (String::new(), 0);
+        }
+        let zeros = digits.len() - digits.trim_start_matches('0').len();
+        (significant.to_owned(), whole.len().wrapping_sub(zeros))
+    }
+    // Every f64 has an exact decimal expansion of at most 1074 places after the point
+    normalize(s) == normalize(&format!("{:.1074}", x.abs()))
+}
+
+/// Intervals are written as calls of the `interval` function, like `interval(1.9, 2.1)`, since
+/// `[1.9, 2.1]` is a list. Reading the text back gives an interval that contains this one, and
+/// the same interval if its bounds are written exactly.
+impl Display for Interval {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if self.lo == self.hi || self.is_nan() {
+            write!(f, "{}", self.lo)
+        } else {
+            write!(f, "interval({}, {})", self.lo, self.hi)
+        }
+    }
+}
+
+impl Add for Interval {
+    type Output = Interval;
+    fn add(self, other: Interval) -> Interval {
+        Interval {
+            lo: add(self.lo, other.lo, false),
+            hi: add(self.hi, other.hi, true),
+        }
+    }
+}
+
+impl Sub for Interval {
+    type Output = Interval;
+    fn sub(self, other: Interval) -> Interval {
+        Interval {
+            lo: add(self.lo, -other.hi, false),
+            hi: add(self.hi, -other.lo, true),
+        }
+    }
+}
+
+impl Mul for Interval {
+    type Output = Interval;
+    fn mul(self, other: Interval) -> Interval {
+        if self.is_nan() || other.is_nan() {
+            return Interval::nan();
+        }
+        let pairs = [
+            (self.lo, other.lo),
+            (self.lo, other.hi),
+            (self.hi, other.lo),
+            (self.hi, other.hi),
+        ];
+        let lo = pairs
+            .iter()
+            .map(|&(a, b)| mul(a, b, false))
+            .fold(f64::INFINITY, f64::min);
+        let hi = pairs
+            .iter()
+            .map(|&(a, b)| mul(a, b, true))
+            .fold(f64::NEG_INFINITY, f64::max);
+        Interval { lo, hi }
+    }
+}
+
+/// Dividing by an interval containing zero gives the hull of all quotients, and dividing by
+/// exactly zero gives NaN.
+impl Div for Interval {
+    type Output = Interval;
+    fn div(self, other: Interval) -> Interval {
+        if self.is_nan() || other.is_nan() || (other.lo == 0.0 && other.hi == 0.0) {
+            return Interval::nan();
+        }
+        if other.contains(0.0) {
+            return if self.contains(0.0) || (other.lo < 0.0 && other.hi > 0.0) {
+                Interval::entire()
+            } else if other.lo == 0.0 {
+                // Dividing by (0, hi]
+                if self.hi < 0.0 {
+                    Interval::new(f64::NEG_INFINITY, div(self.hi, other.hi, true))
+                } else {
+                    Interval::new(div(self.lo, other.hi, false), f64::INFINITY)
+                }
+            } else {
+                // Dividing by [lo, 0)
+                if self.hi < 0.0 {
+                    Interval::new(div(self.hi, other.lo, false), f64::INFINITY)
+                } else {
+                    Interval::new(f64::NEG_INFINITY, div(self.lo, other.lo, true))
+                }
+            };
+        }
+        let pairs = [
+            (self.lo, other.lo),
+            (self.lo, other.hi),
+            (self.hi, other.lo),
+            (self.hi, other.hi),
+        ];
+        let lo = pairs
+            .iter()
+            .map(|&(a, b)| div(a, b, false))
+            .fold(f64::INFINITY, f64::min);
+        let hi = pairs
+            .iter()
+            .map(|&(a, b)| div(a, b, true))
+            .fold(f64::NEG_INFINITY, f64::max);
+        Interval { lo, hi }
+    }
+}
+
+/// The remainder of truncating division, like `%` of `f64`. It is exact for single numbers,
+/// and otherwise bounded by the dividend and the largest divisor.
+impl Rem for Interval {
+    type Output = Interval;
+    fn rem(self, other: Interval) -> Interval {
+        if self.lo == self.hi && other.lo == other.hi {
+            return Interval::point(self.lo % other.lo);
+        }
+        let largest = other.abs().hi;
+        Interval::new(
+            self.lo.max(-largest).min(0.0),
+            self.hi.min(largest).max(0.0),
+        )
+    }
+}
+
+impl Neg for Interval {
+    type Output = Interval;
+    fn neg(self) -> Interval {
+        Interval {
+            lo: -self.hi,
+            hi: -self.lo,
+        }
+    }
+}
+
+macro_rules! impl_interval_assign_op {
+    ($assign_op:ident, $assign_method:ident, $op:tt) => {
+        impl $assign_op for Interval {
+            fn $assign_method(&mut self, other: Interval) {
+                *self = *self $op other;
+            }
+        }
+    };
+}
+impl_interval_assign_op!(AddAssign, add_assign, +);
+impl_interval_assign_op!(SubAssign, sub_assign, -);
+impl_interval_assign_op!(MulAssign, mul_assign, *);
+impl_interval_assign_op!(DivAssign, div_assign, /);
@@ -1,16 +1,106 @@
+#[cfg(feature = "num")]
+mod bignum;
+mod complex;
+#[cfg(feature = "num")]
+mod decimal;
+mod diagnostic;
+mod dual;
+mod error;
 mod expr;
+mod format;
 mod interpreter;
+mod interval;
+mod matrix;
+mod modular;
 mod parser;
+mod quantity;
+mod stdlib;
 mod tokenizer;
+mod uncertain;
+mod value;
 
+#[cfg(feature = "num")]
+pub use bignum::*;
+pub use complex::*;
+#[cfg(feature = "num")]
+pub use decimal::*;
+pub use diagnostic::*;
+pub use dual::*;
+pub use error::*;
 pub use expr::*;
+pub use format::*;
 pub use interpreter::*;
+pub use interval::*;
+pub use matrix::*;
+pub use modular::*;
 pub use parser::*;
+pub use quantity::*;
 pub use tokenizer::*;
+pub use uncertain::*;
+pub use value::*;
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
 use std::fmt::Debug;
-use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Rem, Sub, SubAssign};
-use std::str::FromStr;
+use std::num::{Saturating, Wrapping};
+use std::ops::{
+    Add, AddAssign, BitAnd, BitOr, BitXor, Div, DivAssign, Mul, MulAssign, Neg, Not, Rem, Sub,
+    SubAssign,
+};
+
+/// How integer arithmetic handles results that do not fit in the number type.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ArithmeticMode {
+    /// Overflow is an `InterpretError::Overflow`.
+    #[default]
+    Checked,
+    /// Results wrap around at the bounds of the type.
+    Wrapping,
+    /// Results are clamped to the bounds of the type.
+    Saturating,
+}
+
+/// How a result is rounded to the precision of a `DecimalContext`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum RoundingMode {
+    /// To the nearest, and ties to the even neighbor. Also known as banker's rounding.
+    #[default]
+    HalfEven,
+    /// To the nearest, and ties away from zero.
+    HalfUp,
+    /// To the nearest, and ties toward zero.
+    HalfDown,
+    /// Away from zero.
+    Up,
+    /// Toward zero, truncating.
+    Down,
+    /// Toward positive infinity.
+    Ceiling,
+    /// Toward negative infinity.
+    Floor,
+}
+
+/// The precision and rounding of decimal arithmetic.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DecimalContext {
+    /// The number of significant digits results are rounded to, at least 1.
+    pub precision: usize,
+    pub rounding: RoundingMode,
+}
+
+/// 28 significant digits, rounding half to even.
+impl Default for DecimalContext {
+    fn default() -> Self {
+        DecimalContext {
+            precision: 28,
+            rounding: RoundingMode::HalfEven,
+        }
+    }
+}
 
 /// Defines the minimum operations and definitions to parse and evaluate expressions.
 pub trait Num:
@@ -18,13 +108,11 @@ pub trait Num:
     + Clone
     + PartialEq
     + PartialOrd
-    + FromStr
     + Add<Output = Self>
     + Sub<Output = Self>
     + Mul<Output = Self>
     + Div<Output = Self>
     + Rem<Output = Self>
-    + Neg<Output = Self>
     + AddAssign
     + SubAssign
     + MulAssign
@@ -38,36 +126,431 @@ pub trait Num:
     fn is_whole(&self) -> bool;
     /// Returns number to the power of `other`.
     fn pow(self, other: Self) -> Self;
+    /// Parses a number literal like `12.5`, or returns `None` if the type cannot represent it.
+    /// Types implementing `FromStr` can return `s.parse().ok()`.
+    fn parse_literal(s: &str) -> Option<Self>;
+
+    /// Applies the arithmetic operator `op` (`+`, `-`, `*`, `/`, `%` or `^`) to the number and
+    /// `other`. This is what the interpreter uses to evaluate operators, with its settings.
+    /// Integers report division by zero and negative exponents as errors, and handle overflow
+    /// according to the arithmetic mode. The default implementation ignores the settings and
+    /// uses the plain operators.
+    fn arithmetic(
+        self,
+        op: OpVal,
+        other: Self,
+        settings: &Settings,
+    ) -> Result<Self, InterpretError<'static>> {
+        let _ = settings;
+        Ok(match op {
+            OpVal::Add => self + other,
+            OpVal::Sub => self - other,
+            OpVal::Mul => self * other,
+            OpVal::Div => self / other,
+            OpVal::Mod => self % other,
+            OpVal::Pow => self.pow(other),
+            _ => unreachable!(),
+        })
+    }
+
+    /// Returns false for NaN and infinities, which strict IEEE mode reports as errors. The
+    /// default implementation always returns true.
+    fn is_finite(&self) -> bool {
+        true
+    }
+
+    /// Returns false if the number displays as more than one term, like `3+4i` or `5 km`, so a
+    /// printed `Expr` wraps it in parentheses as an operand. Negative numbers are parenthesized
+    /// either way. The default implementation returns true.
+    fn is_atomic(&self) -> bool {
+        true
+    }
+
+    /// Negates the number, handling overflow like `arithmetic`. The default implementation
+    /// subtracts the number from zero with `arithmetic`, so types without `Neg`, like unsigned
+    /// integers, can be negated too.
+    fn negate(self, settings: &Settings) -> Result<Self, InterpretError<'static>> {
+        Self::zero().arithmetic(OpVal::Sub, self, settings)
+    }
+
+    /// Returns the number with a standard uncertainty of `uncertainty`, which is what `±`
+    /// evaluates to, or `None` if the type cannot represent uncertainty. The tokenizer only
+    /// reads `+-` as `±` for types that can. The default implementation returns `None`.
+    fn plus_minus(self, uncertainty: Self) -> Option<Self> {
+        let _ = uncertainty;
+        None
+    }
+
+    /// Converts the number to the unit of `unit`, which is what `to` evaluates to. Types with
+    /// units return `InterpretError::DimensionMismatch` if the dimensions differ. The default
+    /// implementation returns `OutOfDomain`, since plain numbers have no units.
+    fn convert(self, unit: Self) -> Result<Self, InterpretError<'static>> {
+        let _ = unit;
+        Err(InterpretError::OutOfDomain(OpVal::To.symbol()))
+    }
+
+    /// Returns one of the unit `symbol`, for types with units. The interpreter uses it for a
+    /// function's name used like a variable, since a unit can share its symbol with a function,
+    /// like the minute `min`. The default implementation returns `None`.
+    fn unit_named(symbol: &str) -> Option<Self> {
+        let _ = symbol;
+        None
+    }
+
+    /// Converts an angle of `self` degrees to the angle mode of `settings`, which is what `°`
+    /// evaluates to. The default implementation only handles degree mode, where the number is
+    /// unchanged, and returns `OutOfDomain` otherwise.
+    fn degrees(self, settings: &Settings) -> Result<Self, InterpretError<'static>> {
+        match settings.angle_mode {
+            AngleMode::Degrees => Ok(self),
+            _ => Err(InterpretError::OutOfDomain(OpVal::Degree.symbol())),
+        }
+    }
+}
+
+/// Defines a method of `Real` that calls the `f64` method of the same name.
+macro_rules! real_via_f64 {
+    ($($name:ident),*) => {
+        $(
+            fn $name(self) -> Self {
+                Self::from_f64(self.to_f64().$name())
+            }
+        )*
+    };
+    ($($name:ident(other)),*) => {
+        $(
+            fn $name(self, other: Self) -> Self {
+                Self::from_f64(self.to_f64().$name(other.to_f64()))
+            }
+        )*
+    };
+}
+
+/// Implements the methods of `Real` through their derivatives, for types that carry derivatives
+/// along with a value. The type needs the methods `apply(&self, f, derivative)`, which applies
+/// a function of one argument, and `combine(&self, d_self, other, d_other, value)`, which
+/// makes a value depending on two numbers with the given derivatives.
+macro_rules! real_via_derivatives {
+    () => {
+        fn sqrt(self) -> Self {
+            self.apply(f64::sqrt, |x| 0.5 / x.sqrt())
+        }
+        fn cbrt(self) -> Self {
+            self.apply(f64::cbrt, |x| 1.0 / (3.0 * x.cbrt().powi(2)))
+        }
+        fn exp(self) -> Self {
+            self.apply(f64::exp, f64::exp)
+        }
+        fn ln(self) -> Self {
+            self.apply(f64::ln, |x| 1.0 / x)
+        }
+        fn log2(self) -> Self {
+            self.apply(f64::log2, |x| 1.0 / (x * std::f64::consts::LN_2))
+        }
+        fn log10(self) -> Self {
+            self.apply(f64::log10, |x| 1.0 / (x * std::f64::consts::LN_10))
+        }
+        fn floor(self) -> Self {
+            self.apply(f64::floor, |_| 0.0)
+        }
+        fn ceil(self) -> Self {
+            self.apply(f64::ceil, |_| 0.0)
+        }
+        fn round(self) -> Self {
+            self.apply(f64::round, |_| 0.0)
+        }
+        fn trunc(self) -> Self {
+            self.apply(f64::trunc, |_| 0.0)
+        }
+
+        fn sin(self) -> Self {
+            self.apply(f64::sin, f64::cos)
+        }
+        fn cos(self) -> Self {
+            self.apply(f64::cos, |x| -x.sin())
+        }
+        fn tan(self) -> Self {
+            self.apply(f64::tan, |x| 1.0 / x.cos().powi(2))
+        }
+        fn asin(self) -> Self {
+            self.apply(f64::asin, |x| 1.0 / (1.0 - x * x).sqrt())
+        }
+        fn acos(self) -> Self {
+            self.apply(f64::acos, |x| -1.0 / (1.0 - x * x).sqrt())
+        }
+        fn atan(self) -> Self {
+            self.apply(f64::atan, |x| 1.0 / (1.0 + x * x))
+        }
+        fn sinh(self) -> Self {
+            self.apply(f64::sinh, f64::cosh)
+        }
+        fn cosh(self) -> Self {
+            self.apply(f64::cosh, f64::sinh)
+        }
+        fn tanh(self) -> Self {
+            self.apply(f64::tanh, |x| 1.0 / x.cosh().powi(2))
+        }
+        fn asinh(self) -> Self {
+            self.apply(f64::asinh, |x| 1.0 / (x * x + 1.0).sqrt())
+        }
+        fn acosh(self) -> Self {
+            self.apply(f64::acosh, |x| 1.0 / (x * x - 1.0).sqrt())
+        }
+        fn atanh(self) -> Self {
+            self.apply(f64::atanh, |x| 1.0 / (1.0 - x * x))
+        }
+
+        fn atan2(self, other: Self) -> Self {
+            let (y, x) = (self.to_f64(), other.to_f64());
+            let r2 = x * x + y * y;
+            self.combine(x / r2, &other, -y / r2, y.atan2(x))
+        }
+        fn hypot(self, other: Self) -> Self {
+            let h = self.to_f64().hypot(other.to_f64());
+            self.combine(self.to_f64() / h, &other, other.to_f64() / h, h)
+        }
+
+        fn gamma(self) -> Self {
+            use $crate::stdlib::{digamma, gamma};
+            self.apply(gamma, |x| gamma(x) * digamma(x))
+        }
+        fn ln_gamma(self) -> Self {
+            use $crate::stdlib::{digamma, ln_gamma};
+            self.apply(ln_gamma, digamma)
+        }
+    };
+}
+pub(crate) use real_via_derivatives;
+
+/// Numbers that support roots, exponentials, logarithms, trigonometry and rounding, which
+/// `install_real_stdlib` makes available as functions. The methods are named and behave like
+/// those of `f64`, with trigonometry in radians.
+///
+/// Only `from_f64` and `to_f64` are required. Every other method defaults to converting to
+/// `f64` and back, so types that are more precise than `f64` or track more than a value
+/// should override them.
+pub trait Real: Num + Neg<Output = Self> {
+    fn from_f64(x: f64) -> Self;
+    fn to_f64(&self) -> f64;
+
+    real_via_f64!(sqrt, cbrt, exp, ln, log2, log10, floor, ceil, round, trunc);
+    real_via_f64!(sin, cos, tan, asin, acos, atan, sinh, cosh, tanh, asinh, acosh, atanh);
+    real_via_f64!(atan2(other), hypot(other));
+
+    /// The gamma function, which extends the factorial so that `gamma(n + 1) = n!`.
+    fn gamma(self) -> Self {
+        Self::from_f64(stdlib::gamma(self.to_f64()))
+    }
+
+    /// The natural logarithm of the absolute value of the gamma function.
+    fn ln_gamma(self) -> Self {
+        Self::from_f64(stdlib::ln_gamma(self.to_f64()))
+    }
+}
+
+/// Integers with bitwise operations, which `install_integer_stdlib` makes available as
+/// functions along with number theory such as `gcd`.
+pub trait Integer:
+    Num + BitAnd<Output = Self> + BitOr<Output = Self> + BitXor<Output = Self> + Not<Output = Self>
+{
+    /// Shifts the bits left by `bits`, or returns `None` if `bits` is negative or too large
+    /// for the type.
+    fn shift_left(self, bits: Self) -> Option<Self>;
+    /// Shifts the bits right by `bits`, or returns `None` if `bits` is negative or too large
+    /// for the type.
+    fn shift_right(self, bits: Self) -> Option<Self>;
 }
 
 macro_rules! impl_num_for_integer {
-    ($itype:ty) => {
-        impl Num for $itype {
-            #[inline(always)]
-            fn zero() -> Self {
-                0
+    ($($itype:ty),*) => {
+        $(
+            impl Num for $itype {
+                #[inline(always)]
+                fn zero() -> Self {
+                    0
+                }
+                #[inline(always)]
+                fn one() -> Self {
+                    1
+                }
+                #[inline(always)]
+                fn is_whole(&self) -> bool {
+                    true
+                }
+                fn parse_literal(s: &str) -> Option<Self> {
+                    s.parse().ok()
+                }
+                /// Wraps on overflow. A negative exponent gives 0 unless the number is 1 or -1,
+                /// like `1 / self.pow(-other)` would.
+                #[inline(always)]
+                fn pow(self, other: Self) -> Self {
+                    let settings = Settings {
+                        arithmetic_mode: ArithmeticMode::Wrapping,
+                        ..Settings::default()
+                    };
+                    self.arithmetic(OpVal::Pow, other, &settings).unwrap_or(0)
+                }
+
+                fn arithmetic(
+                    self,
+                    op: OpVal,
+                    other: Self,
+                    settings: &Settings,
+                ) -> Result<Self, InterpretError<'static>> {
+                    use ArithmeticMode::*;
+                    let mode = settings.arithmetic_mode;
+                    if other == 0 && (op == OpVal::Div || op == OpVal::Mod) {
+                        return Err(InterpretError::DivisionByZero);
+                    }
+                    let result = match (op, mode) {
+                        (OpVal::Add, Checked) => self.checked_add(other),
+                        (OpVal::Add, Wrapping) => Some(self.wrapping_add(other)),
+                        (OpVal::Add, Saturating) => Some(self.saturating_add(other)),
+                        (OpVal::Sub, Checked) => self.checked_sub(other),
+                        (OpVal::Sub, Wrapping) => Some(self.wrapping_sub(other)),
+                        (OpVal::Sub, Saturating) => Some(self.saturating_sub(other)),
+                        (OpVal::Mul, Checked) => self.checked_mul(other),
+                        (OpVal::Mul, Wrapping) => Some(self.wrapping_mul(other)),
+                        (OpVal::Mul, Saturating) => Some(self.saturating_mul(other)),
+                        // Only MIN / -1 overflows
+                        (OpVal::Div, Checked) => self.checked_div(other),
+                        (OpVal::Div, Wrapping) => Some(self.wrapping_div(other)),
+                        (OpVal::Div, Saturating) => Some(self.saturating_div(other)),
+                        // MIN % -1 is 0, though computing it overflows in Rust
+                        (OpVal::Mod, _) => Some(self.wrapping_rem(other)),
+                        (OpVal::Pow, _) => {
+                            // 0, 1 and -1 are the numbers whose powers stay small
+                            let small = self.checked_mul(self).map_or(false, |square| square <= 1);
+                            if other < Self::zero() {
+                                // Of which 1 and -1 have whole reciprocals
+                                return if small && self != 0 {
+                                    Ok(if other % 2 == 0 { 1 } else { self })
+                                } else {
+                                    Err(InterpretError::NegativeExponent)
+                                };
+                            }
+                            match (u32::try_from(other), mode) {
+                                (Ok(exp), Checked) => self.checked_pow(exp),
+                                (Ok(exp), Saturating) => Some(self.saturating_pow(exp)),
+                                (Err(_), Checked) if !small => None,
+                                (Err(_), Saturating) if !small => {
+                                    if self < Self::zero() && other % 2 == 1 {
+                                        Some(<$itype>::MIN)
+                                    } else {
+                                        Some(<$itype>::MAX)
+                                    }
+                                }
+                                // Exponentiation by squaring handles exponents of any size
+                                _ => {
+                                    let (mut base, mut exp, mut result) = (self, other, 1 as $itype);
+                                    while exp > 0 {
+                                        if exp % 2 == 1 {
+                                            result = result.wrapping_mul(base);
+                                        }
+                                        base = base.wrapping_mul(base);
+                                        exp /= 2;
+                                    }
+                                    Some(result)
+                                }
+                            }
+                        }
+                        _ => unreachable!(),
+                    };
+                    result.ok_or(InterpretError::Overflow)
+                }
+
+                /// Negating a positive unsigned integer overflows. It wraps around to
+                /// `MAX + 1 - self` and saturates at 0.
+                fn negate(self, settings: &Settings) -> Result<Self, InterpretError<'static>> {
+                    match settings.arithmetic_mode {
+                        ArithmeticMode::Checked => self.checked_neg().ok_or(InterpretError::Overflow),
+                        ArithmeticMode::Wrapping => Ok(self.wrapping_neg()),
+                        ArithmeticMode::Saturating => Ok(Self::zero().saturating_sub(self)),
+                    }
+                }
             }
-            #[inline(always)]
-            fn one() -> Self {
-                1
+
+            impl Integer for $itype {
+                fn shift_left(self, bits: Self) -> Option<Self> {
+                    u32::try_from(bits)
+                        .ok()
+                        .and_then(|bits| self.checked_shl(bits))
+                }
+                fn shift_right(self, bits: Self) -> Option<Self> {
+                    u32::try_from(bits)
+                        .ok()
+                        .and_then(|bits| self.checked_shr(bits))
+                }
             }
-            #[inline(always)]
-            fn is_whole(&self) -> bool {
-                true
+        )*
+    };
+}
+impl_num_for_integer!(i8, i16, i32, i64, i128, isize);
+impl_num_for_integer!(u8, u16, u32, u64, u128, usize);
+
+/// Implements `Num` and `Integer` for `$wrapper<$itype>`, which always uses the arithmetic mode
+/// `$mode` whatever the settings say.
+macro_rules! impl_num_for_integer_wrapper {
+    ($wrapper:ident, $mode:ident, $($itype:ty),*) => {
+        $(
+            impl Num for $wrapper<$itype> {
+                fn zero() -> Self {
+                    $wrapper(0)
+                }
+                fn one() -> Self {
+                    $wrapper(1)
+                }
+                fn is_whole(&self) -> bool {
+                    true
+                }
+                fn parse_literal(s: &str) -> Option<Self> {
+                    s.parse().ok().map($wrapper)
+                }
+                /// A negative exponent gives 0 unless the number is 1 or -1.
+                fn pow(self, other: Self) -> Self {
+                    self.arithmetic(OpVal::Pow, other, &Settings::default())
+                        .unwrap_or($wrapper(0))
+                }
+
+                fn arithmetic(
+                    self,
+                    op: OpVal,
+                    other: Self,
+                    _: &Settings,
+                ) -> Result<Self, InterpretError<'static>> {
+                    let settings = Settings {
+                        arithmetic_mode: ArithmeticMode::$mode,
+                        ..Settings::default()
+                    };
+                    self.0.arithmetic(op, other.0, &settings).map($wrapper)
+                }
+
+                fn negate(self, _: &Settings) -> Result<Self, InterpretError<'static>> {
+                    let settings = Settings {
+                        arithmetic_mode: ArithmeticMode::$mode,
+                        ..Settings::default()
+                    };
+                    self.0.negate(&settings).map($wrapper)
+                }
             }
-            #[inline(always)]
-            fn pow(self, other: Self) -> Self {
-                self.wrapping_pow(other as u32) // Wraps on overflow...
+
+            impl Integer for $wrapper<$itype> {
+                fn shift_left(self, bits: Self) -> Option<Self> {
+                    self.0.shift_left(bits.0).map($wrapper)
+                }
+                fn shift_right(self, bits: Self) -> Option<Self> {
+                    self.0.shift_right(bits.0).map($wrapper)
+                }
             }
-        }
+        )*
     };
 }
-impl_num_for_integer!(i8);
-impl_num_for_integer!(i16);
-impl_num_for_integer!(i32);
-impl_num_for_integer!(i64);
-impl_num_for_integer!(i128);
-impl_num_for_integer!(isize);
+impl_num_for_integer_wrapper!(
+    Wrapping, Wrapping, i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize
+);
+impl_num_for_integer_wrapper!(
+    Saturating, Saturating, i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize
+);
 
 macro_rules! impl_num_for_float {
     ($ftype:ty) => {
@@ -84,11 +567,60 @@ macro_rules! impl_num_for_float {
             fn is_whole(&self) -> bool {
                 self.fract() == 0.0
             }
+            fn parse_literal(s: &str) -> Option<Self> {
+                s.parse().ok()
+            }
             #[inline(always)]
             fn pow(self, other: Self) -> Self {
                 self.powf(other) // inf or -inf if overflowed...
             }
+            #[inline(always)]
+            fn is_finite(&self) -> bool {
+                <$ftype>::is_finite(*self)
+            }
+            #[inline(always)]
+            fn negate(self, _: &Settings) -> Result<Self, InterpretError<'static>> {
+                Ok(-self)
+            }
+            fn degrees(self, settings: &Settings) -> Result<Self, InterpretError<'static>> {
+                Ok(settings.angle_mode.from_degrees(self))
+            }
         }
+
+        impl Real for $ftype {
+            #[inline(always)]
+            fn from_f64(x: f64) -> Self {
+                x as $ftype
+            }
+            #[inline(always)]
+            fn to_f64(&self) -> f64 {
+                *self as f64
+            }
+            impl_real_for_float!(
+                $ftype, sqrt, cbrt, exp, ln, log2, log10, floor, ceil, round, trunc, sin, cos, tan,
+                asin, acos, atan, sinh, cosh, tanh, asinh, acosh, atanh
+            );
+            #[inline(always)]
+            fn atan2(self, other: Self) -> Self {
+                <$ftype>::atan2(self, other)
+            }
+            #[inline(always)]
+            fn hypot(self, other: Self) -> Self {
+                <$ftype>::hypot(self, other)
+            }
+        }
+    };
+}
+
+/// Defines methods of `Real` with the inherent methods of a float type.
+macro_rules! impl_real_for_float {
+    ($ftype:ty, $($name:ident),*) => {
+        $(
+            #[inline(always)]
+            fn $name(self) -> Self {
+                <$ftype>::$name(self)
+            }
+        )*
     };
 }
 impl_num_for_float!(f32);
@@ -0,0 +1,388 @@
+use crate::{InterpretError, Num, OpVal, Settings};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display, Formatter};
+
+/// A matrix of numbers, stored row by row. Literals are written as lists of rows, like
+/// `[[1, 2], [3, 4]]`.
+///
+/// The operations take the settings of the interpreter and return its errors: matrices of the
+/// wrong shapes are a `ShapeMismatch` or `TypeMismatch` error, and inverting a singular matrix
+/// is an `OutOfDomain` error. Elimination only divides exactly until the last step, so inverses
+/// and solutions that are whole are exact for integer types too, and others are an
+/// `OutOfDomain` error.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Matrix<N> {
+    rows: usize,
+    cols: usize,
+    elements: Vec<N>,
+}
+
+impl<N: Num> Matrix<N> {
+    /// Builds a matrix from its rows, or returns `None` if they differ in length or are empty,
+    /// or there are none.
+    pub fn from_rows(rows: Vec<Vec<N>>) -> Option<Matrix<N>> {
+        let cols = rows.first()?.len();
+        if cols == 0 || rows.iter().any(|row| row.len() != cols) {
+            return None;
+        }
+        Some(Matrix {
+            rows: rows.len(),
+            cols,
+            elements: rows.into_iter().flatten().collect(),
+        })
+    }
+
+    /// The `n`×`n` matrix with ones on its diagonal and zeros elsewhere.
+    pub fn identity(n: usize) -> Matrix<N> {
+        let elements = (0..n * n)
+            .map(|i| if i / n == i % n { N::one() } else { N::zero() })
+            .collect();
+        Matrix {
+            rows: n,
+            cols: n,
+            elements,
+        }
+    }
+
+    /// The list as a matrix with one column.
+    pub(crate) fn column(elements: Vec<N>) -> Matrix<N> {
+        Matrix {
+            rows: elements.len(),
+            cols: 1,
+            elements,
+        }
+    }
+
+    pub(crate) fn into_elements(self) -> Vec<N> {
+        self.elements
+    }
+
+    /// A matrix of the same shape with the elements `elements`.
+    pub(crate) fn with_elements(&self, elements: Vec<N>) -> Matrix<N> {
+        Matrix {
+            rows: self.rows,
+            cols: self.cols,
+            elements,
+        }
+    }
+
+    pub fn transpose(&self) -> Matrix<N> {
+        let elements = (0..self.rows * self.cols)
+            .map(|i| self.get(i % self.rows, i / self.rows).clone())
+            .collect();
+        Matrix {
+            rows: self.cols,
+            cols: self.rows,
+            elements,
+        }
+    }
+
+    /// The matrix product, which is a `ShapeMismatch` error for `*` unless `other` has as many
+    /// rows as this matrix has columns.
+    pub fn mul(
+        &self,
+        other: &Matrix<N>,
+        settings: &Settings,
+    ) -> Result<Matrix<N>, InterpretError<'static>> {
+        if self.cols != other.rows {
+            return Err(InterpretError::ShapeMismatch(
+                OpVal::Mul.symbol(),
+                self.describe(),
+                other.describe(),
+                0..0,
+            ));
+        }
+        let mut elements = Vec::with_capacity(self.rows * other.cols);
+        for i in 0..self.rows {
+            for j in 0..other.cols {
+                let mut sum = N::zero();
+                for k in 0..self.cols {
+                    let product = self.get(i, k).clone().arithmetic(
+                        OpVal::Mul,
+                        other.get(k, j).clone(),
+                        settings,
+                    )?;
+                    sum = sum.arithmetic(OpVal::Add, product, settings)?;
+                }
+                elements.push(sum);
+            }
+        }
+        Ok(Matrix {
+            rows: self.rows,
+            cols: other.cols,
+            elements,
+        })
+    }
+
+    /// The matrix to the power of a whole number `exponent`, by repeated multiplication. A
+    /// negative exponent raises the inverse. Only square matrices have powers.
+    pub fn pow(
+        &self,
+        exponent: N,
+        settings: &Settings,
+    ) -> Result<Matrix<N>, InterpretError<'static>> {
+        let op = OpVal::Pow.symbol();
+        self.ensure_square(op)?;
+        if !exponent.is_whole() {
+            return Err(InterpretError::OutOfDomain(op));
+        }
+        let (mut base, mut exponent) = if exponent < N::zero() {
+            let inverse = self.eliminate(Matrix::identity(self.rows), op, settings)?;
+            (inverse, exponent.negate(settings)?)
+        } else {
+            (self.clone(), exponent)
+        };
+        let two = N::one() + N::one();
+        let mut result = Matrix::identity(self.rows);
+        while exponent > N::zero() {
+            let bit = exponent.clone() % two.clone();
+            if bit != N::zero() {
+                result = result.mul(&base, settings)?;
+            }
+            exponent = (exponent - bit) / two.clone();
+            if exponent > N::zero() {
+                base = base.mul(&base, settings)?;
+            }
+        }
+        Ok(result)
+    }
+
+    /// The determinant of a square matrix, by fraction-free elimination, which only divides
+    /// exactly and so is exact for integers too.
+    pub fn determinant(&self, settings: &Settings) -> Result<N, InterpretError<'static>> {
+        self.ensure_square("det")?;
+        let n = self.rows;
+        let mut a = self.to_rows();
+        let (mut negative, mut previous) = (false, N::one());
+        for k in 0..n {
+            let pivot = match pivot_row(&a, k, settings) {
+                Some(pivot) => pivot,
+                None => return Ok(N::zero()),
+            };
+            if pivot != k {
+                a.swap(pivot, k);
+                negative = !negative;
+            }
+            for i in k + 1..n {
+                for j in k + 1..n {
+                    let kept = a[i][j]
+                        .clone()
+                        .arithmetic(OpVal::Mul, a[k][k].clone(), settings)?;
+                    let removed =
+                        a[i][k]
+                            .clone()
+                            .arithmetic(OpVal::Mul, a[k][j].clone(), settings)?;
+                    a[i][j] = kept.arithmetic(OpVal::Sub, removed, settings)?.arithmetic(
+                        OpVal::Div,
+                        previous.clone(),
+                        settings,
+                    )?;
+                }
+            }
+            previous = a[k][k].clone();
+        }
+        if negative {
+            previous.negate(settings)
+        } else {
+            Ok(previous)
+        }
+    }
+
+    /// The inverse of a square matrix, which is an `OutOfDomain` error for `inv` if the matrix
+    /// is singular, or if its type cannot represent the inverse exactly, like integers.
+    pub fn inverse(&self, settings: &Settings) -> Result<Matrix<N>, InterpretError<'static>> {
+        self.ensure_square("inv")?;
+        self.eliminate(Matrix::identity(self.rows), "inv", settings)
+    }
+
+    /// The matrix `x` for which `self * x` is `b`, where this matrix is square and `b` has as
+    /// many rows. A singular matrix, or a solution that integers cannot represent exactly, is an
+    /// `OutOfDomain` error for `solve`.
+    pub fn solve(
+        &self,
+        b: &Matrix<N>,
+        settings: &Settings,
+    ) -> Result<Matrix<N>, InterpretError<'static>> {
+        self.ensure_square("solve")?;
+        if b.rows != self.rows {
+            return Err(InterpretError::ShapeMismatch(
+                "solve",
+                self.describe(),
+                b.describe(),
+                0..0,
+            ));
+        }
+        self.eliminate(b.clone(), "solve", settings)
+    }
+
+    /// Solves `self * x = b` by fraction-free Gauss-Jordan elimination with partial pivoting.
+    /// Every division but the last, by the determinant, is exact, and the last is checked when
+    /// the type's division truncates.
+    fn eliminate(
+        &self,
+        b: Matrix<N>,
+        op: &'static str,
+        settings: &Settings,
+    ) -> Result<Matrix<N>, InterpretError<'static>> {
+        let n = self.rows;
+        // The augmented matrix [self | b]
+        let mut a: Vec<Vec<N>> = self
+            .to_rows()
+            .into_iter()
+            .zip(b.to_rows())
+            .map(|(mut row, b_row)| {
+                row.extend(b_row);
+                row
+            })
+            .collect();
+        let mut previous = N::one();
+        for k in 0..n {
+            let pivot = pivot_row(&a, k, settings).ok_or(InterpretError::OutOfDomain(op))?;
+            a.swap(pivot, k);
+            let (pivot_values, pivot) = (a[k].clone(), a[k][k].clone());
+            for (i, row) in a.iter_mut().enumerate() {
+                if i == k {
+                    continue;
+                }
+                let factor = row[k].clone();
+                for (j, x) in row.iter_mut().enumerate() {
+                    let kept = x.clone().arithmetic(OpVal::Mul, pivot.clone(), settings)?;
+                    let removed =
+                        factor
+                            .clone()
+                            .arithmetic(OpVal::Mul, pivot_values[j].clone(), settings)?;
+                    *x = kept.arithmetic(OpVal::Sub, removed, settings)?.arithmetic(
+                        OpVal::Div,
+                        previous.clone(),
+                        settings,
+                    )?;
+                }
+            }
+            previous = pivot;
+        }
+        // The left side is now the determinant times the identity
+        let truncates = division_truncates::<N>(settings);
+        let mut elements = Vec::with_capacity(b.elements.len());
+        for row in a {
+            for x in row.into_iter().skip(n) {
+                let quotient = x
+                    .clone()
+                    .arithmetic(OpVal::Div, previous.clone(), settings)?;
+                if truncates
+                    && quotient
+                        .clone()
+                        .arithmetic(OpVal::Mul, previous.clone(), settings)?
+                        != x
+                {
+                    return Err(InterpretError::OutOfDomain(op));
+                }
+                elements.push(quotient);
+            }
+        }
+        Ok(b.with_elements(elements))
+    }
+
+    /// A `TypeMismatch` error for `op` unless the matrix is square.
+    fn ensure_square(&self, op: &'static str) -> Result<(), InterpretError<'static>> {
+        if self.rows == self.cols {
+            Ok(())
+        } else {
+            Err(InterpretError::TypeMismatch(
+                op,
+                String::from("a square matrix"),
+                self.describe(),
+                0..0,
+            ))
+        }
+    }
+
+    fn to_rows(&self) -> Vec<Vec<N>> {
+        self.elements
+            .chunks(self.cols.max(1))
+            .map(<[N]>::to_vec)
+            .collect()
+    }
+}
+
+impl<N> Matrix<N> {
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> &N {
+        &self.elements[row * self.cols + col]
+    }
+
+    pub fn row(&self, row: usize) -> &[N] {
+        &self.elements[row * self.cols..(row + 1) * self.cols]
+    }
+
+    /// The elements, row by row.
+    pub fn elements(&self) -> &[N] {
+        &self.elements
+    }
+
+    /// Describes the shape for error messages, e.g. "a 2×3 matrix".
+    pub fn describe(&self) -> String {
+        format!("a {}×{} matrix", self.rows, self.cols)
+    }
+}
+
+/// The row from `k` down whose element in column `k` is largest in magnitude, or `None` if
+/// they are all zero.
+fn pivot_row<N: Num>(a: &[Vec<N>], k: usize, settings: &Settings) -> Option<usize> {
+    let magnitude = |x: &N| {
+        if *x < N::zero() {
+            x.clone().negate(settings).unwrap_or_else(|_| x.clone())
+        } else {
+            x.clone()
+        }
+    };
+    let mut pivot: Option<(usize, N)> = None;
+    for (i, row) in a.iter().enumerate().skip(k) {
+        if row[k] == N::zero() {
+            continue;
+        }
+        let size = magnitude(&row[k]);
+        if pivot.as_ref().is_none_or(|(_, largest)| size > *largest) {
+            pivot = Some((i, size));
+        }
+    }
+    pivot.map(|(i, _)| i)
+}
+
+/// Whether dividing numbers of type `N` truncates the quotient, like for integers, which is
+/// when `1 / 2` is zero.
+fn division_truncates<N: Num>(settings: &Settings) -> bool {
+    let half = N::one()
+        .arithmetic(OpVal::Add, N::one(), settings)
+        .and_then(|two| N::one().arithmetic(OpVal::Div, two, settings));
+    matches!(half, Ok(half) if half == N::zero())
+}
+
+/// Matrices are written as lists of rows, like `[[1, 2], [3, 4]]`.
+impl<N: Display> Display for Matrix<N> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "[")?;
+        for (i, row) in self.elements.chunks(self.cols.max(1)).enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "[")?;
+            for (j, n) in row.iter().enumerate() {
+                if j > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{}", n)?;
+            }
+            write!(f, "]")?;
+        }
+        write!(f, "]")
+    }
+}
@@ -0,0 +1,253 @@
+use crate::{InterpretError, Num, OpVal, Settings};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt::{self, Display, Formatter};
+use std::num::ParseIntError;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Rem, Sub, SubAssign};
+use std::str::FromStr;
+
+/// An integer modulo `M`, which must be at least 1, for arithmetic in a finite ring or, with a
+/// prime modulus, a finite field.
+///
+/// `/` multiplies by the modular inverse, which only exists for numbers coprime with `M`.
+/// Dividing by 0 is an `InterpretError::DivisionByZero` and by any other number without an
+/// inverse an `OutOfDomain` error. `^` is fast modular exponentiation. `%` is the remainder of
+/// the residues.
+///
+/// Results are residues from 0 to `M - 1`. Numbers also keep the whole number they stand for,
+/// through `+`, `-`, `*`, `/` that divides exactly and `^`, as long as it fits in an `i128`. `^`
+/// raises to that whole number, so exponents are never reduced and `2^(50 + 50)` is `2^100`,
+/// and a negative exponent raises the inverse. An exponent whose whole number is not known, or
+/// a negative one of a number without an inverse, is an `OutOfDomain` error. Numbers compare
+/// and are equal by their residues.
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Modular<const M: u64> {
+    value: u64,
+    whole: Option<i128>,
+}
+
+impl<const M: u64> Modular<M> {
+    pub fn new(value: u64) -> Modular<M> {
+        Modular {
+            value: value % M,
+            whole: Some(i128::from(value)),
+        }
+    }
+
+    /// The number with the same residue standing for `whole`, if it is known.
+    fn with_whole(self, whole: Option<i128>) -> Modular<M> {
+        Modular { whole, ..self }
+    }
+
+    /// The residue, from 0 to `M - 1`.
+    pub fn value(self) -> u64 {
+        self.value % M
+    }
+
+    /// The number whose product with this one is 1, if there is one.
+    pub fn inverse(self) -> Option<Modular<M>> {
+        // The extended Euclidean algorithm, with the coefficients kept as residues
+        let (mut r0, mut r1) = (M, self.value());
+        let (mut t0, mut t1) = (Modular::<M>::new(0), Modular::<M>::new(1));
+        while r1 != 0 {
+            let q = r0 / r1;
+            (r0, r1) = (r1, r0 - q * r1);
+            (t0, t1) = (t1, t0 - Modular::new(q) * t1);
+        }
+        if r0 == 1 {
+            Some(t0.with_whole(None))
+        } else {
+            None
+        }
+    }
+
+    /// The number to the power of `exponent`, by squaring and multiplying.
+    pub fn pow(self, exponent: u128) -> Modular<M> {
+        let whole = self
+            .whole
+            .zip(u32::try_from(exponent).ok())
+            .and_then(|(whole, exponent)| whole.checked_pow(exponent));
+        let (mut base, mut result, mut exponent) = (self, Modular::new(1), exponent);
+        while exponent > 0 {
+            if exponent % 2 == 1 {
+                result *= base;
+            }
+            base = base * base;
+            exponent /= 2;
+        }
+        result.with_whole(whole)
+    }
+}
+
+impl<const M: u64> From<u64> for Modular<M> {
+    fn from(value: u64) -> Self {
+        Modular::new(value)
+    }
+}
+
+impl<const M: u64> Num for Modular<M> {
+    fn zero() -> Self {
+        Modular::new(0)
+    }
+    fn one() -> Self {
+        Modular::new(1)
+    }
+    fn is_whole(&self) -> bool {
+        true
+    }
+    fn parse_literal(s: &str) -> Option<Self> {
+        s.parse().ok()
+    }
+    /// Like `^`, but 0 where that is an error.
+    fn pow(self, other: Self) -> Self {
+        self.arithmetic(OpVal::Pow, other, &Settings::default())
+            .unwrap_or_else(|_| Modular::new(0))
+    }
+
+    fn arithmetic(
+        self,
+        op: OpVal,
+        other: Self,
+        _: &Settings,
+    ) -> Result<Self, InterpretError<'static>> {
+        Ok(match op {
+            OpVal::Add => self + other,
+            OpVal::Sub => self - other,
+            OpVal::Mul => self * other,
+            OpVal::Div => {
+                if other.value() == 0 {
+                    return Err(InterpretError::DivisionByZero);
+                }
+                if other.inverse().is_none() {
+                    return Err(InterpretError::OutOfDomain(op.symbol()));
+                }
+                self / other
+            }
+            OpVal::Mod => {
+                if other.value() == 0 {
+                    return Err(InterpretError::DivisionByZero);
+                }
+                self % other
+            }
+            OpVal::Pow => match other.whole {
+                Some(exponent) if exponent >= 0 => self.pow(exponent.unsigned_abs()),
+                Some(exponent) => self
+                    .inverse()
+                    .ok_or(InterpretError::OutOfDomain(op.symbol()))?
+                    .pow(exponent.unsigned_abs()),
+                None => return Err(InterpretError::OutOfDomain(op.symbol())),
+            },
+            _ => unreachable!(),
+        })
+    }
+
+    fn negate(self, _: &Settings) -> Result<Self, InterpretError<'static>> {
+        Ok(-self)
+    }
+}
+
+impl<const M: u64> PartialEq for Modular<M> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value() == other.value()
+    }
+}
+
+impl<const M: u64> PartialOrd for Modular<M> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.value().partial_cmp(&other.value())
+    }
+}
+
+/// Parses a whole number, which is kept as the whole number of its residue.
+impl<const M: u64> FromStr for Modular<M> {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Modular::new(s.parse()?))
+    }
+}
+
+impl<const M: u64> Display for Modular<M> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.value())
+    }
+}
+
+impl<const M: u64> Add for Modular<M> {
+    type Output = Modular<M>;
+    fn add(self, other: Modular<M>) -> Modular<M> {
+        let sum = u128::from(self.value()) + u128::from(other.value());
+        Modular::new((sum % u128::from(M)) as u64).with_whole(
+            self.whole
+                .zip(other.whole)
+                .and_then(|(a, b)| a.checked_add(b)),
+        )
+    }
+}
+
+impl<const M: u64> Sub for Modular<M> {
+    type Output = Modular<M>;
+    fn sub(self, other: Modular<M>) -> Modular<M> {
+        self + -other
+    }
+}
+
+impl<const M: u64> Mul for Modular<M> {
+    type Output = Modular<M>;
+    fn mul(self, other: Modular<M>) -> Modular<M> {
+        let product = u128::from(self.value()) * u128::from(other.value());
+        Modular::new((product % u128::from(M)) as u64).with_whole(
+            self.whole
+                .zip(other.whole)
+                .and_then(|(a, b)| a.checked_mul(b)),
+        )
+    }
+}
+
+/// Panics if `other` has no inverse, like integer division by zero.
+impl<const M: u64> Div for Modular<M> {
+    type Output = Modular<M>;
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, other: Modular<M>) -> Modular<M> {
+        // The inverse is unique, so a quotient of whole numbers is the quotient of residues
+        let whole = self
+            .whole
+            .zip(other.whole)
+            .and_then(|(a, b)| match a.checked_rem(b) {
+                Some(0) => a.checked_div(b),
+                _ => None,
+            });
+        (self * other.inverse().expect("the divisor has no modular inverse")).with_whole(whole)
+    }
+}
+
+impl<const M: u64> Rem for Modular<M> {
+    type Output = Modular<M>;
+    fn rem(self, other: Modular<M>) -> Modular<M> {
+        Modular::new(self.value() % other.value())
+    }
+}
+
+impl<const M: u64> Neg for Modular<M> {
+    type Output = Modular<M>;
+    fn neg(self) -> Modular<M> {
+        Modular::new(M - self.value()).with_whole(self.whole.and_then(i128::checked_neg))
+    }
+}
+
+macro_rules! impl_modular_assign_op {
+    ($assign_op:ident, $assign_method:ident, $op:tt) => {
+        impl<const M: u64> $assign_op for Modular<M> {
+            fn $assign_method(&mut self, other: Modular<M>) {
+                *self = *self $op other;
+            }
+        }
+    };
+}
+impl_modular_assign_op!(AddAssign, add_assign, +);
+impl_modular_assign_op!(SubAssign, sub_assign, -);
+impl_modular_assign_op!(MulAssign, mul_assign, *);
+impl_modular_assign_op!(DivAssign, div_assign, /);
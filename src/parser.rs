@@ -1,23 +1,44 @@
-use crate::{Expr, Num, OpVal, SymbolVal, Token, TokenValue};
+use crate::{Expr, Num, OpVal, OwnedParseErrorCode, SymbolVal, Token, TokenValue};
 use peekmore::{PeekMore, PeekMoreIterator};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::fmt::{self, Display, Formatter};
 use std::ops::Range;
 use std::slice::Iter;
 
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ParseErrorCode<'t, N: Num> {
     ExpectedValue,
     ExpectedClosingParen,
-    UnexpectedToken(&'t Token<'t, N>),
+    UnexpectedToken(#[cfg_attr(feature = "serde", serde(borrow))] Token<'t, N>),
     UnexpectedEOF,
 }
 use ParseErrorCode::*;
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ParseError<'t, N: Num> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub code: ParseErrorCode<'t, N>,
     pub span: Range<usize>,
 }
 
+impl<'t, N: Num + Display> Display for ParseErrorCode<'t, N> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", OwnedParseErrorCode::from(self))
+    }
+}
+
+impl<'t, N: Num + Display> Display for ParseError<'t, N> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.code)
+    }
+}
+
+impl<'t, N: Num + Display> std::error::Error for ParseError<'t, N> {}
+
 pub type ParseResult<'input, N> = Result<Expr<'input, N>, ParseError<'input, N>>;
 
 macro_rules! error {
@@ -29,21 +50,110 @@ macro_rules! error {
     };
 }
 
-type TokenIter<'t, N> = PeekMoreIterator<Iter<'t, Token<'t, N>>>;
+struct TokenIter<'t, N: Num> {
+    iter: PeekMoreIterator<Iter<'t, Token<'t, N>>>,
+    /// Errors that were recovered from, or `None` to stop at the first error.
+    errors: Option<Vec<ParseError<'t, N>>>,
+    /// Where the input ends, for `UnexpectedEOF` errors.
+    end: usize,
+    /// Where the last consumed token ends.
+    last_end: usize,
+}
+
+impl<'t, N: Num> TokenIter<'t, N> {
+    fn new(tokens: &'t [Token<'t, N>], recovering: bool) -> Self {
+        TokenIter {
+            iter: tokens.iter().peekmore(),
+            errors: if recovering { Some(Vec::new()) } else { None },
+            end: tokens.last().map_or(0, |tok| tok.span.end),
+            last_end: 0,
+        }
+    }
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<&'t Token<'t, N>> {
+        let tok = self.iter.next();
+        if let Some(tok) = tok {
+            self.last_end = tok.span.end;
+        }
+        tok
+    }
+
+    #[inline(always)]
+    fn peek(&mut self) -> Option<&&'t Token<'t, N>> {
+        self.iter.peek()
+    }
+
+    #[inline(always)]
+    fn peek_nth(&mut self, n: usize) -> Option<&&'t Token<'t, N>> {
+        self.iter.peek_nth(n)
+    }
+
+    #[inline(always)]
+    fn recovering(&self) -> bool {
+        self.errors.is_some()
+    }
+
+    /// Returns the error when not recovering. Otherwise the error is recorded and parsing
+    /// continues.
+    fn report(&mut self, err: ParseError<'t, N>) -> Result<(), ParseError<'t, N>> {
+        match self.errors.as_mut() {
+            Some(errors) => {
+                // Everything left open at the end of input is the same error
+                let repeated_eof = err.code == UnexpectedEOF
+                    && errors.last().is_some_and(|last| last.code == UnexpectedEOF);
+                if !repeated_eof {
+                    errors.push(err);
+                }
+                Ok(())
+            }
+            None => Err(err),
+        }
+    }
+
+    fn eof(&self) -> ParseError<'t, N> {
+        error!(UnexpectedEOF, self.end..self.end + 1)
+    }
+}
 
 pub fn parse<'input, N: Num>(tokens: &'input [Token<'input, N>]) -> ParseResult<'input, N> {
-    let mut iter = tokens.iter().peekmore();
-    let result = parse_expr(&mut iter);
-    match result {
-        Ok(_) => {
-            if let Some(tok) = iter.next() {
-                Err(error!(UnexpectedToken(tok), tok.span.clone()))
-            } else {
-                result
+    let mut iter = TokenIter::new(tokens, false);
+    let result = parse_expr(&mut iter)?;
+    if let Some(tok) = iter.next() {
+        Err(error!(UnexpectedToken(tok.clone()), tok.span.clone()))
+    } else {
+        Ok(result)
+    }
+}
+
+/// Parses all of `tokens` instead of stopping at the first error, and returns every error found
+/// along with a best-effort expression. Wherever a value could not be parsed, the expression
+/// holds an `Expr::Error`.
+pub fn parse_recovering<'input, N: Num>(
+    tokens: &'input [Token<'input, N>],
+) -> (Expr<'input, N>, Vec<ParseError<'input, N>>) {
+    let mut iter = TokenIter::new(tokens, true);
+    // Errors are collected rather than returned
+    let mut result = parse_expr(&mut iter).unwrap_or(Expr::Error);
+    while let Some(tok) = iter.next() {
+        let _ = iter.report(error!(UnexpectedToken(tok.clone()), tok.span.clone()));
+        // Continue past a stray token, e.g. the ")" in "1 + 2) * 3", so the rest is checked too
+        match iter.peek().map(|tok| (&tok.value, &tok.span)) {
+            Some((TokenValue::Op(op), span))
+                if *op != OpVal::Sub && *op != OpVal::Exclaim && *op != OpVal::Degree =>
+            {
+                let (op, span) = (*op, span.clone());
+                iter.next();
+                let rhs = parse_expr(&mut iter).unwrap_or(Expr::Error);
+                result = Expr::Op(op, Box::new(result), Box::new(rhs), span);
+            }
+            Some(_) => {
+                let _ = parse_expr(&mut iter);
             }
+            None => {}
         }
-        Err(_) => result,
     }
+    (result, iter.errors.unwrap_or_default())
 }
 
 #[inline]
@@ -52,11 +162,11 @@ fn parse_expr<'t, N: Num>(tokens: &mut TokenIter<'t, N>) -> ParseResult<'t, N> {
 }
 
 fn parse_eq<'t, N: Num>(tokens: &mut TokenIter<'t, N>) -> ParseResult<'t, N> {
-    let mut result = parse_add(tokens)?;
+    let mut result = parse_to(tokens)?;
     while let Some(peek_tok) = tokens.peek() {
         if peek_tok.value == TokenValue::Op(OpVal::Eq) {
             tokens.next(); // Consume '='
-            let rhs = parse_add(tokens)?;
+            let rhs = parse_to(tokens)?;
             result = Expr::Eq(Box::new(result), Box::new(rhs));
         } else {
             break;
@@ -65,14 +175,31 @@ fn parse_eq<'t, N: Num>(tokens: &mut TokenIter<'t, N>) -> ParseResult<'t, N> {
     Ok(result)
 }
 
+/// The identifier `to` after a value is the conversion operator, as in `5 km to mi`.
+fn parse_to<'t, N: Num>(tokens: &mut TokenIter<'t, N>) -> ParseResult<'t, N> {
+    let mut result = parse_add(tokens)?;
+    while let Some(peek_tok) = tokens.peek() {
+        if peek_tok.value == TokenValue::Id(Cow::Borrowed("to")) {
+            let span = tokens.next().unwrap().span.clone(); // Consume 'to'
+            let rhs = parse_add(tokens)?;
+            result = Expr::Op(OpVal::To, Box::new(result), Box::new(rhs), span);
+        } else {
+            break;
+        }
+    }
+    Ok(result)
+}
+
 fn parse_add<'t, N: Num>(tokens: &mut TokenIter<'t, N>) -> ParseResult<'t, N> {
     let mut result = parse_mul(tokens)?;
     while let Some(peek_tok) = tokens.peek() {
         match peek_tok.value {
-            TokenValue::Op(op) if op == OpVal::Add || op == OpVal::Sub => {
-                tokens.next(); // Consume '+' or '-'
+            TokenValue::Op(op)
+                if op == OpVal::Add || op == OpVal::Sub || op == OpVal::PlusMinus =>
+            {
+                let span = tokens.next().unwrap().span.clone(); // Consume '+', '-' or '±'
                 let rhs = parse_mul(tokens)?;
-                result = Expr::Op(op, Box::new(result), Box::new(rhs));
+                result = Expr::Op(op, Box::new(result), Box::new(rhs), span);
             }
             _ => break,
         }
@@ -85,9 +212,9 @@ fn parse_mul<'t, N: Num>(tokens: &mut TokenIter<'t, N>) -> ParseResult<'t, N> {
     while let Some(peek_tok) = tokens.peek() {
         match peek_tok.value {
             TokenValue::Op(op) if op == OpVal::Mul || op == OpVal::Div || op == OpVal::Mod => {
-                tokens.next(); // Consume '*' or '/' or '%'
+                let span = tokens.next().unwrap().span.clone(); // Consume '*' or '/' or '%'
                 let rhs = parse_pow(tokens)?;
-                result = Expr::Op(op, Box::new(result), Box::new(rhs));
+                result = Expr::Op(op, Box::new(result), Box::new(rhs), span);
             }
             _ => break,
         }
@@ -99,9 +226,9 @@ fn parse_pow<'t, N: Num>(tokens: &mut TokenIter<'t, N>) -> ParseResult<'t, N> {
     let mut result = parse_parentheses_mul(tokens)?;
     while let Some(peek_tok) = tokens.peek() {
         if peek_tok.value == TokenValue::Op(OpVal::Pow) {
-            tokens.next(); // Consume '^'
+            let span = tokens.next().unwrap().span.clone(); // Consume '^'
             let rhs = parse_factor(tokens)?;
-            result = Expr::Op(OpVal::Pow, Box::new(result), Box::new(rhs));
+            result = Expr::Op(OpVal::Pow, Box::new(result), Box::new(rhs), span);
         } else {
             break;
         }
@@ -109,24 +236,48 @@ fn parse_pow<'t, N: Num>(tokens: &mut TokenIter<'t, N>) -> ParseResult<'t, N> {
     Ok(result)
 }
 
+/// Consumes `closing`, or reports that it is missing. When recovering, a wrong token is left
+/// for the caller, since it most likely belongs to an enclosing expression.
+fn expect_closing<'t, N: Num>(
+    tokens: &mut TokenIter<'t, N>,
+    closing: SymbolVal,
+    missing: fn(Token<'t, N>) -> ParseErrorCode<'t, N>,
+) -> Result<(), ParseError<'t, N>> {
+    match tokens.peek() {
+        Some(tok) if tok.value == TokenValue::Symbol(closing) => {
+            tokens.next();
+            Ok(())
+        }
+        Some(&tok) => {
+            if !tokens.recovering() {
+                tokens.next();
+            }
+            tokens.report(error!(missing(tok.clone()), tok.span.clone()))
+        }
+        None => tokens.report(tokens.eof()),
+    }
+}
+
 fn parse_parentheses_mul<'t, N: Num>(tokens: &mut TokenIter<'t, N>) -> ParseResult<'t, N> {
-    if let Some(func_or_var_mul) = parse_func_or_var_mul(tokens) {
-        Ok(func_or_var_mul?)
+    if let Some(mut result) = parse_func_or_var_mul(tokens)? {
+        // The result of a function call can be indexed, as in `f(x)[0]`
+        while let Some(peek_tok) = tokens.peek() {
+            if peek_tok.value == TokenValue::Symbol(SymbolVal::LB) {
+                result = parse_index(tokens, result)?;
+            } else {
+                break;
+            }
+        }
+        Ok(result)
     } else {
         let mut result = parse_factorial(tokens)?;
         while let Some(peek_tok) = tokens.peek() {
             if peek_tok.value == TokenValue::Symbol(SymbolVal::LP) {
-                tokens.next(); // Consume '('
+                // An implicit multiplication is located at its '('
+                let span = tokens.next().unwrap().span.clone();
                 let rhs = parse_expr(tokens)?;
-                if let Some(tok) = tokens.next() {
-                    if tok.value == TokenValue::Symbol(SymbolVal::RP) {
-                        result = Expr::Op(OpVal::Mul, Box::new(result), Box::new(rhs));
-                    } else {
-                        return Err(error!(ExpectedClosingParen, tok.span.clone()));
-                    }
-                } else {
-                    return Err(error!(UnexpectedEOF, 0..0));
-                }
+                expect_closing(tokens, SymbolVal::RP, |_| ExpectedClosingParen)?;
+                result = Expr::Op(OpVal::Mul, Box::new(result), Box::new(rhs), span);
             } else {
                 break;
             }
@@ -135,21 +286,21 @@ fn parse_parentheses_mul<'t, N: Num>(tokens: &mut TokenIter<'t, N>) -> ParseResu
     }
 }
 
-// This function returns Option to the result, because it doesn't *have* to parse a value.
+// This function returns an Option, because it doesn't *have* to parse a value.
 // And because it should only be used by parse_parentheses_mul.
-fn parse_func_or_var_mul<'t, N: Num>(tokens: &mut TokenIter<'t, N>) -> Option<ParseResult<'t, N>> {
+fn parse_func_or_var_mul<'t, N: Num>(
+    tokens: &mut TokenIter<'t, N>,
+) -> Result<Option<Expr<'t, N>>, ParseError<'t, N>> {
     match tokens.peek() {
         Some(Token {
             value: TokenValue::Id(id),
-            ..
+            span,
         }) => {
+            let start = span.start;
             // Check for opening parentheses
-            if let Some(tok) = tokens.peek_nth(1) {
-                if tok.value != TokenValue::Symbol(SymbolVal::LP) {
-                    return None;
-                }
-            } else {
-                return None;
+            match tokens.peek_nth(1) {
+                Some(tok) if tok.value == TokenValue::Symbol(SymbolVal::LP) => {}
+                _ => return Ok(None),
             }
 
             // Consume previous tokens
@@ -162,89 +313,155 @@ fn parse_func_or_var_mul<'t, N: Num>(tokens: &mut TokenIter<'t, N>) -> Option<Pa
             if let Some(tok) = tokens.peek() {
                 if tok.value == TokenValue::Symbol(SymbolVal::RP) {
                     tokens.next(); // Consume ')'
-                    return Some(Ok(Expr::FuncOrVarMul(id, Vec::new())));
+                    return Ok(Some(Expr::FuncOrVarMul(
+                        Cow::Borrowed(id),
+                        Vec::new(),
+                        start..tokens.last_end,
+                    )));
                 }
             }
 
             // Collecting function parameters
             let mut params = Vec::with_capacity(3);
-            while let Ok(expr) = parse_expr(tokens) {
-                params.push(expr);
-                match tokens.next() {
-                    Some(Token {
-                        value: TokenValue::Symbol(SymbolVal::Comma),
-                        ..
-                    }) => {
-                        continue;
-                    }
-                    Some(Token {
-                        value: TokenValue::Symbol(SymbolVal::RP),
-                        ..
-                    }) => {
-                        break;
+            'params: loop {
+                params.push(parse_expr(tokens)?);
+                loop {
+                    match tokens.peek().map(|tok| &tok.value) {
+                        Some(TokenValue::Symbol(SymbolVal::Comma)) => {
+                            tokens.next();
+                            continue 'params;
+                        }
+                        Some(TokenValue::Symbol(SymbolVal::RP)) => {
+                            tokens.next();
+                            break 'params;
+                        }
+                        Some(_) => {
+                            // Skip anything else between parameters when recovering
+                            let tok = tokens.next().unwrap();
+                            tokens
+                                .report(error!(UnexpectedToken(tok.clone()), tok.span.clone()))?;
+                        }
+                        None => {
+                            tokens.report(tokens.eof())?;
+                            break 'params;
+                        }
                     }
-                    Some(tok) => return Some(Err(error!(UnexpectedToken(tok), tok.span.clone()))),
-                    None => return Some(Err(error!(UnexpectedEOF, 0..0))),
                 }
             }
-            Some(Ok(Expr::FuncOrVarMul(id, params)))
+            Ok(Some(Expr::FuncOrVarMul(
+                Cow::Borrowed(id),
+                params,
+                start..tokens.last_end,
+            )))
         }
-        _ => None,
+        _ => Ok(None),
     }
 }
 
+/// Parses the `[index]` after `list`, which the next token opens.
+fn parse_index<'t, N: Num>(tokens: &mut TokenIter<'t, N>, list: Expr<'t, N>) -> ParseResult<'t, N> {
+    let start = tokens.next().unwrap().span.start; // Consume '['
+    let index = parse_expr(tokens)?;
+    expect_closing(tokens, SymbolVal::RB, UnexpectedToken)?;
+    Ok(Expr::Index(
+        Box::new(list),
+        Box::new(index),
+        start..tokens.last_end,
+    ))
+}
+
 fn parse_factorial<'t, N: Num>(tokens: &mut TokenIter<'t, N>) -> ParseResult<'t, N> {
     let mut result = parse_factor(tokens)?;
     while let Some(peek_tok) = tokens.peek() {
-        if peek_tok.value == TokenValue::Op(OpVal::Exclaim) {
-            tokens.next(); // Consume '!'
-            result = Expr::FuncOrVarMul("factorial", vec![result]);
-        } else {
-            break;
+        match peek_tok.value {
+            TokenValue::Op(OpVal::Exclaim) => {
+                let span = tokens.next().unwrap().span.clone(); // Consume '!'
+                result = Expr::FuncOrVarMul(Cow::Borrowed("factorial"), vec![result], span);
+            }
+            TokenValue::Op(OpVal::Degree) => {
+                let span = tokens.next().unwrap().span.clone(); // Consume '°'
+                result = Expr::Degrees(Box::new(result), span);
+            }
+            TokenValue::Symbol(SymbolVal::LB) => result = parse_index(tokens, result)?,
+            _ => break,
         }
     }
     Ok(result)
 }
 
 fn parse_factor<'t, N: Num>(tokens: &mut TokenIter<'t, N>) -> ParseResult<'t, N> {
+    // When recovering, a closing symbol is left for the expression it closes
+    if tokens.recovering() {
+        if let Some(&tok) = tokens.peek() {
+            if let TokenValue::Symbol(SymbolVal::RP)
+            | TokenValue::Symbol(SymbolVal::RB)
+            | TokenValue::Symbol(SymbolVal::Comma) = tok.value
+            {
+                tokens.report(error!(UnexpectedToken(tok.clone()), tok.span.clone()))?;
+                return Ok(Expr::Error);
+            }
+        }
+    }
     match tokens.next() {
         Some(tok) => match &tok.value {
-            TokenValue::Num(num) => Ok(Expr::Num(num)),
-            TokenValue::Id(id) => Ok(Expr::Var(id)),
+            TokenValue::Num(num) => Ok(Expr::Num(num.clone())),
+            TokenValue::Id(id) => Ok(Expr::Var(Cow::Borrowed(id))),
             TokenValue::Op(op) => match op {
-                OpVal::Sub => Ok(Expr::Neg(Box::new(parse_expr(tokens)?))),
-                _ => Err(error!(UnexpectedToken(tok), tok.span.clone())),
+                OpVal::Sub => Ok(Expr::Neg(Box::new(parse_expr(tokens)?), tok.span.clone())),
+                _ => {
+                    // Skip the operator and try again when recovering, e.g. in "2 + * 3"
+                    tokens.report(error!(UnexpectedToken(tok.clone()), tok.span.clone()))?;
+                    parse_factor(tokens)
+                }
             },
             TokenValue::Symbol(sym) => match sym {
                 SymbolVal::LP => {
                     let expr = parse_expr(tokens)?;
                     // Expect a closing parentheses
-                    if let Some(tok) = tokens.next() {
-                        if tok.value == TokenValue::Symbol(SymbolVal::RP) {
-                            Ok(expr)
-                        } else {
-                            Err(error!(UnexpectedToken(tok), tok.span.clone()))
-                        }
-                    } else {
-                        Err(error!(UnexpectedEOF, 0..0))
-                    }
+                    expect_closing(tokens, SymbolVal::RP, UnexpectedToken)?;
+                    Ok(expr)
                 }
                 SymbolVal::Pipe => {
                     let expr = parse_expr(tokens)?;
                     // Expect a closing pipe
-                    if let Some(tok) = tokens.next() {
-                        if tok.value == TokenValue::Symbol(SymbolVal::Pipe) {
-                            Ok(Expr::FuncOrVarMul("abs", vec![expr]))
-                        } else {
-                            Err(error!(UnexpectedToken(tok), tok.span.clone()))
+                    expect_closing(tokens, SymbolVal::Pipe, UnexpectedToken)?;
+                    Ok(Expr::FuncOrVarMul(
+                        Cow::Borrowed("abs"),
+                        vec![expr],
+                        tok.span.start..tokens.last_end,
+                    ))
+                }
+                SymbolVal::LB => {
+                    let mut items = Vec::new();
+                    // An empty list has no items to separate
+                    let empty = matches!(
+                        tokens.peek(),
+                        Some(tok) if tok.value == TokenValue::Symbol(SymbolVal::RB)
+                    );
+                    if !empty {
+                        items.push(parse_expr(tokens)?);
+                        while let Some(peek_tok) = tokens.peek() {
+                            if peek_tok.value == TokenValue::Symbol(SymbolVal::Comma) {
+                                tokens.next(); // Consume ','
+                                items.push(parse_expr(tokens)?);
+                            } else {
+                                break;
+                            }
                         }
-                    } else {
-                        Err(error!(UnexpectedEOF, 0..0))
                     }
+                    // Expect a closing bracket
+                    expect_closing(tokens, SymbolVal::RB, UnexpectedToken)?;
+                    Ok(Expr::List(items, tok.span.start..tokens.last_end))
                 }
-                _ => Err(error!(UnexpectedToken(tok), tok.span.clone())),
+                _ => Err(error!(UnexpectedToken(tok.clone()), tok.span.clone())),
             },
+            // The tokenizer has already reported an invalid number when recovering
+            TokenValue::Error if tokens.recovering() => Ok(Expr::Error),
+            TokenValue::Error => Err(error!(UnexpectedToken(tok.clone()), tok.span.clone())),
         },
-        None => Err(error!(UnexpectedEOF, 0..0)),
+        None => {
+            tokens.report(tokens.eof())?;
+            Ok(Expr::Error)
+        }
     }
 }
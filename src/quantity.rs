@@ -0,0 +1,689 @@
+use crate::{InterpretError, Num, OpVal, Real, Settings};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt::{self, Display, Formatter};
+use std::num::ParseFloatError;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Rem, Sub, SubAssign};
+use std::str::FromStr;
+
+/// The exponents of the SI base units in a dimension, in the order of `BASE_UNITS`.
+type Dimension = [i32; 7];
+
+/// The SI base units. The kilogram is the base unit of mass, but the gram takes the prefixes.
+const BASE_UNITS: [&str; 7] = ["m", "kg", "s", "A", "K", "mol", "cd"];
+
+const NUMBER: Dimension = [0, 0, 0, 0, 0, 0, 0];
+const LENGTH: Dimension = [1, 0, 0, 0, 0, 0, 0];
+const MASS: Dimension = [0, 1, 0, 0, 0, 0, 0];
+const TIME: Dimension = [0, 0, 1, 0, 0, 0, 0];
+
+struct UnitDef {
+    symbol: &'static str,
+    /// The size of the unit in SI base units is `scale` times ten to the power of `exponent`.
+    scale: f64,
+    exponent: i32,
+    dimension: Dimension,
+    /// Whether the unit takes SI prefixes.
+    prefixed: bool,
+}
+
+const fn unit(
+    symbol: &'static str,
+    scale: f64,
+    exponent: i32,
+    dimension: Dimension,
+    prefixed: bool,
+) -> UnitDef {
+    UnitDef {
+        symbol,
+        scale,
+        exponent,
+        dimension,
+        prefixed,
+    }
+}
+
+const UNITS: &[UnitDef] = &[
+    unit("m", 1.0, 0, LENGTH, true),
+    unit("g", 1.0, -3, MASS, true),
+    unit("s", 1.0, 0, TIME, true),
+    unit("A", 1.0, 0, [0, 0, 0, 1, 0, 0, 0], true),
+    unit("K", 1.0, 0, [0, 0, 0, 0, 1, 0, 0], true),
+    unit("mol", 1.0, 0, [0, 0, 0, 0, 0, 1, 0], true),
+    unit("cd", 1.0, 0, [0, 0, 0, 0, 0, 0, 1], true),
+    unit("L", 1.0, -3, [3, 0, 0, 0, 0, 0, 0], true),
+    unit("Hz", 1.0, 0, [0, 0, -1, 0, 0, 0, 0], true),
+    unit("N", 1.0, 0, [1, 1, -2, 0, 0, 0, 0], true),
+    unit("Pa", 1.0, 0, [-1, 1, -2, 0, 0, 0, 0], true),
+    unit("J", 1.0, 0, [2, 1, -2, 0, 0, 0, 0], true),
+    unit("W", 1.0, 0, [2, 1, -3, 0, 0, 0, 0], true),
+    unit("C", 1.0, 0, [0, 0, 1, 1, 0, 0, 0], true),
+    unit("V", 1.0, 0, [2, 1, -3, -1, 0, 0, 0], true),
+    unit("min", 60.0, 0, TIME, false),
+    unit("h", 3600.0, 0, TIME, false),
+    unit("day", 86400.0, 0, TIME, false),
+    unit("in", 0.0254, 0, LENGTH, false),
+    unit("ft", 0.3048, 0, LENGTH, false),
+    unit("yd", 0.9144, 0, LENGTH, false),
+    unit("mi", 1609.344, 0, LENGTH, false),
+    unit("lb", 0.453_592_37, 0, MASS, false),
+    unit("oz", 0.028_349_523_125, 0, MASS, false),
+    unit("mph", 0.44704, 0, [1, 0, -1, 0, 0, 0, 0], false),
+];
+
+/// The prefixes and their powers of ten. `µ` is written as the micro sign, the Greek letter mu
+/// or `u`.
+const PREFIXES: &[(&str, i32)] = &[
+    ("n", -9),
+    ("µ", -6),
+    ("μ", -6),
+    ("u", -6),
+    ("m", -3),
+    ("c", -2),
+    ("k", 3),
+    ("M", 6),
+    ("G", 9),
+];
+
+/// The scale and power of ten of the size in SI base units, and the dimension, of the unit
+/// `symbol`, which may have a prefix.
+fn lookup(symbol: &str) -> Option<(f64, i32, Dimension)> {
+    // Whole symbols come first, so "min" is a minute rather than a milli-inch
+    if let Some(unit) = UNITS.iter().find(|unit| unit.symbol == symbol) {
+        return Some((unit.scale, unit.exponent, unit.dimension));
+    }
+    PREFIXES.iter().find_map(|(prefix, exponent)| {
+        let rest = symbol.strip_prefix(prefix)?;
+        let unit = UNITS
+            .iter()
+            .find(|unit| unit.prefixed && unit.symbol == rest)?;
+        Some((unit.scale, exponent + unit.exponent, unit.dimension))
+    })
+}
+
+/// Every unit symbol, with and without prefixes.
+pub(crate) fn unit_symbols() -> impl Iterator<Item = String> {
+    UNITS.iter().flat_map(|unit| {
+        let prefixed = PREFIXES
+            .iter()
+            .filter(move |_| unit.prefixed)
+            .map(move |(prefix, _)| format!("{}{}", prefix, unit.symbol));
+        std::iter::once(unit.symbol.to_owned()).chain(prefixed)
+    })
+}
+
+/// A product of unit symbols raised to powers, e.g. `[("km", 1), ("h", -1)]` for `km/h`.
+type Unit = Vec<(String, i32)>;
+
+fn dimension(unit: &[(String, i32)]) -> Dimension {
+    let mut dimension = NUMBER;
+    for (symbol, power) in unit {
+        let (_, _, exponents) = lookup(symbol).expect("units are looked up when created");
+        for (total, exponent) in dimension.iter_mut().zip(exponents) {
+            *total += exponent * power;
+        }
+    }
+    dimension
+}
+
+/// The size of `unit` in SI base units. The numerator, the denominator and the powers of ten
+/// are multiplied out separately, so ratios like `h/min` and `µs/ns` come out exact.
+fn scale(unit: &[(String, i32)]) -> f64 {
+    let (mut numer, mut denom, mut exponent) = (1.0, 1.0, 0);
+    for (symbol, power) in unit {
+        let (scale, e, _) = lookup(symbol).expect("units are looked up when created");
+        if *power > 0 {
+            numer *= scale.powi(*power);
+        } else {
+            denom *= scale.powi(-power);
+        }
+        exponent += e * power;
+    }
+    if exponent >= 0 {
+        numer * 10f64.powi(exponent) / denom
+    } else {
+        numer / (denom * 10f64.powi(-exponent))
+    }
+}
+
+/// Multiplies `unit` by `other` raised to `power`, cancelling symbols that appear in both.
+fn multiply(unit: &mut Unit, other: &[(String, i32)], power: i32) {
+    for (symbol, p) in other {
+        match unit.iter_mut().find(|(s, _)| s == symbol) {
+            Some((_, q)) => *q += p * power,
+            None => unit.push((symbol.clone(), p * power)),
+        }
+    }
+    unit.retain(|(_, p)| *p != 0);
+}
+
+/// Parses a unit like `km`, `m/s^2` or `kg*m^2/s^2`, or returns `None` if it has an unknown
+/// symbol.
+fn parse_unit(s: &str) -> Option<Unit> {
+    let mut unit = Vec::new();
+    let (mut rest, mut sign) = (s, 1);
+    loop {
+        let end = rest.find(['*', '/']).unwrap_or(rest.len());
+        let (symbol, power) = match rest[..end].split_once('^') {
+            Some((symbol, power)) => (symbol.trim(), power.trim().parse().ok()?),
+            None => (rest[..end].trim(), 1),
+        };
+        lookup(symbol)?;
+        multiply(&mut unit, &[(symbol.to_owned(), power)], sign);
+        if end == rest.len() {
+            return Some(unit);
+        }
+        sign = if rest[end..].starts_with('/') { -1 } else { 1 };
+        rest = &rest[end + 1..];
+    }
+}
+
+/// Displays a unit in the form `parse_unit` reads, e.g. `kg*m/s^2`. Units with only negative
+/// powers are written like `s^-1`.
+struct DisplayUnit<'a, S>(&'a [(S, i32)]);
+
+impl<S: AsRef<str>> Display for DisplayUnit<'_, S> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let fmt_factor = |f: &mut Formatter, symbol: &S, power: i32| {
+            if power == 1 {
+                write!(f, "{}", symbol.as_ref())
+            } else {
+                write!(f, "{}^{}", symbol.as_ref(), power)
+            }
+        };
+        let has_numerator = self.0.iter().any(|(_, p)| *p > 0);
+        let mut first = true;
+        for (symbol, power) in self.0.iter().filter(|(_, p)| *p > 0 || !has_numerator) {
+            if !first {
+                write!(f, "*")?;
+            }
+            first = false;
+            fmt_factor(f, symbol, *power)?;
+        }
+        if has_numerator {
+            for (symbol, power) in self.0.iter().filter(|(_, p)| *p < 0) {
+                write!(f, "/")?;
+                fmt_factor(f, symbol, -power)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A dimension in SI base units, or 1 for numbers.
+fn dimension_name(dimension: Dimension) -> String {
+    if dimension == NUMBER {
+        return String::from("1");
+    }
+    let unit: Vec<_> = BASE_UNITS
+        .iter()
+        .zip(dimension)
+        .filter(|(_, exponent)| *exponent != 0)
+        .map(|(symbol, exponent)| (*symbol, exponent))
+        .collect();
+    DisplayUnit(&unit).to_string()
+}
+
+/// A number with a physical unit, e.g. `5 km` or `9.81 m/s^2`.
+///
+/// Units are products of the SI units, and of common units like `min`, `h`, `mi` and `mph`,
+/// raised to whole powers. The SI units take the prefixes `n`, `µ` (or `u`), `m`, `c`, `k`, `M`
+/// and `G`. A quantity keeps the unit it was calculated in, so `5 km / 20 min` is `0.25 km/min`,
+/// until it is converted with `to`. Adding, subtracting, comparing or converting quantities of
+/// different dimensions is an `InterpretError::DimensionMismatch`, except that a plain zero has
+/// every dimension, so `0 + 3 m` is `3 m`. Units that cancel out leave a plain number.
+///
+/// `Real` functions like `sin` and `ln` are only defined for plain numbers, and give NaN for
+/// quantities with a dimension, which `install_quantity_stdlib` makes a `DimensionMismatch`
+/// error. `sqrt` and `cbrt` take roots of the unit when its powers allow. Quantities compare and
+/// are equal by their size in SI base units, and a quantity `is_whole` if it is a whole plain
+/// number.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Quantity {
+    /// The value in `unit`.
+    value: f64,
+    /// Empty for plain numbers.
+    unit: Unit,
+}
+
+impl Quantity {
+    /// The quantity `value` in `unit`, which is a product or quotient of unit symbols raised to
+    /// whole powers, like `km/h` or `kg*m^2/s^2`. Returns `None` for unknown units.
+    pub fn new(value: f64, unit: &str) -> Option<Quantity> {
+        if unit.trim().is_empty() {
+            return Some(Quantity::number(value));
+        }
+        Some(
+            Quantity {
+                value,
+                unit: parse_unit(unit)?,
+            }
+            .normalized(),
+        )
+    }
+
+    /// The plain number `value`, without a unit.
+    pub fn number(value: f64) -> Quantity {
+        Quantity {
+            value,
+            unit: Vec::new(),
+        }
+    }
+
+    /// The value in the quantity's unit.
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    /// The unit, or an empty string for plain numbers.
+    pub fn unit(&self) -> String {
+        DisplayUnit(&self.unit).to_string()
+    }
+
+    /// The value in SI base units.
+    pub fn si_value(&self) -> f64 {
+        self.value * scale(&self.unit)
+    }
+
+    /// The exponents of the SI base units metre, kilogram, second, ampere, kelvin, mole and
+    /// candela in the unit.
+    pub fn dimension(&self) -> [i32; 7] {
+        dimension(&self.unit)
+    }
+
+    /// Whether the quantity is a plain number, without a dimension.
+    pub fn is_number(&self) -> bool {
+        self.unit.is_empty()
+    }
+
+    /// Whether the quantity is a plain zero, which has every dimension.
+    fn is_zero(&self) -> bool {
+        self.is_number() && self.value == 0.0
+    }
+
+    /// The plain number, or a `DimensionMismatch` error for the function `id` if the quantity
+    /// has a dimension.
+    pub(crate) fn plain<'a>(&self, id: &'a str) -> Result<f64, InterpretError<'a>> {
+        if self.is_number() {
+            Ok(self.value)
+        } else {
+            Err(Quantity::mismatch(id, NUMBER, self.dimension()))
+        }
+    }
+
+    /// The value converted to the unit of `other`, or a `DimensionMismatch` error for the
+    /// function `id` if the dimensions differ.
+    pub(crate) fn value_in_unit_of<'a>(
+        &self,
+        other: &Quantity,
+        id: &'a str,
+    ) -> Result<f64, InterpretError<'a>> {
+        self.value_in(&other.unit)
+            .ok_or_else(|| Quantity::mismatch(id, other.dimension(), self.dimension()))
+    }
+
+    /// Converts the quantity to the unit of `unit`, or returns `None` if their dimensions
+    /// differ.
+    pub fn to(&self, unit: &Quantity) -> Option<Quantity> {
+        Some(Quantity {
+            value: self.value_in(&unit.unit)?,
+            unit: unit.unit.clone(),
+        })
+    }
+
+    fn nan() -> Quantity {
+        Quantity::number(f64::NAN)
+    }
+
+    /// The value converted to `unit`, or `None` if the dimensions differ.
+    fn value_in(&self, unit: &[(String, i32)]) -> Option<f64> {
+        if self.is_zero() {
+            return Some(0.0);
+        }
+        if dimension(&self.unit) != dimension(unit) {
+            return None;
+        }
+        let mut ratio = self.unit.clone();
+        multiply(&mut ratio, unit, -1);
+        Some(self.value * scale(&ratio))
+    }
+
+    fn mismatch(op: &str, lhs: Dimension, rhs: Dimension) -> InterpretError<'_> {
+        // The interpreter replaces the span with the operator's
+        InterpretError::DimensionMismatch(op, dimension_name(lhs), dimension_name(rhs), 0..0)
+    }
+
+    /// Turns a unit without a dimension, like `m/km`, into a plain number.
+    fn normalized(self) -> Quantity {
+        if !self.unit.is_empty() && dimension(&self.unit) == NUMBER {
+            Quantity::number(self.si_value())
+        } else {
+            self
+        }
+    }
+
+    /// The quantity to the power of `exponent`, or `None` if the powers of the unit would not
+    /// be whole.
+    fn powf(&self, exponent: f64) -> Option<Quantity> {
+        let mut unit = Vec::with_capacity(self.unit.len());
+        for (symbol, power) in &self.unit {
+            let power = f64::from(*power) * exponent;
+            if power.fract() != 0.0 || power.abs() > f64::from(i32::MAX) {
+                return None;
+            }
+            unit.push((symbol.clone(), power as i32));
+        }
+        unit.retain(|(_, p)| *p != 0);
+        Some(Quantity {
+            value: self.value.powf(exponent),
+            unit,
+        })
+    }
+
+    /// The `n`th root, with `root` applied to the value, or `None` if the powers of the unit
+    /// are not divisible by `n`.
+    pub(crate) fn root(&self, n: i32, root: impl Fn(f64) -> f64) -> Option<Quantity> {
+        if self.unit.iter().any(|(_, p)| p % n != 0) {
+            return None;
+        }
+        Some(Quantity {
+            value: root(self.value),
+            unit: self.unit.iter().map(|(s, p)| (s.clone(), p / n)).collect(),
+        })
+    }
+
+    /// Applies `f` to the value, keeping the unit.
+    fn map(self, f: fn(f64) -> f64) -> Quantity {
+        Quantity {
+            value: f(self.value),
+            unit: self.unit,
+        }
+    }
+
+    /// Applies `f` to a plain number, or gives NaN for a quantity with a dimension.
+    fn dimensionless(self, f: fn(f64) -> f64) -> Quantity {
+        if self.is_number() {
+            Quantity::number(f(self.value))
+        } else {
+            Quantity::nan()
+        }
+    }
+}
+
+impl From<f64> for Quantity {
+    fn from(value: f64) -> Self {
+        Quantity::number(value)
+    }
+}
+
+impl Num for Quantity {
+    fn zero() -> Self {
+        Quantity::number(0.0)
+    }
+    fn one() -> Self {
+        Quantity::number(1.0)
+    }
+    fn is_whole(&self) -> bool {
+        self.is_number() && self.value.fract() == 0.0
+    }
+    fn parse_literal(s: &str) -> Option<Self> {
+        s.parse().ok()
+    }
+    fn pow(self, other: Self) -> Self {
+        self.arithmetic(OpVal::Pow, other, &Settings::default())
+            .unwrap_or_else(|_| Quantity::nan())
+    }
+
+    /// Adding, subtracting or taking the remainder of quantities of different dimensions, and
+    /// raising to a power with a dimension, are `DimensionMismatch` errors. A power that leaves
+    /// a unit with a fractional power, like `(1 m)^0.5`, is `OutOfDomain`.
+    fn arithmetic(
+        self,
+        op: OpVal,
+        other: Self,
+        _: &Settings,
+    ) -> Result<Self, InterpretError<'static>> {
+        match op {
+            OpVal::Add | OpVal::Sub | OpVal::Mod => {
+                // A plain zero takes the unit of the other operand
+                let lhs = if self.is_zero() {
+                    Quantity {
+                        value: 0.0,
+                        unit: other.unit.clone(),
+                    }
+                } else {
+                    self
+                };
+                let rhs = other.value_in(&lhs.unit).ok_or_else(|| {
+                    Quantity::mismatch(op.symbol(), lhs.dimension(), other.dimension())
+                })?;
+                let value = match op {
+                    OpVal::Add => lhs.value + rhs,
+                    OpVal::Sub => lhs.value - rhs,
+                    _ => lhs.value % rhs,
+                };
+                Ok(Quantity {
+                    value,
+                    unit: lhs.unit,
+                })
+            }
+            OpVal::Mul | OpVal::Div => {
+                let (value, power) = if op == OpVal::Mul {
+                    (self.value * other.value, 1)
+                } else {
+                    (self.value / other.value, -1)
+                };
+                let mut unit = self.unit;
+                multiply(&mut unit, &other.unit, power);
+                Ok(Quantity { value, unit }.normalized())
+            }
+            OpVal::Pow => {
+                if !other.is_number() {
+                    return Err(Quantity::mismatch(op.symbol(), NUMBER, other.dimension()));
+                }
+                self.powf(other.value)
+                    .ok_or(InterpretError::OutOfDomain(op.symbol()))
+            }
+            _ => unreachable!(),
+        }
+    }
+    fn is_finite(&self) -> bool {
+        self.value.is_finite()
+    }
+    fn is_atomic(&self) -> bool {
+        self.is_number()
+    }
+    fn convert(self, unit: Self) -> Result<Self, InterpretError<'static>> {
+        self.to(&unit).ok_or_else(|| {
+            Quantity::mismatch(OpVal::To.symbol(), self.dimension(), unit.dimension())
+        })
+    }
+    fn unit_named(symbol: &str) -> Option<Self> {
+        Quantity::new(1.0, symbol)
+    }
+
+    fn negate(self, _: &Settings) -> Result<Self, InterpretError<'static>> {
+        Ok(-self)
+    }
+
+    fn degrees(self, settings: &Settings) -> Result<Self, InterpretError<'static>> {
+        Ok(settings.angle_mode.from_degrees(self))
+    }
+}
+
+/// Defines methods of `Real` that are only defined for plain numbers.
+macro_rules! dimensionless {
+    ($($name:ident),*) => {
+        $(
+            fn $name(self) -> Self {
+                self.dimensionless(f64::$name)
+            }
+        )*
+    };
+}
+
+impl Real for Quantity {
+    fn from_f64(x: f64) -> Self {
+        Quantity::number(x)
+    }
+    /// The value in SI base units.
+    fn to_f64(&self) -> f64 {
+        self.si_value()
+    }
+
+    fn sqrt(self) -> Self {
+        self.root(2, f64::sqrt).unwrap_or_else(Quantity::nan)
+    }
+    fn cbrt(self) -> Self {
+        self.root(3, f64::cbrt).unwrap_or_else(Quantity::nan)
+    }
+    fn floor(self) -> Self {
+        self.map(f64::floor)
+    }
+    fn ceil(self) -> Self {
+        self.map(f64::ceil)
+    }
+    fn round(self) -> Self {
+        self.map(f64::round)
+    }
+    fn trunc(self) -> Self {
+        self.map(f64::trunc)
+    }
+    dimensionless!(exp, ln, log2, log10);
+    dimensionless!(sin, cos, tan, asin, acos, atan, sinh, cosh, tanh, asinh, acosh, atanh);
+
+    /// The angle of the point (`other`, `self`), which need the same dimension.
+    fn atan2(self, other: Self) -> Self {
+        match other.value_in(&self.unit) {
+            Some(x) => Quantity::number(self.value.atan2(x)),
+            None => Quantity::nan(),
+        }
+    }
+    fn hypot(self, other: Self) -> Self {
+        match other.value_in(&self.unit) {
+            Some(x) => Quantity {
+                value: self.value.hypot(x),
+                unit: self.unit,
+            },
+            None => Quantity::nan(),
+        }
+    }
+    fn gamma(self) -> Self {
+        self.dimensionless(crate::stdlib::gamma)
+    }
+    fn ln_gamma(self) -> Self {
+        self.dimensionless(crate::stdlib::ln_gamma)
+    }
+}
+
+impl PartialEq for Quantity {
+    fn eq(&self, other: &Self) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+/// Quantities of different dimensions are not ordered, except that a plain zero, which has every
+/// dimension, compares with any quantity, so `abs` and `sign` work.
+impl PartialOrd for Quantity {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        if self.dimension() == other.dimension() || self.is_zero() || other.is_zero() {
+            self.si_value().partial_cmp(&other.si_value())
+        } else {
+            None
+        }
+    }
+}
+
+/// An error parsing a `Quantity`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseQuantityError {
+    InvalidNumber(ParseFloatError),
+    UnknownUnit(String),
+}
+
+impl From<ParseFloatError> for ParseQuantityError {
+    fn from(err: ParseFloatError) -> Self {
+        ParseQuantityError::InvalidNumber(err)
+    }
+}
+
+impl Display for ParseQuantityError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ParseQuantityError::InvalidNumber(err) => write!(f, "{}", err),
+            ParseQuantityError::UnknownUnit(unit) => write!(f, "Unknown unit {:?}.", unit),
+        }
+    }
+}
+
+impl std::error::Error for ParseQuantityError {}
+
+/// Parses a number with an optional unit after it, e.g. `5`, `5 km` or `9.81m/s^2`.
+impl FromStr for Quantity {
+    type Err = ParseQuantityError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Ok(value) = s.parse() {
+            return Ok(Quantity::number(value));
+        }
+        let split = s
+            .find(|c: char| c.is_whitespace() || c.is_alphabetic())
+            .unwrap_or(s.len());
+        let (value, unit) = s.split_at(split);
+        let value = value.parse()?;
+        Quantity::new(value, unit)
+            .ok_or_else(|| ParseQuantityError::UnknownUnit(unit.trim().to_owned()))
+    }
+}
+
+impl Display for Quantity {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if self.is_number() {
+            write!(f, "{}", self.value)
+        } else {
+            write!(f, "{} {}", self.value, DisplayUnit(&self.unit))
+        }
+    }
+}
+
+macro_rules! impl_quantity_op {
+    ($op:ident, $method:ident, $opval:expr) => {
+        /// Gives NaN where the interpreter reports a `DimensionMismatch`.
+        impl $op for Quantity {
+            type Output = Quantity;
+            fn $method(self, other: Quantity) -> Quantity {
+                self.arithmetic($opval, other, &Settings::default())
+                    .unwrap_or_else(|_| Quantity::nan())
+            }
+        }
+    };
+}
+impl_quantity_op!(Add, add, OpVal::Add);
+impl_quantity_op!(Sub, sub, OpVal::Sub);
+impl_quantity_op!(Mul, mul, OpVal::Mul);
+impl_quantity_op!(Div, div, OpVal::Div);
+impl_quantity_op!(Rem, rem, OpVal::Mod);
+
+impl Neg for Quantity {
+    type Output = Quantity;
+    fn neg(self) -> Quantity {
+        self.map(|x| -x)
+    }
+}
+
+macro_rules! impl_quantity_assign_op {
+    ($assign_op:ident, $assign_method:ident, $op:tt) => {
+        impl $assign_op for Quantity {
+            fn $assign_method(&mut self, other: Quantity) {
+                *self = self.clone() $op other;
+            }
+        }
+    };
+}
+impl_quantity_assign_op!(AddAssign, add_assign, +);
+impl_quantity_assign_op!(SubAssign, sub_assign, -);
+impl_quantity_assign_op!(MulAssign, mul_assign, *);
+impl_quantity_assign_op!(DivAssign, div_assign, /);
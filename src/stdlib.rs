@@ -0,0 +1,1571 @@
+use crate::value::spread;
+use crate::{
+    ensure_arg_count, AngleMode, Complex, Dual, Function, Integer, InterpretError, Interpreter,
+    Interval, Matrix, Modular, Num, OpVal, Quantity, Real, Settings, Uncertain, Value, Variant,
+};
+#[cfg(feature = "num")]
+use crate::{BigInt, Decimal, DecimalContext, Rational, RoundingMode};
+#[cfg(feature = "num")]
+use num::ToPrimitive;
+use std::f64::consts::PI;
+use std::num::{Saturating, Wrapping};
+
+/// Registers `$f` as a function of one argument.
+macro_rules! unary {
+    ($interpreter:expr, $name:expr, $f:expr) => {
+        $interpreter.set_var(
+            String::from($name),
+            Variant::Function(|id, args, _| {
+                ensure_arg_count(1, 1, args.len(), id)?;
+                let f: fn(N) -> N = $f;
+                Ok(f(args[0].clone()))
+            }),
+        );
+    };
+}
+
+/// Registers `$f` as a function of two arguments.
+macro_rules! binary {
+    ($interpreter:expr, $name:expr, $f:expr) => {
+        $interpreter.set_var(
+            String::from($name),
+            Variant::Function(|id, args, _| {
+                ensure_arg_count(2, 2, args.len(), id)?;
+                let f: fn(N, N) -> N = $f;
+                Ok(f(args[0].clone(), args[1].clone()))
+            }),
+        );
+    };
+}
+
+/// Registers `$f`, a function of any number of arguments, so that the elements of lists are
+/// passed as arguments of their own, as in `max(v)` or `max(v, 0)`.
+macro_rules! aggregate {
+    ($interpreter:expr, $name:expr, $f:expr) => {
+        $interpreter.set_var(
+            String::from($name),
+            Variant::ValueFunction(|id, args, settings| {
+                let f: Function<N> = $f;
+                f(id, &spread(args), settings).map(Value::Num)
+            }),
+        );
+    };
+}
+
+/// Registers `$f` as a trigonometric function of an angle in the angle mode.
+macro_rules! trig {
+    ($interpreter:expr, $name:expr, $f:expr) => {
+        $interpreter.set_var(
+            String::from($name),
+            Variant::Function(|id, args, settings| {
+                ensure_arg_count(1, 1, args.len(), id)?;
+                let f: fn(N) -> N = $f;
+                Ok(f(settings.angle_mode.to_radians(args[0].clone())))
+            }),
+        );
+    };
+}
+
+/// Registers `$f` as an inverse trigonometric function returning an angle in the angle mode.
+macro_rules! inverse_trig {
+    ($interpreter:expr, $name:expr, $f:expr) => {
+        $interpreter.set_var(
+            String::from($name),
+            Variant::Function(|id, args, settings| {
+                ensure_arg_count(1, 1, args.len(), id)?;
+                let f: fn(N) -> N = $f;
+                Ok(settings.angle_mode.from_radians(f(args[0].clone())))
+            }),
+        );
+    };
+}
+
+/// Registers a function converting an angle in `$unit` to the angle mode.
+macro_rules! angle_unit {
+    ($interpreter:expr, $name:expr, $unit:expr) => {
+        $interpreter.set_var(
+            String::from($name),
+            Variant::Function(|id, args, settings| {
+                ensure_arg_count(1, 1, args.len(), id)?;
+                // An angle already in the mode is left exact
+                if settings.angle_mode == $unit {
+                    return Ok(args[0].clone());
+                }
+                let radians = $unit.to_radians(args[0].clone());
+                Ok(settings.angle_mode.from_radians(radians))
+            }),
+        );
+    };
+}
+
+/// Registers `$f` as a function of one argument that is not defined at the poles of the gamma
+/// function.
+macro_rules! gamma_like {
+    ($interpreter:expr, $name:expr, $f:expr) => {
+        $interpreter.set_var(
+            String::from($name),
+            Variant::Function(|id, args, _| {
+                ensure_arg_count(1, 1, args.len(), id)?;
+                let f: fn(N) -> N = $f;
+                if is_gamma_pole(&args[0]) {
+                    Err(InterpretError::OutOfDomain(id))
+                } else {
+                    Ok(f(args[0].clone()))
+                }
+            }),
+        );
+    };
+}
+
+/// Registers `$f` as a bit shift, which is not defined for every amount.
+macro_rules! shift {
+    ($interpreter:expr, $name:expr, $f:expr) => {
+        $interpreter.set_var(
+            String::from($name),
+            Variant::Function(|id, args, _| {
+                ensure_arg_count(2, 2, args.len(), id)?;
+                let f: fn(N, N) -> Option<N> = $f;
+                f(args[0].clone(), args[1].clone()).ok_or(InterpretError::OutOfDomain(id))
+            }),
+        );
+    };
+}
+
+/// Registers `$f` as a trigonometric function of a complex angle in the angle mode.
+macro_rules! complex_trig {
+    ($interpreter:expr, $name:expr, $f:expr) => {
+        $interpreter.set_var(
+            String::from($name),
+            Variant::Function(|id, args, settings| {
+                ensure_arg_count(1, 1, args.len(), id)?;
+                let f: fn(Complex) -> Complex = $f;
+                let radians = args[0] * Complex::from(settings.angle_mode.to_radians(1.0));
+                Ok(f(radians))
+            }),
+        );
+    };
+}
+
+/// Registers `$f` as an inverse trigonometric function returning a complex angle in the angle
+/// mode.
+macro_rules! complex_inverse_trig {
+    ($interpreter:expr, $name:expr, $f:expr) => {
+        $interpreter.set_var(
+            String::from($name),
+            Variant::Function(|id, args, settings| {
+                ensure_arg_count(1, 1, args.len(), id)?;
+                let f: fn(Complex) -> Complex = $f;
+                Ok(f(args[0]) * Complex::from(settings.angle_mode.from_radians(1.0)))
+            }),
+        );
+    };
+}
+
+/// Registers a function converting a complex angle in `$unit` to the angle mode.
+macro_rules! complex_angle_unit {
+    ($interpreter:expr, $name:expr, $unit:expr) => {
+        $interpreter.set_var(
+            String::from($name),
+            Variant::Function(|id, args, settings| {
+                ensure_arg_count(1, 1, args.len(), id)?;
+                if settings.angle_mode == $unit {
+                    return Ok(args[0]);
+                }
+                let radians = $unit.to_radians(1.0);
+                Ok(args[0] * Complex::from(settings.angle_mode.from_radians(radians)))
+            }),
+        );
+    };
+}
+
+/// Registers `$f` as a function of a plain number, so a quantity with a dimension is a
+/// `DimensionMismatch` error.
+macro_rules! plain {
+    ($interpreter:expr, $name:expr, $f:expr) => {
+        $interpreter.set_var(
+            String::from($name),
+            Variant::Function(|id, args, settings| {
+                ensure_arg_count(1, 1, args.len(), id)?;
+                let f: fn(f64, &Settings) -> f64 = $f;
+                Ok(Quantity::number(f(args[0].plain(id)?, settings)))
+            }),
+        );
+    };
+}
+
+/// Registers a function rounding a decimal to a whole number in `$mode`.
+#[cfg(feature = "num")]
+macro_rules! decimal_rounding {
+    ($interpreter:expr, $name:expr, $mode:expr) => {
+        $interpreter.set_var(
+            String::from($name),
+            Variant::Function(|id, args, _| {
+                ensure_arg_count(1, 1, args.len(), id)?;
+                Ok(args[0].round_at(0, $mode))
+            }),
+        );
+    };
+}
+
+/// Registers the functions every `Num` supports: `abs`, `sign`, and `min` and `max` of any
+/// number of arguments, which may be lists.
+pub fn install_num_stdlib<N: Num>(interpreter: &mut Interpreter<N>) {
+    interpreter.set_var(
+        String::from("abs"),
+        Variant::Function(|id, args, settings| {
+            ensure_arg_count(1, 1, args.len(), id)?;
+            abs(args[0].clone(), settings)
+        }),
+    );
+    interpreter.set_var(
+        String::from("sign"),
+        Variant::Function(|id, args, settings| {
+            ensure_arg_count(1, 1, args.len(), id)?;
+            if args[0] > N::zero() {
+                Ok(N::one())
+            } else if args[0] < N::zero() {
+                Ok(N::one().negate(settings)?)
+            } else {
+                // Zero, or NaN
+                Ok(args[0].clone())
+            }
+        }),
+    );
+    aggregate!(interpreter, "min", |id, args, _| {
+        ensure_arg_count(1, usize::MAX, args.len(), id)?;
+        Ok(args[1..].iter().fold(
+            args[0].clone(),
+            |min, x| {
+                if *x < min {
+                    x.clone()
+                } else {
+                    min
+                }
+            },
+        ))
+    });
+    aggregate!(interpreter, "max", |id, args, _| {
+        ensure_arg_count(1, usize::MAX, args.len(), id)?;
+        Ok(args[1..].iter().fold(
+            args[0].clone(),
+            |max, x| {
+                if *x > max {
+                    x.clone()
+                } else {
+                    max
+                }
+            },
+        ))
+    });
+}
+
+/// Registers the functions of lists: `len(v)`, the dot product `dot(a, b)`, and `sum`,
+/// `product` and `mean` of any number of arguments, which may be lists.
+pub fn install_list_stdlib<N: Num>(interpreter: &mut Interpreter<N>) {
+    interpreter.set_var(
+        String::from("len"),
+        Variant::ValueFunction(|id, args, settings| {
+            ensure_arg_count(1, 1, args.len(), id)?;
+            let list = args[0].clone().into_list(id)?;
+            count(list.len(), settings).map(Value::Num)
+        }),
+    );
+    interpreter.set_var(
+        String::from("dot"),
+        Variant::ValueFunction(|id, args, settings| {
+            ensure_arg_count(2, 2, args.len(), id)?;
+            let a = args[0].clone().into_list(id)?;
+            let b = args[1].clone().into_list(id)?;
+            if a.len() != b.len() {
+                return Err(InterpretError::ShapeMismatch(
+                    id,
+                    args[0].describe(),
+                    args[1].describe(),
+                    0..0,
+                ));
+            }
+            let mut sum = N::zero();
+            for (x, y) in a.into_iter().zip(b) {
+                let product = x.arithmetic(OpVal::Mul, y, settings)?;
+                sum = sum.arithmetic(OpVal::Add, product, settings)?;
+            }
+            Ok(Value::Num(sum))
+        }),
+    );
+    aggregate!(interpreter, "sum", |_, args, settings| {
+        args.iter().try_fold(N::zero(), |sum, x| {
+            sum.arithmetic(OpVal::Add, x.clone(), settings)
+        })
+    });
+    aggregate!(interpreter, "product", |_, args, settings| {
+        args.iter().try_fold(N::one(), |product, x| {
+            product.arithmetic(OpVal::Mul, x.clone(), settings)
+        })
+    });
+    aggregate!(interpreter, "mean", |id, args, settings| {
+        ensure_arg_count(1, usize::MAX, args.len(), id)?;
+        let sum = args.iter().try_fold(N::zero(), |sum, x| {
+            sum.arithmetic(OpVal::Add, x.clone(), settings)
+        })?;
+        sum.arithmetic(OpVal::Div, count(args.len(), settings)?, settings)
+    });
+}
+
+/// The largest size of matrix `identity` builds.
+const MAX_IDENTITY_SIZE: usize = 1000;
+
+/// Registers the functions of matrices: `transpose(A)`, the determinant `det(A)`, the inverse
+/// `inv(A)`, the `n`×`n` identity matrix `identity(n)`, and `solve(A, b)`, which solves
+/// `A * x = b` for a list or matrix `b`.
+pub fn install_matrix_stdlib<N: Num>(interpreter: &mut Interpreter<N>) {
+    interpreter.set_var(
+        String::from("transpose"),
+        Variant::ValueFunction(|id, args, _| {
+            ensure_arg_count(1, 1, args.len(), id)?;
+            let matrix = args[0].clone().into_matrix(id)?;
+            Ok(Value::Matrix(matrix.transpose()))
+        }),
+    );
+    interpreter.set_var(
+        String::from("det"),
+        Variant::ValueFunction(|id, args, settings| {
+            ensure_arg_count(1, 1, args.len(), id)?;
+            let matrix = args[0].clone().into_matrix(id)?;
+            matrix.determinant(settings).map(Value::Num)
+        }),
+    );
+    interpreter.set_var(
+        String::from("inv"),
+        Variant::ValueFunction(|id, args, settings| {
+            ensure_arg_count(1, 1, args.len(), id)?;
+            let matrix = args[0].clone().into_matrix(id)?;
+            matrix.inverse(settings).map(Value::Matrix)
+        }),
+    );
+    interpreter.set_var(
+        String::from("identity"),
+        Variant::ValueFunction(|id, args, settings| {
+            ensure_arg_count(1, 1, args.len(), id)?;
+            let n = args[0].clone().into_num(id)?;
+            // Counting up to n needs no conversion from N, and rejects fractions
+            let mut size = N::zero();
+            for len in 0..=MAX_IDENTITY_SIZE {
+                if size == n && len > 0 {
+                    return Ok(Value::Matrix(Matrix::identity(len)));
+                }
+                size = match size.arithmetic(OpVal::Add, N::one(), settings) {
+                    Ok(next) => next,
+                    Err(_) => break,
+                };
+            }
+            Err(InterpretError::OutOfDomain(id))
+        }),
+    );
+    interpreter.set_var(
+        String::from("solve"),
+        Variant::ValueFunction(|id, args, settings| {
+            ensure_arg_count(2, 2, args.len(), id)?;
+            let a = args[0].clone().into_matrix(id)?;
+            match args[1].clone() {
+                Value::List(list) => {
+                    if list.len() != a.rows() {
+                        return Err(InterpretError::ShapeMismatch(
+                            id,
+                            args[0].describe(),
+                            args[1].describe(),
+                            0..0,
+                        ));
+                    }
+                    let x = a.solve(&Matrix::column(list), settings)?;
+                    Ok(Value::List(x.elements().to_vec()))
+                }
+                b => Ok(Value::Matrix(a.solve(&b.into_matrix(id)?, settings)?)),
+            }
+        }),
+    );
+}
+
+/// Registers the constants `pi`, `e` and `tau`, and the functions of `Real` numbers. `inf` and
+/// `nan` are registered too if the type can represent them.
+///
+/// Trigonometric functions work in the angle mode of the settings, and `deg`, `rad` and `grad`
+/// convert angles to it, e.g. `sin(deg(30))`, like `°` in `sin(30°)`. `log(x)` is the base 10
+/// logarithm unless a base is given as `log(x, b)`.
+pub fn install_real_stdlib<N: Real>(interpreter: &mut Interpreter<N>) {
+    interpreter.set_var(String::from("pi"), Variant::Num(N::from_f64(PI)));
+    interpreter.set_var(
+        String::from("e"),
+        Variant::Num(N::from_f64(std::f64::consts::E)),
+    );
+    interpreter.set_var(
+        String::from("tau"),
+        Variant::Num(N::from_f64(std::f64::consts::TAU)),
+    );
+    if !N::from_f64(f64::INFINITY).is_finite() {
+        interpreter.set_var(
+            String::from("inf"),
+            Variant::Num(N::from_f64(f64::INFINITY)),
+        );
+        interpreter.set_var(String::from("nan"), Variant::Num(N::from_f64(f64::NAN)));
+    }
+
+    unary!(interpreter, "sqrt", N::sqrt);
+    unary!(interpreter, "cbrt", N::cbrt);
+    binary!(interpreter, "root", root);
+    binary!(interpreter, "hypot", N::hypot);
+
+    // Factorial and gamma functions, which are not defined at their poles
+    interpreter.set_var(
+        String::from("factorial"),
+        Variant::Function(|id, args, _| {
+            ensure_arg_count(1, 1, args.len(), id)?;
+            let x = args[0].clone() + N::one();
+            if is_gamma_pole(&x) {
+                Err(InterpretError::OutOfDomain(id))
+            } else {
+                Ok(x.gamma())
+            }
+        }),
+    );
+    gamma_like!(interpreter, "gamma", N::gamma);
+    gamma_like!(interpreter, "lgamma", N::ln_gamma);
+    interpreter.set_var(
+        String::from("beta"),
+        Variant::Function(|id, args, _| {
+            ensure_arg_count(2, 2, args.len(), id)?;
+            if is_gamma_pole(&args[0]) || is_gamma_pole(&args[1]) {
+                Err(InterpretError::OutOfDomain(id))
+            } else {
+                Ok(beta(args[0].clone(), args[1].clone()))
+            }
+        }),
+    );
+
+    // Trigonometric and hyperbolic
+    trig!(interpreter, "sin", N::sin);
+    trig!(interpreter, "cos", N::cos);
+    trig!(interpreter, "tan", N::tan);
+    inverse_trig!(interpreter, "asin", N::asin);
+    inverse_trig!(interpreter, "acos", N::acos);
+    inverse_trig!(interpreter, "atan", N::atan);
+    interpreter.set_var(
+        String::from("atan2"),
+        Variant::Function(|id, args, settings| {
+            ensure_arg_count(2, 2, args.len(), id)?;
+            let radians = args[0].clone().atan2(args[1].clone());
+            Ok(settings.angle_mode.from_radians(radians))
+        }),
+    );
+    angle_unit!(interpreter, "deg", AngleMode::Degrees);
+    angle_unit!(interpreter, "rad", AngleMode::Radians);
+    angle_unit!(interpreter, "grad", AngleMode::Gradians);
+    unary!(interpreter, "sinh", N::sinh);
+    unary!(interpreter, "cosh", N::cosh);
+    unary!(interpreter, "tanh", N::tanh);
+    unary!(interpreter, "asinh", N::asinh);
+    unary!(interpreter, "acosh", N::acosh);
+    unary!(interpreter, "atanh", N::atanh);
+
+    // Exponents and logarithms
+    unary!(interpreter, "exp", N::exp);
+    unary!(interpreter, "ln", N::ln);
+    unary!(interpreter, "log2", N::log2);
+    unary!(interpreter, "log10", N::log10);
+    interpreter.set_var(
+        String::from("log"),
+        Variant::Function(|id, args, _| {
+            ensure_arg_count(1, 2, args.len(), id)?;
+            match args.get(1) {
+                Some(base) => Ok(args[0].clone().ln() / base.clone().ln()),
+                None => Ok(args[0].clone().log10()),
+            }
+        }),
+    );
+
+    // Rounding
+    unary!(interpreter, "floor", N::floor);
+    unary!(interpreter, "ceil", N::ceil);
+    unary!(interpreter, "round", N::round);
+    unary!(interpreter, "trunc", N::trunc);
+}
+
+/// Registers the functions of `Integer` numbers: `factorial`, `gcd` and `lcm` of any number of
+/// arguments, the number theory functions `modinv(a, m)`, `powmod(a, e, m)`, `isprime(n)` and
+/// `factor(n)`, and the bitwise `and`, `or`, `xor`, `not`, `shl` and `shr`. Overflow is handled
+/// according to the arithmetic mode, except that the modular functions never overflow.
+///
+/// `isprime` gives 1 or 0. `factor` gives the smallest prime factor of a number from 2 up, so a
+/// number is factored completely by repeatedly dividing it by `factor`.
+pub fn install_integer_stdlib<N: Integer>(interpreter: &mut Interpreter<N>) {
+    interpreter.set_var(
+        String::from("factorial"),
+        Variant::Function(|id, args, settings| {
+            ensure_arg_count(1, 1, args.len(), id)?;
+            if args[0] < N::zero() {
+                return Err(InterpretError::OutOfDomain(id));
+            }
+            let (mut product, mut i) = (N::one(), N::one());
+            while i < args[0] {
+                let factor = i + N::one();
+                let next = product
+                    .clone()
+                    .arithmetic(OpVal::Mul, factor.clone(), settings)?;
+                // A wrapping product that reaches 0 stays 0, and a saturating one that reaches
+                // the maximum stays there, so the rest of a huge factorial need not be computed
+                let saturated = next == product
+                    && matches!(
+                        next.clone().arithmetic(OpVal::Add, N::one(), settings),
+                        Ok(ref n) if *n == next
+                    );
+                product = next;
+                if product == N::zero() || saturated {
+                    break;
+                }
+                i = factor;
+            }
+            Ok(product)
+        }),
+    );
+    aggregate!(interpreter, "gcd", |id, args, settings| {
+        ensure_arg_count(1, usize::MAX, args.len(), id)?;
+        let mut result = N::zero();
+        for n in args {
+            result = gcd(result, n.clone());
+        }
+        abs(result, settings)
+    });
+    aggregate!(interpreter, "lcm", |id, args, settings| {
+        ensure_arg_count(1, usize::MAX, args.len(), id)?;
+        let mut result = N::one();
+        for n in args {
+            if *n == N::zero() {
+                return Ok(N::zero());
+            }
+            let divisor = gcd(result.clone(), n.clone());
+            result = (result / divisor).arithmetic(OpVal::Mul, n.clone(), settings)?;
+        }
+        abs(result, settings)
+    });
+    interpreter.set_var(
+        String::from("modinv"),
+        Variant::Function(|id, args, _| {
+            ensure_arg_count(2, 2, args.len(), id)?;
+            if args[1] <= N::zero() {
+                return Err(InterpretError::OutOfDomain(id));
+            }
+            mod_inverse(args[0].clone(), &args[1]).ok_or(InterpretError::OutOfDomain(id))
+        }),
+    );
+    interpreter.set_var(
+        String::from("powmod"),
+        Variant::Function(|id, args, _| {
+            ensure_arg_count(3, 3, args.len(), id)?;
+            if args[2] <= N::zero() {
+                return Err(InterpretError::OutOfDomain(id));
+            } else if args[1] < N::zero() {
+                return Err(InterpretError::NegativeExponent);
+            }
+            let base = rem_euclid(args[0].clone(), &args[2]);
+            Ok(pow_mod(base, args[1].clone(), &args[2]))
+        }),
+    );
+    interpreter.set_var(
+        String::from("isprime"),
+        Variant::Function(|id, args, _| {
+            ensure_arg_count(1, 1, args.len(), id)?;
+            Ok(if is_prime(&args[0]) {
+                N::one()
+            } else {
+                N::zero()
+            })
+        }),
+    );
+    interpreter.set_var(
+        String::from("factor"),
+        Variant::Function(|id, args, _| {
+            ensure_arg_count(1, 1, args.len(), id)?;
+            if args[0] < small(2) {
+                return Err(InterpretError::OutOfDomain(id));
+            }
+            Ok(smallest_prime_factor(args[0].clone()))
+        }),
+    );
+    binary!(interpreter, "and", |a, b| a & b);
+    binary!(interpreter, "or", |a, b| a | b);
+    binary!(interpreter, "xor", |a, b| a ^ b);
+    unary!(interpreter, "not", |a| !a);
+    shift!(interpreter, "shl", N::shift_left);
+    shift!(interpreter, "shr", N::shift_right);
+}
+
+/// Registers the constants `pi`, `e`, `tau` and the imaginary unit `i` (also `j`), and the
+/// functions of complex numbers.
+///
+/// Square roots, exponentials, logarithms and trigonometry take their principal values, so
+/// `sqrt(-1)` is `i` and `ln(-1)` is `πi`. `re`, `im`, `abs`, `arg` and `conj` take complex
+/// numbers apart, and `polar(r, θ)` puts them together. Angles are in the angle mode of the
+/// settings. Functions that need an order, `min`, `max`, `factorial` and `gamma`, only accept
+/// real numbers.
+pub fn install_complex_stdlib(interpreter: &mut Interpreter<Complex>) {
+    // The registering macros refer to the number type as N
+    type N = Complex;
+
+    interpreter.set_var(String::from("pi"), Variant::Num(Complex::from(PI)));
+    interpreter.set_var(
+        String::from("e"),
+        Variant::Num(Complex::from(std::f64::consts::E)),
+    );
+    interpreter.set_var(
+        String::from("tau"),
+        Variant::Num(Complex::from(std::f64::consts::TAU)),
+    );
+    interpreter.set_var(String::from("i"), Variant::Num(Complex::I));
+    interpreter.set_var(String::from("j"), Variant::Num(Complex::I));
+
+    // Parts
+    unary!(interpreter, "re", |z| Complex::from(z.re));
+    unary!(interpreter, "im", |z| Complex::from(z.im));
+    unary!(interpreter, "abs", |z| Complex::from(z.abs()));
+    unary!(interpreter, "conj", Complex::conj);
+    unary!(interpreter, "sign", |z| if z == Complex::zero() {
+        z
+    } else {
+        z / Complex::from(z.abs())
+    });
+    interpreter.set_var(
+        String::from("arg"),
+        Variant::Function(|id, args, settings| {
+            ensure_arg_count(1, 1, args.len(), id)?;
+            Ok(Complex::from(
+                settings.angle_mode.from_radians(args[0].arg()),
+            ))
+        }),
+    );
+    interpreter.set_var(
+        String::from("polar"),
+        Variant::Function(|id, args, settings| {
+            ensure_arg_count(2, 2, args.len(), id)?;
+            let (r, theta) = (real_arg(id, args[0])?, real_arg(id, args[1])?);
+            Ok(Complex::from_polar(
+                r,
+                settings.angle_mode.to_radians(theta),
+            ))
+        }),
+    );
+    aggregate!(interpreter, "min", |id, args, _| {
+        ensure_arg_count(1, usize::MAX, args.len(), id)?;
+        let mut min = real_arg(id, args[0])?;
+        for z in &args[1..] {
+            let x = real_arg(id, *z)?;
+            if x < min {
+                min = x;
+            }
+        }
+        Ok(Complex::from(min))
+    });
+    aggregate!(interpreter, "max", |id, args, _| {
+        ensure_arg_count(1, usize::MAX, args.len(), id)?;
+        let mut max = real_arg(id, args[0])?;
+        for z in &args[1..] {
+            let x = real_arg(id, *z)?;
+            if x > max {
+                max = x;
+            }
+        }
+        Ok(Complex::from(max))
+    });
+
+    // Roots, exponents and logarithms
+    unary!(interpreter, "sqrt", Complex::sqrt);
+    unary!(interpreter, "exp", Complex::exp);
+    unary!(interpreter, "ln", Complex::ln);
+    unary!(interpreter, "log2", |z| z.ln()
+        / Complex::from(std::f64::consts::LN_2));
+    unary!(interpreter, "log10", |z| z.ln()
+        / Complex::from(std::f64::consts::LN_10));
+    interpreter.set_var(
+        String::from("log"),
+        Variant::Function(|id, args, _| {
+            ensure_arg_count(1, 2, args.len(), id)?;
+            let base = args
+                .get(1)
+                .map_or(Complex::from(std::f64::consts::LN_10), |base| base.ln());
+            Ok(args[0].ln() / base)
+        }),
+    );
+
+    // Trigonometric and hyperbolic
+    complex_trig!(interpreter, "sin", Complex::sin);
+    complex_trig!(interpreter, "cos", Complex::cos);
+    complex_trig!(interpreter, "tan", Complex::tan);
+    complex_inverse_trig!(interpreter, "asin", Complex::asin);
+    complex_inverse_trig!(interpreter, "acos", Complex::acos);
+    complex_inverse_trig!(interpreter, "atan", Complex::atan);
+    complex_angle_unit!(interpreter, "deg", AngleMode::Degrees);
+    complex_angle_unit!(interpreter, "rad", AngleMode::Radians);
+    complex_angle_unit!(interpreter, "grad", AngleMode::Gradians);
+    unary!(interpreter, "sinh", Complex::sinh);
+    unary!(interpreter, "cosh", Complex::cosh);
+    unary!(interpreter, "tanh", Complex::tanh);
+
+    // Factorial and gamma of real numbers
+    interpreter.set_var(
+        String::from("factorial"),
+        Variant::Function(|id, args, _| {
+            ensure_arg_count(1, 1, args.len(), id)?;
+            let x = real_arg(id, args[0])? + 1.0;
+            if is_gamma_pole(&x) {
+                Err(InterpretError::OutOfDomain(id))
+            } else {
+                Ok(Complex::from(gamma(x)))
+            }
+        }),
+    );
+    interpreter.set_var(
+        String::from("gamma"),
+        Variant::Function(|id, args, _| {
+            ensure_arg_count(1, 1, args.len(), id)?;
+            let x = real_arg(id, args[0])?;
+            if is_gamma_pole(&x) {
+                Err(InterpretError::OutOfDomain(id))
+            } else {
+                Ok(Complex::from(gamma(x)))
+            }
+        }),
+    );
+}
+
+/// Registers the functions of intervals that `install_real_stdlib` does not have, or has in a
+/// form that needs an order: `interval(a, b)`, which is the interval from `a` to `b`, `lo`,
+/// `hi`, `mid` and `width`, and `abs`, `sign`, `min` and `max`, which give the interval of
+/// their results.
+pub fn install_interval_stdlib(interpreter: &mut Interpreter<Interval>) {
+    // The registering macros refer to the number type as N
+    type N = Interval;
+
+    binary!(interpreter, "interval", Interval::hull);
+    unary!(interpreter, "lo", |x| Interval::point(x.lo));
+    unary!(interpreter, "hi", |x| Interval::point(x.hi));
+    unary!(interpreter, "mid", |x| Interval::point(x.mid()));
+    unary!(interpreter, "width", |x| Interval::point(x.width()));
+    unary!(interpreter, "abs", Interval::abs);
+    unary!(interpreter, "sign", |x| {
+        let sign = |x: f64| {
+            if x > 0.0 {
+                1.0
+            } else if x < 0.0 {
+                -1.0
+            } else {
+                // Zero, or NaN
+                x
+            }
+        };
+        Interval::new(sign(x.lo), sign(x.hi))
+    });
+    aggregate!(interpreter, "min", |id, args, _| {
+        ensure_arg_count(1, usize::MAX, args.len(), id)?;
+        Ok(args[1..].iter().fold(args[0], |min, x| min.min(*x)))
+    });
+    aggregate!(interpreter, "max", |id, args, _| {
+        ensure_arg_count(1, usize::MAX, args.len(), id)?;
+        Ok(args[1..].iter().fold(args[0], |max, x| max.max(*x)))
+    });
+}
+
+/// Registers `value(x)` and `uncertainty(x)`, which take a number with uncertainty apart.
+pub fn install_uncertain_stdlib(interpreter: &mut Interpreter<Uncertain>) {
+    // The registering macros refer to the number type as N
+    type N = Uncertain;
+
+    unary!(interpreter, "value", |x| Uncertain::exact(x.value()));
+    unary!(interpreter, "uncertainty", |x| Uncertain::exact(
+        x.uncertainty()
+    ));
+}
+
+/// Registers every unit, with and without prefixes, as a variable holding one of it, so units
+/// can be used on their own, as in `km/h` or `x to mph`, and `value(x)`, which is the number
+/// of units in `x`. A unit named like a function already registered, like the minute `min`,
+/// leaves the function in place. Used like a variable, the name is still the unit, through
+/// `Num::unit_named`.
+///
+/// It also replaces the functions of `install_real_stdlib` that give NaN for misused units:
+/// functions of plain numbers like `sin`, `ln` and `gamma` make a dimension a
+/// `DimensionMismatch` error, so do `hypot` and `atan2` of different dimensions, and `sqrt`,
+/// `cbrt` and `root` of a unit whose powers they can't divide are an `OutOfDomain` error.
+pub fn install_quantity_stdlib(interpreter: &mut Interpreter<Quantity>) {
+    // The registering macros refer to the number type as N
+    type N = Quantity;
+
+    for symbol in crate::quantity::unit_symbols() {
+        if let Some(Variant::Function(_) | Variant::ValueFunction(_)) =
+            interpreter.vars.get(&symbol)
+        {
+            continue;
+        }
+        let unit = Quantity::new(1.0, &symbol).expect("units parse");
+        interpreter.set_var(symbol, Variant::Num(unit));
+    }
+    unary!(interpreter, "value", |x| Quantity::number(x.value()));
+
+    // Roots divide the powers of the unit
+    interpreter.set_var(
+        String::from("sqrt"),
+        Variant::Function(|id, args, _| {
+            ensure_arg_count(1, 1, args.len(), id)?;
+            args[0]
+                .root(2, f64::sqrt)
+                .ok_or(InterpretError::OutOfDomain(id))
+        }),
+    );
+    interpreter.set_var(
+        String::from("cbrt"),
+        Variant::Function(|id, args, _| {
+            ensure_arg_count(1, 1, args.len(), id)?;
+            args[0]
+                .root(3, f64::cbrt)
+                .ok_or(InterpretError::OutOfDomain(id))
+        }),
+    );
+    interpreter.set_var(
+        String::from("root"),
+        Variant::Function(|id, args, _| {
+            ensure_arg_count(2, 2, args.len(), id)?;
+            let n = args[1].plain(id)?;
+            if args[0].is_number() {
+                return Ok(Quantity::number(root(args[0].value(), n)));
+            }
+            if n.fract() != 0.0 || n.abs() > f64::from(i32::MAX) {
+                return Err(InterpretError::OutOfDomain(id));
+            }
+            args[0]
+                .root(n as i32, |x| root(x, n))
+                .ok_or(InterpretError::OutOfDomain(id))
+        }),
+    );
+
+    // Functions of two quantities of the same dimension
+    interpreter.set_var(
+        String::from("hypot"),
+        Variant::Function(|id, args, _| {
+            ensure_arg_count(2, 2, args.len(), id)?;
+            args[1].value_in_unit_of(&args[0], id)?;
+            Ok(args[0].clone().hypot(args[1].clone()))
+        }),
+    );
+    interpreter.set_var(
+        String::from("atan2"),
+        Variant::Function(|id, args, settings| {
+            ensure_arg_count(2, 2, args.len(), id)?;
+            args[1].value_in_unit_of(&args[0], id)?;
+            let radians = args[0].clone().atan2(args[1].clone());
+            Ok(settings.angle_mode.from_radians(radians))
+        }),
+    );
+
+    // Functions of plain numbers
+    plain!(interpreter, "sin", |x, settings| settings
+        .angle_mode
+        .to_radians(x)
+        .sin());
+    plain!(interpreter, "cos", |x, settings| settings
+        .angle_mode
+        .to_radians(x)
+        .cos());
+    plain!(interpreter, "tan", |x, settings| settings
+        .angle_mode
+        .to_radians(x)
+        .tan());
+    plain!(interpreter, "asin", |x, settings| settings
+        .angle_mode
+        .from_radians(x.asin()));
+    plain!(interpreter, "acos", |x, settings| settings
+        .angle_mode
+        .from_radians(x.acos()));
+    plain!(interpreter, "atan", |x, settings| settings
+        .angle_mode
+        .from_radians(x.atan()));
+    plain!(interpreter, "sinh", |x, _| x.sinh());
+    plain!(interpreter, "cosh", |x, _| x.cosh());
+    plain!(interpreter, "tanh", |x, _| x.tanh());
+    plain!(interpreter, "asinh", |x, _| x.asinh());
+    plain!(interpreter, "acosh", |x, _| x.acosh());
+    plain!(interpreter, "atanh", |x, _| x.atanh());
+    plain!(interpreter, "exp", |x, _| x.exp());
+    plain!(interpreter, "ln", |x, _| x.ln());
+    plain!(interpreter, "log2", |x, _| x.log2());
+    plain!(interpreter, "log10", |x, _| x.log10());
+    interpreter.set_var(
+        String::from("log"),
+        Variant::Function(|id, args, _| {
+            ensure_arg_count(1, 2, args.len(), id)?;
+            let x = args[0].plain(id)?;
+            match args.get(1) {
+                Some(base) => Ok(Quantity::number(x.ln() / base.plain(id)?.ln())),
+                None => Ok(Quantity::number(x.log10())),
+            }
+        }),
+    );
+    interpreter.set_var(
+        String::from("factorial"),
+        Variant::Function(|id, args, _| {
+            ensure_arg_count(1, 1, args.len(), id)?;
+            let x = args[0].plain(id)? + 1.0;
+            if is_gamma_pole(&x) {
+                Err(InterpretError::OutOfDomain(id))
+            } else {
+                Ok(Quantity::number(gamma(x)))
+            }
+        }),
+    );
+    interpreter.set_var(
+        String::from("gamma"),
+        Variant::Function(|id, args, _| {
+            ensure_arg_count(1, 1, args.len(), id)?;
+            let x = args[0].plain(id)?;
+            if is_gamma_pole(&x) {
+                Err(InterpretError::OutOfDomain(id))
+            } else {
+                Ok(Quantity::number(gamma(x)))
+            }
+        }),
+    );
+    interpreter.set_var(
+        String::from("lgamma"),
+        Variant::Function(|id, args, _| {
+            ensure_arg_count(1, 1, args.len(), id)?;
+            let x = args[0].plain(id)?;
+            if is_gamma_pole(&x) {
+                Err(InterpretError::OutOfDomain(id))
+            } else {
+                Ok(Quantity::number(ln_gamma(x)))
+            }
+        }),
+    );
+    interpreter.set_var(
+        String::from("beta"),
+        Variant::Function(|id, args, _| {
+            ensure_arg_count(2, 2, args.len(), id)?;
+            let (a, b) = (args[0].plain(id)?, args[1].plain(id)?);
+            if is_gamma_pole(&a) || is_gamma_pole(&b) {
+                Err(InterpretError::OutOfDomain(id))
+            } else {
+                Ok(Quantity::number(beta(a, b)))
+            }
+        }),
+    );
+}
+
+/// Registers `modinv(x)`, the inverse of `x` modulo `M`, which is an `OutOfDomain` error if it
+/// does not exist.
+pub fn install_modular_stdlib<const M: u64>(interpreter: &mut Interpreter<Modular<M>>) {
+    interpreter.set_var(
+        String::from("modinv"),
+        Variant::Function(|id, args, _| {
+            ensure_arg_count(1, 1, args.len(), id)?;
+            args[0].inverse().ok_or(InterpretError::OutOfDomain(id))
+        }),
+    );
+}
+
+/// Registers the functions of exact fractions: `floor`, `ceil`, `round`, `trunc`, `numer` and
+/// `denom`, and `factorial` of whole numbers.
+#[cfg(feature = "num")]
+pub fn install_rational_stdlib(interpreter: &mut Interpreter<Rational>) {
+    // The registering macros refer to the number type as N
+    type N = Rational;
+
+    unary!(interpreter, "floor", |x| x.floor());
+    unary!(interpreter, "ceil", |x| x.ceil());
+    unary!(interpreter, "round", |x| x.round());
+    unary!(interpreter, "trunc", |x| x.trunc());
+    unary!(interpreter, "numer", |x| Rational::from(x.numer().clone()));
+    unary!(interpreter, "denom", |x| Rational::from(x.denom().clone()));
+    interpreter.set_var(
+        String::from("factorial"),
+        Variant::Function(|id, args, _| {
+            ensure_arg_count(1, 1, args.len(), id)?;
+            if !args[0].is_whole() || args[0] < Rational::zero() {
+                return Err(InterpretError::OutOfDomain(id));
+            }
+            let mut product = BigInt::one();
+            let mut i = BigInt::one();
+            while &i < args[0].numer() {
+                i += BigInt::one();
+                product *= i.clone();
+            }
+            Ok(Rational::from(product))
+        }),
+    );
+}
+
+/// Registers the functions of decimals, which round to the precision of the settings:
+/// `sqrt`, `exp`, `ln`, `log(x)` (base 10) and `log(x, base)`, `factorial` of whole numbers,
+/// and the rounding functions `floor`, `ceil`, `trunc` and `round(x)` or `round(x, places)`.
+/// `round` uses the rounding mode of the settings, e.g. half to even.
+#[cfg(feature = "num")]
+pub fn install_decimal_stdlib(interpreter: &mut Interpreter<Decimal>) {
+    interpreter.set_var(
+        String::from("sqrt"),
+        Variant::Function(|id, args, settings| {
+            ensure_arg_count(1, 1, args.len(), id)?;
+            args[0]
+                .sqrt(&settings.decimal)
+                .ok_or(InterpretError::OutOfDomain(id))
+        }),
+    );
+    interpreter.set_var(
+        String::from("exp"),
+        Variant::Function(|id, args, settings| {
+            ensure_arg_count(1, 1, args.len(), id)?;
+            args[0].exp(&settings.decimal)
+        }),
+    );
+    interpreter.set_var(
+        String::from("ln"),
+        Variant::Function(|id, args, settings| {
+            ensure_arg_count(1, 1, args.len(), id)?;
+            args[0]
+                .ln(&settings.decimal)
+                .ok_or(InterpretError::OutOfDomain(id))
+        }),
+    );
+    interpreter.set_var(
+        String::from("log"),
+        Variant::Function(|id, args, settings| {
+            ensure_arg_count(1, 2, args.len(), id)?;
+            let working = DecimalContext {
+                precision: settings.decimal.precision + 5,
+                ..settings.decimal
+            };
+            let base = args.get(1).cloned().unwrap_or_else(|| Decimal::from(10));
+            match (args[0].ln(&working), base.ln(&working)) {
+                (Some(x), Some(base)) if base != Decimal::zero() => x
+                    .div_with(&base, &settings.decimal)
+                    .ok_or(InterpretError::OutOfDomain(id)),
+                _ => Err(InterpretError::OutOfDomain(id)),
+            }
+        }),
+    );
+    interpreter.set_var(
+        String::from("round"),
+        Variant::Function(|id, args, settings| {
+            ensure_arg_count(1, 2, args.len(), id)?;
+            let places = match args.get(1) {
+                Some(places) => places
+                    .to_integer()
+                    .and_then(|n| n.to_i64())
+                    .ok_or(InterpretError::OutOfDomain(id))?,
+                None => 0,
+            };
+            Ok(args[0].round_at(-places, settings.decimal.rounding))
+        }),
+    );
+    interpreter.set_var(
+        String::from("factorial"),
+        Variant::Function(|id, args, settings| {
+            ensure_arg_count(1, 1, args.len(), id)?;
+            let n = match args[0].to_integer() {
+                Some(n) if n >= BigInt::zero() => n,
+                _ => return Err(InterpretError::OutOfDomain(id)),
+            };
+            let working = DecimalContext {
+                precision: settings.decimal.precision + 10,
+                ..settings.decimal
+            };
+            let (mut product, mut i) = (Decimal::one(), BigInt::one());
+            while i < n {
+                i += BigInt::one();
+                product = (product * Decimal::from(i.clone())).round(&working);
+            }
+            Ok(product.round(&settings.decimal))
+        }),
+    );
+    decimal_rounding!(interpreter, "floor", RoundingMode::Floor);
+    decimal_rounding!(interpreter, "ceil", RoundingMode::Ceiling);
+    decimal_rounding!(interpreter, "trunc", RoundingMode::Down);
+}
+
+/// The real part of an argument of the function `id`, which must not have an imaginary part.
+fn real_arg(id: &str, z: Complex) -> Result<f64, InterpretError<'_>> {
+    if z.is_real() {
+        Ok(z.re)
+    } else {
+        Err(InterpretError::OutOfDomain(id))
+    }
+}
+
+fn abs<N: Num>(x: N, settings: &Settings) -> Result<N, InterpretError<'static>> {
+    if x < N::zero() {
+        x.negate(settings)
+    } else if x == N::zero() {
+        // Turns -0.0 into 0.0
+        Ok(N::zero())
+    } else {
+        Ok(x)
+    }
+}
+
+/// The greatest common divisor of `a` and `b` by Euclid's algorithm. Its sign depends on the
+/// signs of `a` and `b`.
+fn gcd<N: Num>(mut a: N, mut b: N) -> N {
+    while b != N::zero() {
+        let r = a % b.clone();
+        a = b;
+        b = r;
+    }
+    a
+}
+
+/// The number of elements `len` as a number of type `N`, or an `Overflow` error if it does not
+/// fit.
+fn count<N: Num>(len: usize, settings: &Settings) -> Result<N, InterpretError<'static>> {
+    (0..len).try_fold(N::zero(), |count, _| {
+        count.arithmetic(OpVal::Add, N::one(), settings)
+    })
+}
+
+/// `n` as a number of type `N`, for small `n`.
+fn small<N: Num>(n: u32) -> N {
+    (0..n).fold(N::zero(), |sum, _| sum + N::one())
+}
+
+/// `a % m` from 0 to `m - 1`, for a positive `m`.
+fn rem_euclid<N: Num>(a: N, m: &N) -> N {
+    let r = a % m.clone();
+    if r < N::zero() {
+        r + m.clone()
+    } else {
+        r
+    }
+}
+
+/// `(a + b) % m` for `a` and `b` from 0 to `m - 1`, without overflowing.
+fn add_mod<N: Num>(a: N, b: N, m: &N) -> N {
+    let gap = m.clone() - b.clone();
+    if a >= gap {
+        a - gap
+    } else {
+        a + b
+    }
+}
+
+/// `(a - b) % m` for `a` and `b` from 0 to `m - 1`, without overflowing.
+fn sub_mod<N: Num>(a: N, b: N, m: &N) -> N {
+    if a >= b {
+        a - b
+    } else {
+        a + (m.clone() - b)
+    }
+}
+
+/// `(a * b) % m` for `a` and `b` from 0 to `m - 1`. Products that would overflow are computed
+/// by doubling and adding instead.
+fn mul_mod<N: Num>(a: N, b: N, m: &N) -> N {
+    if let Ok(product) = a
+        .clone()
+        .arithmetic(OpVal::Mul, b.clone(), &Settings::default())
+    {
+        // Types that always wrap or saturate change the product when it overflows
+        if b == N::zero() || product.clone() / b.clone() == a {
+            return product % m.clone();
+        }
+    }
+    let two = small::<N>(2);
+    let (mut a, mut b, mut result) = (a, b, N::zero());
+    while b != N::zero() {
+        if b.clone() % two.clone() != N::zero() {
+            result = add_mod(result, a.clone(), m);
+        }
+        a = add_mod(a.clone(), a, m);
+        b /= two.clone();
+    }
+    result
+}
+
+/// `base^exponent % m` for `base` from 0 to `m - 1` and a nonnegative `exponent`, by squaring
+/// and multiplying.
+fn pow_mod<N: Num>(mut base: N, mut exponent: N, m: &N) -> N {
+    let two = small::<N>(2);
+    let mut result = N::one() % m.clone();
+    while exponent != N::zero() {
+        if exponent.clone() % two.clone() != N::zero() {
+            result = mul_mod(result, base.clone(), m);
+        }
+        base = mul_mod(base.clone(), base, m);
+        exponent /= two.clone();
+    }
+    result
+}
+
+/// The inverse of `a` modulo a positive `m`, from 0 to `m - 1`, if `a` and `m` are coprime. The
+/// extended Euclidean algorithm keeps its coefficients modulo `m`, so they never overflow.
+fn mod_inverse<N: Num>(a: N, m: &N) -> Option<N> {
+    let (mut r0, mut r1) = (m.clone(), rem_euclid(a, m));
+    let (mut t0, mut t1) = (N::zero(), N::one() % m.clone());
+    while r1 != N::zero() {
+        let q = r0.clone() / r1.clone();
+        let r = r0 - q.clone() * r1.clone();
+        let t = sub_mod(t0, mul_mod(q % m.clone(), t1.clone(), m), m);
+        (r0, r1) = (r1, r);
+        (t0, t1) = (t1, t);
+    }
+    if r0 == N::one() {
+        Some(t0)
+    } else {
+        None
+    }
+}
+
+/// The primes that `is_prime` divides by and uses as Miller-Rabin bases.
+const SMALL_PRIMES: [u32; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+/// Whether `n` is prime, by the Miller-Rabin test with the first 12 primes as bases, which is
+/// exact below 3.3 * 10^24.
+fn is_prime<N: Num>(n: &N) -> bool {
+    if *n < small(2) {
+        return false;
+    }
+    for &p in SMALL_PRIMES.iter() {
+        let p = small::<N>(p);
+        if *n == p {
+            return true;
+        } else if n.clone() % p == N::zero() {
+            return false;
+        }
+    }
+    // n - 1 = d * 2^s with an odd d
+    let two = small::<N>(2);
+    let n_minus_one = n.clone() - N::one();
+    let (mut d, mut s) = (n_minus_one.clone(), 0);
+    while d.clone() % two.clone() == N::zero() {
+        d /= two.clone();
+        s += 1;
+    }
+    'bases: for &a in SMALL_PRIMES.iter() {
+        let mut x = pow_mod(small(a), d.clone(), n);
+        if x == N::one() || x == n_minus_one {
+            continue;
+        }
+        for _ in 1..s {
+            x = mul_mod(x.clone(), x, n);
+            if x == n_minus_one {
+                continue 'bases;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+/// The smallest prime factor of `n`, which is at least 2.
+fn smallest_prime_factor<N: Num>(n: N) -> N {
+    for &p in SMALL_PRIMES.iter() {
+        let p = small::<N>(p);
+        if n.clone() % p.clone() == N::zero() {
+            return p;
+        }
+    }
+    if is_prime(&n) {
+        return n;
+    }
+    let d = pollard_rho(&n);
+    let (a, b) = (
+        smallest_prime_factor(d.clone()),
+        smallest_prime_factor(n / d),
+    );
+    if a < b {
+        a
+    } else {
+        b
+    }
+}
+
+/// A nontrivial factor of a composite `n` without small factors, by Pollard's rho algorithm.
+fn pollard_rho<N: Num>(n: &N) -> N {
+    let mut c = N::one();
+    loop {
+        let f = |x: N| add_mod(mul_mod(x.clone(), x, n), c.clone(), n);
+        let (mut x, mut y, mut d) = (small::<N>(2), small::<N>(2), N::one());
+        while d == N::one() {
+            x = f(x);
+            y = f(f(y));
+            let difference = if x > y {
+                x.clone() - y.clone()
+            } else {
+                y.clone() - x.clone()
+            };
+            d = gcd(difference, n.clone());
+        }
+        if d != *n {
+            return d;
+        }
+        c += N::one();
+    }
+}
+
+/// The `n`th root of `x`. Odd roots of negative numbers are real, e.g. `root(-8, 3)` is -2.
+fn root<N: Real>(x: N, n: N) -> N {
+    let two = N::one() + N::one();
+    let exponent = N::one() / n.clone();
+    if x < N::zero() && n.is_whole() && n % two != N::zero() {
+        -(-x).pow(exponent)
+    } else {
+        x.pow(exponent)
+    }
+}
+
+/// Whether `x` is one of the poles of the gamma function: 0, -1, -2, ...
+fn is_gamma_pole<N: Num>(x: &N) -> bool {
+    *x <= N::zero() && x.is_whole()
+}
+
+/// The beta function `Γ(a)Γ(b) / Γ(a + b)`.
+fn beta<N: Real>(a: N, b: N) -> N {
+    let sum = a.clone() + b.clone();
+    if a > N::zero() && b > N::zero() {
+        (a.ln_gamma() + b.ln_gamma() - sum.ln_gamma()).exp()
+    } else if is_gamma_pole(&sum) {
+        N::zero()
+    } else {
+        a.gamma() * b.gamma() / sum.gamma()
+    }
+}
+
+/// Coefficients of the Lanczos approximation with g = 7.
+const LANCZOS: [f64; 9] = [
+    0.999_999_999_999_809_9,
+    676.520_368_121_885_1,
+    -1_259.139_216_722_402_8,
+    771.323_428_777_653_1,
+    -176.615_029_162_140_6,
+    12.507_343_278_686_905,
+    -0.138_571_095_265_720_12,
+    9.984_369_578_019_572e-6,
+    1.505_632_735_149_311_6e-7,
+];
+const LANCZOS_G: f64 = 7.0;
+
+/// Returns the Lanczos sum and `t` for `x`, where `Γ(x + 1) = sqrt(2π) t^(x + 0.5) e^-t sum`.
+fn lanczos(x: f64) -> (f64, f64) {
+    let mut sum = LANCZOS[0];
+    for (i, p) in LANCZOS.iter().enumerate().skip(1) {
+        sum += p / (x + i as f64);
+    }
+    (sum, x + LANCZOS_G + 0.5)
+}
+
+/// The gamma function, extending `(x - 1)!` to real numbers. Positive whole numbers give exact
+/// factorials as far as `f64` can hold them. Poles give NaN.
+pub(crate) fn gamma(x: f64) -> f64 {
+    if is_gamma_pole(&x) {
+        f64::NAN
+    } else if x.fract() == 0.0 && x <= 171.0 {
+        (2..x as u32).fold(1.0, |product, n| product * n as f64)
+    } else if x < 0.5 {
+        // Reflection formula
+        PI / ((PI * x).sin() * gamma(1.0 - x))
+    } else {
+        let (sum, t) = lanczos(x - 1.0);
+        // t^(x - 0.5) is split in two to keep it from overflowing before e^-t shrinks it
+        let half = t.powf((x - 0.5) / 2.0);
+        (2.0 * PI).sqrt() * half * (half * (-t).exp()) * sum
+    }
+}
+
+/// The natural logarithm of the absolute value of the gamma function. Unlike `gamma`, it does
+/// not overflow for large `x`. Poles give infinity.
+pub(crate) fn ln_gamma(x: f64) -> f64 {
+    if is_gamma_pole(&x) {
+        f64::INFINITY
+    } else if x < 0.5 {
+        (PI / (PI * x).sin().abs()).ln() - ln_gamma(1.0 - x)
+    } else {
+        let (sum, t) = lanczos(x - 1.0);
+        0.5 * (2.0 * PI).ln() + (x - 0.5) * t.ln() - t + sum.ln()
+    }
+}
+
+/// The digamma function, the derivative of `ln_gamma`. Poles give NaN.
+pub(crate) fn digamma(mut x: f64) -> f64 {
+    if is_gamma_pole(&x) {
+        return f64::NAN;
+    } else if x < 0.5 {
+        // Reflection formula
+        return digamma(1.0 - x) - PI / (PI * x).tan();
+    }
+    // Recurrence up to where the asymptotic series is accurate
+    let mut result = 0.0;
+    while x < 10.0 {
+        result -= 1.0 / x;
+        x += 1.0;
+    }
+    let x2 = 1.0 / (x * x);
+    let series = x2
+        * (1.0 / 12.0 - x2 * (1.0 / 120.0 - x2 * (1.0 / 252.0 - x2 * (1.0 / 240.0 - x2 / 132.0))));
+    result + x.ln() - 0.5 / x - series
+}
+
+macro_rules! impl_default_real {
+    ($($ftype:ty),*) => {
+        $(
+            /// An interpreter with the functions of `install_num_stdlib`,
+            /// `install_list_stdlib`, `install_matrix_stdlib` and `install_real_stdlib`.
+            impl Default for Interpreter<$ftype> {
+                fn default() -> Self {
+                    let mut interpreter = Interpreter::new();
+                    install_num_stdlib(&mut interpreter);
+                    install_list_stdlib(&mut interpreter);
+                    install_matrix_stdlib(&mut interpreter);
+                    install_real_stdlib(&mut interpreter);
+                    interpreter
+                }
+            }
+        )*
+    };
+}
+impl_default_real!(f32, f64, Dual);
+
+macro_rules! impl_default_integer {
+    ($($itype:ty),*) => {
+        $(
+            /// An interpreter with the functions of `install_num_stdlib`,
+            /// `install_list_stdlib`, `install_matrix_stdlib` and `install_integer_stdlib`.
+            impl Default for Interpreter<$itype> {
+                fn default() -> Self {
+                    let mut interpreter = Interpreter::new();
+                    install_num_stdlib(&mut interpreter);
+                    install_list_stdlib(&mut interpreter);
+                    install_matrix_stdlib(&mut interpreter);
+                    install_integer_stdlib(&mut interpreter);
+                    interpreter
+                }
+            }
+        )*
+    };
+}
+impl_default_integer!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+impl_default_integer!(
+    Wrapping<i8>,
+    Wrapping<i16>,
+    Wrapping<i32>,
+    Wrapping<i64>,
+    Wrapping<i128>,
+    Wrapping<isize>,
+    Wrapping<u8>,
+    Wrapping<u16>,
+    Wrapping<u32>,
+    Wrapping<u64>,
+    Wrapping<u128>,
+    Wrapping<usize>
+);
+impl_default_integer!(
+    Saturating<i8>,
+    Saturating<i16>,
+    Saturating<i32>,
+    Saturating<i64>,
+    Saturating<i128>,
+    Saturating<isize>,
+    Saturating<u8>,
+    Saturating<u16>,
+    Saturating<u32>,
+    Saturating<u64>,
+    Saturating<u128>,
+    Saturating<usize>
+);
+#[cfg(feature = "num")]
+impl_default_integer!(BigInt);
+
+/// An interpreter with the functions of `install_list_stdlib`, `install_matrix_stdlib` and
+/// `install_complex_stdlib`.
+impl Default for Interpreter<Complex> {
+    fn default() -> Self {
+        let mut interpreter = Interpreter::new();
+        install_list_stdlib(&mut interpreter);
+        install_matrix_stdlib(&mut interpreter);
+        install_complex_stdlib(&mut interpreter);
+        interpreter
+    }
+}
+
+/// An interpreter with the functions of `install_list_stdlib`, `install_matrix_stdlib`,
+/// `install_real_stdlib` and `install_interval_stdlib`.
+impl Default for Interpreter<Interval> {
+    fn default() -> Self {
+        let mut interpreter = Interpreter::new();
+        install_list_stdlib(&mut interpreter);
+        install_matrix_stdlib(&mut interpreter);
+        install_real_stdlib(&mut interpreter);
+        install_interval_stdlib(&mut interpreter);
+        interpreter
+    }
+}
+
+/// An interpreter with the functions of `install_num_stdlib`, `install_list_stdlib`,
+/// `install_matrix_stdlib`, `install_real_stdlib` and `install_uncertain_stdlib`.
+impl Default for Interpreter<Uncertain> {
+    fn default() -> Self {
+        let mut interpreter = Interpreter::new();
+        install_num_stdlib(&mut interpreter);
+        install_list_stdlib(&mut interpreter);
+        install_matrix_stdlib(&mut interpreter);
+        install_real_stdlib(&mut interpreter);
+        install_uncertain_stdlib(&mut interpreter);
+        interpreter
+    }
+}
+
+/// An interpreter with the functions of `install_num_stdlib`, `install_list_stdlib`,
+/// `install_matrix_stdlib`, `install_real_stdlib` and `install_quantity_stdlib`.
+impl Default for Interpreter<Quantity> {
+    fn default() -> Self {
+        let mut interpreter = Interpreter::new();
+        install_num_stdlib(&mut interpreter);
+        install_list_stdlib(&mut interpreter);
+        install_matrix_stdlib(&mut interpreter);
+        install_real_stdlib(&mut interpreter);
+        install_quantity_stdlib(&mut interpreter);
+        interpreter
+    }
+}
+
+/// An interpreter with the functions of `install_num_stdlib`, `install_list_stdlib`,
+/// `install_matrix_stdlib` and `install_modular_stdlib`.
+impl<const M: u64> Default for Interpreter<Modular<M>> {
+    fn default() -> Self {
+        let mut interpreter = Interpreter::new();
+        install_num_stdlib(&mut interpreter);
+        install_list_stdlib(&mut interpreter);
+        install_matrix_stdlib(&mut interpreter);
+        install_modular_stdlib(&mut interpreter);
+        interpreter
+    }
+}
+
+/// An interpreter with the functions of `install_num_stdlib`, `install_list_stdlib`,
+/// `install_matrix_stdlib` and `install_rational_stdlib`.
+#[cfg(feature = "num")]
+impl Default for Interpreter<Rational> {
+    fn default() -> Self {
+        let mut interpreter = Interpreter::new();
+        install_num_stdlib(&mut interpreter);
+        install_list_stdlib(&mut interpreter);
+        install_matrix_stdlib(&mut interpreter);
+        install_rational_stdlib(&mut interpreter);
+        interpreter
+    }
+}
+
+/// An interpreter with the functions of `install_num_stdlib`, `install_list_stdlib`,
+/// `install_matrix_stdlib` and `install_decimal_stdlib`.
+#[cfg(feature = "num")]
+impl Default for Interpreter<Decimal> {
+    fn default() -> Self {
+        let mut interpreter = Interpreter::new();
+        install_num_stdlib(&mut interpreter);
+        install_list_stdlib(&mut interpreter);
+        install_matrix_stdlib(&mut interpreter);
+        install_decimal_stdlib(&mut interpreter);
+        interpreter
+    }
+}
@@ -1,7 +1,14 @@
 use crate::Num;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::fmt::{self, Display, Formatter};
+use std::iter::{Enumerate, Peekable};
 use std::ops::Range;
+use std::str::CharIndices;
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum OpVal {
     Add,
     Sub,
@@ -11,61 +18,170 @@ pub enum OpVal {
     Pow,
     Eq,
     Exclaim,
+    /// The `°` or `deg` after a number
+    Degree,
+    /// `±`, or `+-` for number types with uncertainty
+    PlusMinus,
+    /// The conversion `x to unit`. It is tokenized as an identifier, which the parser reads as
+    /// `to` after a value.
+    To,
 }
 use OpVal::*;
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum SymbolVal {
     LP,
     RP,
+    /// `[`, which opens a list or an index
+    LB,
+    /// `]`
+    RB,
     Comma,
     Pipe,
 }
 use SymbolVal::*;
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum TokenValue<'input, N: Num> {
     Num(N),
-    Id(&'input str),
+    /// Borrowed from the input, but owned when deserialized from data with escapes.
+    Id(#[cfg_attr(feature = "serde", serde(borrow))] Cow<'input, str>),
     Op(OpVal),
     Symbol(SymbolVal),
+    /// Stands in for an invalid number when tokenizing with `tokenize_recovering`.
+    Error,
 }
 use TokenValue::*;
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Token<'input, N: Num> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub value: TokenValue<'input, N>,
     pub span: Range<usize>,
 }
 
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum TokenizeErrorCode<'input> {
-    InvalidNumber(&'input str),
+    InvalidNumber(#[cfg_attr(feature = "serde", serde(borrow))] Cow<'input, str>),
     UnrecognizedChar(char),
 }
 use TokenizeErrorCode::*;
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TokenizeError<'input> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub code: TokenizeErrorCode<'input>,
     pub span: Range<usize>,
 }
 
+impl OpVal {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Add => "+",
+            Sub => "-",
+            Mul => "*",
+            Div => "/",
+            Mod => "%",
+            Pow => "^",
+            Eq => "=",
+            Exclaim => "!",
+            Degree => "°",
+            PlusMinus => "±",
+            To => "to",
+        }
+    }
+}
+
+impl Display for OpVal {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(self.symbol())
+    }
+}
+
+impl Display for SymbolVal {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(match self {
+            LP => "(",
+            RP => ")",
+            LB => "[",
+            RB => "]",
+            Comma => ",",
+            Pipe => "|",
+        })
+    }
+}
+
+impl<'input, N: Num + Display> Display for TokenValue<'input, N> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Num(n) => write!(f, "{}", n),
+            Id(id) => write!(f, "{}", id),
+            Op(op) => write!(f, "{}", op),
+            Symbol(sym) => write!(f, "{}", sym),
+            Error => write!(f, "?"),
+        }
+    }
+}
+
+impl<'input> Display for TokenizeErrorCode<'input> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            InvalidNumber(s) => write!(f, "Invalid number {:?}.", s),
+            UnrecognizedChar(c) => write!(f, "Unrecognized character {:?}.", c),
+        }
+    }
+}
+
+impl<'input> Display for TokenizeError<'input> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.code)
+    }
+}
+
+impl<'input> std::error::Error for TokenizeError<'input> {}
+
 #[derive(Debug, Clone, Default)]
 pub struct TokenizeOptions {
     identifiers_contain_numbers: bool,
 }
 
-pub fn tokenize<N: Num>(input: &str) -> Result<Vec<Token<N>>, TokenizeError> {
+pub fn tokenize<N: Num>(input: &str) -> Result<Vec<Token<'_, N>>, TokenizeError<'_>> {
     tokenize_with_options(input, TokenizeOptions::default())
 }
 
 pub fn tokenize_with_options<N: Num>(
     input: &str,
     options: TokenizeOptions,
-) -> Result<Vec<Token<N>>, TokenizeError> {
+) -> Result<Vec<Token<'_, N>>, TokenizeError<'_>> {
+    tokenize_impl(input, options, None)
+}
+
+/// Tokenizes all of `input` instead of stopping at the first error, and returns every error
+/// found. Unrecognized characters are skipped, and each invalid number becomes a
+/// `TokenValue::Error` token, which `parse_recovering` turns into an `Expr::Error`.
+pub fn tokenize_recovering<N: Num>(
+    input: &str,
+    options: TokenizeOptions,
+) -> (Vec<Token<'_, N>>, Vec<TokenizeError<'_>>) {
+    let mut errors = Vec::new();
+    // Errors are collected rather than returned
+    let tokens = tokenize_impl(input, options, Some(&mut errors)).unwrap_or_default();
+    (tokens, errors)
+}
+
+fn tokenize_impl<'input, N: Num>(
+    input: &'input str,
+    options: TokenizeOptions,
+    mut errors: Option<&mut Vec<TokenizeError<'input>>>,
+) -> Result<Vec<Token<'input, N>>, TokenizeError<'input>> {
     let mut tokens = Vec::with_capacity(16);
-    let mut chars = input.chars().enumerate().peekable();
+    // Spans count characters, but the input is sliced by bytes
+    let mut chars = input.char_indices().enumerate().peekable();
 
     macro_rules! push_token {
         ($token:expr, $pos:expr, $len:expr) => {
@@ -79,8 +195,25 @@ pub fn tokenize_with_options<N: Num>(
         };
     }
 
-    while let Some((cpos, c)) = chars.next() {
+    // Returns the error, unless errors are being collected
+    macro_rules! report {
+        ($err:expr) => {
+            match errors.as_mut() {
+                Some(errors) => errors.push($err),
+                None => return Err($err),
+            }
+        };
+    }
+
+    // "+-" is "±" for number types with uncertainty, and "+ -" otherwise
+    let plus_minus = N::one().plus_minus(N::zero()).is_some();
+
+    while let Some((cpos, (bpos, c))) = chars.next() {
         match c {
+            '+' if plus_minus && matches!(chars.peek(), Some((_, (_, '-')))) => {
+                chars.next();
+                push_token!(Op(PlusMinus), cpos, 2);
+            }
             '+' => push_token!(Op(Add), cpos, 1),
             '-' => push_token!(Op(Sub), cpos, 1),
             '*' => push_token!(Op(Mul), cpos, 1),
@@ -89,9 +222,13 @@ pub fn tokenize_with_options<N: Num>(
             '^' => push_token!(Op(Pow), cpos, 1),
             '=' => push_token!(Op(Eq), cpos, 1),
             '!' => push_token!(Op(Exclaim), cpos, 1),
+            '°' => push_token!(Op(Degree), cpos, 1),
+            '±' => push_token!(Op(PlusMinus), cpos, 1),
 
             '(' => push_token!(Symbol(LP), cpos, 1),
             ')' => push_token!(Symbol(RP), cpos, 1),
+            '[' => push_token!(Symbol(LB), cpos, 1),
+            ']' => push_token!(Symbol(RB), cpos, 1),
             ',' => push_token!(Symbol(Comma), cpos, 1),
             '|' => push_token!(Symbol(Pipe), cpos, 1),
 
@@ -99,7 +236,7 @@ pub fn tokenize_with_options<N: Num>(
                 if c.is_ascii_digit() || c == '.' {
                     let start = cpos;
                     let mut end = start + 1;
-                    while let Some((_, nc)) = chars.peek() {
+                    while let Some((_, (_, nc))) = chars.peek() {
                         if nc.is_ascii_digit() || *nc == '.' {
                             chars.next(); // Consume nc
                             end += 1;
@@ -107,18 +244,53 @@ pub fn tokenize_with_options<N: Num>(
                             break;
                         }
                     }
-                    if let Ok(num) = input[start..end].parse::<N>() {
+                    let mut bend = byte_pos(&mut chars, input);
+                    let mut num = N::parse_literal(&input[bpos..bend]);
+                    // "i" or "j" directly after a number makes it imaginary, for number types
+                    // that parse it
+                    if starts_with_word(&input[bend..], "i")
+                        || starts_with_word(&input[bend..], "j")
+                    {
+                        if let Some(imaginary) = N::parse_literal(&input[bpos..bend + 1]) {
+                            chars.next();
+                            end += 1;
+                            bend += 1;
+                            num = Some(imaginary);
+                        }
+                    }
+                    // A unit after a number belongs to it, e.g. "5 km" or "3 m^2", for number
+                    // types that parse it
+                    if let Some(len) = unit_len(&input[bend..]) {
+                        if let Some(quantity) = N::parse_literal(&input[bpos..bend + len]) {
+                            for _ in input[bend..bend + len].chars() {
+                                chars.next();
+                                end += 1;
+                            }
+                            bend += len;
+                            num = Some(quantity);
+                        }
+                    }
+                    if let Some(num) = num {
                         push_token!(Num(num), start, end - start);
                     } else {
-                        return Err(TokenizeError {
-                            code: InvalidNumber(&input[start..end]),
+                        report!(TokenizeError {
+                            code: InvalidNumber(Cow::Borrowed(&input[bpos..bend])),
                             span: start..end,
                         });
+                        push_token!(Error, start, end - start);
+                    }
+
+                    // "deg" directly after a number is the same as "°"
+                    if starts_with_word(&input[bend..], "deg") {
+                        for _ in 0..3 {
+                            chars.next();
+                        }
+                        push_token!(Op(Degree), end, 3);
                     }
                 } else if c == '_' || c.is_alphabetic() {
                     let start = cpos;
                     let mut end = start + 1;
-                    while let Some((_, nc)) = chars.peek() {
+                    while let Some((_, (_, nc))) = chars.peek() {
                         // If it is any of _ A-z (or digits if option)
                         if *nc == '_'
                             || nc.is_alphanumeric()
@@ -130,9 +302,13 @@ pub fn tokenize_with_options<N: Num>(
                             break;
                         }
                     }
-                    push_token!(Id(&input[start..end]), start, end - start);
+                    push_token!(
+                        Id(Cow::Borrowed(&input[bpos..byte_pos(&mut chars, input)])),
+                        start,
+                        end - start
+                    );
                 } else if !c.is_whitespace() {
-                    return Err(TokenizeError {
+                    report!(TokenizeError {
                         code: UnrecognizedChar(c),
                         span: cpos..cpos + 1,
                     });
@@ -142,3 +318,40 @@ pub fn tokenize_with_options<N: Num>(
     }
     Ok(tokens)
 }
+
+/// The byte offset of the next character, or the length of the input at its end.
+fn byte_pos(chars: &mut Peekable<Enumerate<CharIndices>>, input: &str) -> usize {
+    chars.peek().map_or(input.len(), |(_, (bpos, _))| *bpos)
+}
+
+/// Whether `rest` starts with the whole word `word`, rather than a longer identifier.
+fn starts_with_word(rest: &str, word: &str) -> bool {
+    rest.starts_with(word)
+        && !rest[word.len()..]
+            .chars()
+            .next()
+            .is_some_and(|c| c == '_' || c.is_alphanumeric())
+}
+
+/// The length in bytes of a unit at the start of `rest`: spaces, a word and an optional whole
+/// power, as in " km" or "m^-2".
+fn unit_len(rest: &str) -> Option<usize> {
+    let word_start = rest.len() - rest.trim_start_matches([' ', '\t']).len();
+    let word_len = rest[word_start..]
+        .find(|c: char| c != '_' && !c.is_alphanumeric())
+        .unwrap_or(rest.len() - word_start);
+    if word_len == 0 || !rest[word_start..].starts_with(char::is_alphabetic) {
+        return None;
+    }
+    let mut len = word_start + word_len;
+    if let Some(power) = rest[len..].strip_prefix('^') {
+        let sign = usize::from(power.starts_with('-'));
+        let digits = power[sign..]
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(power.len() - sign);
+        if digits > 0 {
+            len += 1 + sign + digits;
+        }
+    }
+    Some(len)
+}
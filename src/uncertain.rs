@@ -0,0 +1,251 @@
+use crate::{real_via_derivatives, InterpretError, Num, Real, Settings};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter};
+use std::num::ParseFloatError;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Rem, Sub, SubAssign};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
+
+/// The identifier of the next independent source of uncertainty.
+static NEXT_SOURCE: AtomicU64 = AtomicU64::new(0);
+
+/// A measured value with a standard uncertainty, e.g. `9.81 ± 0.02`.
+///
+/// Uncertainty propagates to first order: every result is linear in the errors of its inputs.
+/// Each `±` creates an independent source of error, and results remember how much they depend
+/// on each source, so reusing a value is correlated with itself. `x - x` is exactly 0, and
+/// `x * x` has twice the relative uncertainty of `x`, unlike the product of two independent
+/// measurements.
+///
+/// Numbers compare by value, and are equal if their values and uncertainties are. A number
+/// `is_whole` if it is exact and whole. Literals are exact, and numbers with uncertainty
+/// display as `value ± uncertainty`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Uncertain {
+    value: f64,
+    /// The derivative of the value with respect to each source of error, scaled by the
+    /// source's standard uncertainty. Zeros are left out.
+    sources: BTreeMap<u64, f64>,
+}
+
+impl Uncertain {
+    /// A measurement of `value` with the standard uncertainty `uncertainty`, independent of
+    /// every other measurement.
+    pub fn new(value: f64, uncertainty: f64) -> Uncertain {
+        let mut sources = BTreeMap::new();
+        if uncertainty != 0.0 {
+            let source = NEXT_SOURCE.fetch_add(1, AtomicOrdering::Relaxed);
+            sources.insert(source, uncertainty.abs());
+        }
+        Uncertain { value, sources }
+    }
+
+    /// The exact number `value`.
+    pub fn exact(value: f64) -> Uncertain {
+        Uncertain {
+            value,
+            sources: BTreeMap::new(),
+        }
+    }
+
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    /// The standard uncertainty.
+    pub fn uncertainty(&self) -> f64 {
+        self.sources.values().fold(0.0, |sum, c| c.hypot(sum))
+    }
+
+    pub fn is_exact(&self) -> bool {
+        self.sources.is_empty()
+    }
+
+    /// The number `value`, which depends on `self` with the derivative `d_self` and on `other`
+    /// with the derivative `d_other`.
+    fn combine(&self, d_self: f64, other: &Uncertain, d_other: f64, value: f64) -> Uncertain {
+        let mut sources = self.scaled_sources(d_self);
+        for (source, c) in &other.sources {
+            *sources.entry(*source).or_insert(0.0) += d_other * c;
+        }
+        sources.retain(|_, c| *c != 0.0);
+        Uncertain { value, sources }
+    }
+
+    /// Applies the function `f` with the derivative `derivative`.
+    fn apply(&self, f: fn(f64) -> f64, derivative: fn(f64) -> f64) -> Uncertain {
+        let mut sources = self.scaled_sources(derivative(self.value));
+        sources.retain(|_, c| *c != 0.0);
+        Uncertain {
+            value: f(self.value),
+            sources,
+        }
+    }
+
+    fn scaled_sources(&self, derivative: f64) -> BTreeMap<u64, f64> {
+        self.sources
+            .iter()
+            .map(|(source, c)| (*source, derivative * c))
+            .collect()
+    }
+}
+
+impl From<f64> for Uncertain {
+    fn from(value: f64) -> Self {
+        Uncertain::exact(value)
+    }
+}
+
+impl Num for Uncertain {
+    fn zero() -> Self {
+        Uncertain::exact(0.0)
+    }
+    fn one() -> Self {
+        Uncertain::exact(1.0)
+    }
+    fn is_whole(&self) -> bool {
+        self.is_exact() && self.value.fract() == 0.0
+    }
+    fn parse_literal(s: &str) -> Option<Self> {
+        s.parse().ok()
+    }
+    fn pow(self, other: Self) -> Self {
+        let value = self.value.powf(other.value);
+        // The derivatives are only needed, and only defined, where there is uncertainty
+        let d_self = if self.is_exact() {
+            0.0
+        } else {
+            other.value * self.value.powf(other.value - 1.0)
+        };
+        let d_other = if other.is_exact() {
+            0.0
+        } else {
+            value * self.value.ln()
+        };
+        self.combine(d_self, &other, d_other, value)
+    }
+    fn is_finite(&self) -> bool {
+        self.value.is_finite() && self.uncertainty().is_finite()
+    }
+    fn is_atomic(&self) -> bool {
+        self.is_exact()
+    }
+    /// Adds an independent uncertainty of the value of `uncertainty`, whose own uncertainty is
+    /// ignored.
+    fn plus_minus(self, uncertainty: Self) -> Option<Self> {
+        Some(self + Uncertain::new(0.0, uncertainty.value))
+    }
+
+    fn negate(self, _: &Settings) -> Result<Self, InterpretError<'static>> {
+        Ok(-self)
+    }
+
+    fn degrees(self, settings: &Settings) -> Result<Self, InterpretError<'static>> {
+        Ok(settings.angle_mode.from_degrees(self))
+    }
+}
+
+impl Real for Uncertain {
+    fn from_f64(x: f64) -> Self {
+        Uncertain::exact(x)
+    }
+    fn to_f64(&self) -> f64 {
+        self.value
+    }
+
+    real_via_derivatives!();
+}
+
+impl PartialEq for Uncertain {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value && self.uncertainty() == other.uncertainty()
+    }
+}
+
+impl PartialOrd for Uncertain {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.value.partial_cmp(&other.value)
+    }
+}
+
+impl FromStr for Uncertain {
+    type Err = ParseFloatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Uncertain::exact(s.parse()?))
+    }
+}
+
+impl Display for Uncertain {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if self.is_exact() {
+            write!(f, "{}", self.value)
+        } else {
+            write!(f, "{} ± {}", self.value, self.uncertainty())
+        }
+    }
+}
+
+impl Add for Uncertain {
+    type Output = Uncertain;
+    fn add(self, other: Uncertain) -> Uncertain {
+        self.combine(1.0, &other, 1.0, self.value + other.value)
+    }
+}
+
+impl Sub for Uncertain {
+    type Output = Uncertain;
+    fn sub(self, other: Uncertain) -> Uncertain {
+        self.combine(1.0, &other, -1.0, self.value - other.value)
+    }
+}
+
+impl Mul for Uncertain {
+    type Output = Uncertain;
+    fn mul(self, other: Uncertain) -> Uncertain {
+        self.combine(other.value, &other, self.value, self.value * other.value)
+    }
+}
+
+impl Div for Uncertain {
+    type Output = Uncertain;
+    fn div(self, other: Uncertain) -> Uncertain {
+        let quotient = self.value / other.value;
+        self.combine(1.0 / other.value, &other, -quotient / other.value, quotient)
+    }
+}
+
+/// The remainder of truncating division, like `%` of `f64`, which changes with the divisor in
+/// steps of the truncated quotient.
+impl Rem for Uncertain {
+    type Output = Uncertain;
+    fn rem(self, other: Uncertain) -> Uncertain {
+        let quotient = (self.value / other.value).trunc();
+        self.combine(1.0, &other, -quotient, self.value % other.value)
+    }
+}
+
+impl Neg for Uncertain {
+    type Output = Uncertain;
+    fn neg(self) -> Uncertain {
+        self.apply(|x| -x, |_| -1.0)
+    }
+}
+
+macro_rules! impl_uncertain_assign_op {
+    ($assign_op:ident, $assign_method:ident, $op:tt) => {
+        impl $assign_op for Uncertain {
+            fn $assign_method(&mut self, other: Uncertain) {
+                *self = self.clone() $op other;
+            }
+        }
+    };
+}
+impl_uncertain_assign_op!(AddAssign, add_assign, +);
+impl_uncertain_assign_op!(SubAssign, sub_assign, -);
+impl_uncertain_assign_op!(MulAssign, mul_assign, *);
+impl_uncertain_assign_op!(DivAssign, div_assign, /);
@@ -0,0 +1,277 @@
+use crate::{InterpretError, Matrix, Num, OpVal, Settings};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display, Formatter};
+
+/// What an expression evaluates to: a number, a list of numbers like `[1, 2, 3]`, or a matrix
+/// like `[[1, 2], [3, 4]]`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Value<N> {
+    Num(N),
+    List(Vec<N>),
+    Matrix(Matrix<N>),
+}
+
+impl<N: Num> Value<N> {
+    /// Describes the kind and size of the value for error messages, e.g. "a list of 3" or
+    /// "a 2×3 matrix".
+    pub fn describe(&self) -> String {
+        match self {
+            Value::Num(_) => String::from("a number"),
+            Value::List(list) => format!("a list of {}", list.len()),
+            Value::Matrix(matrix) => matrix.describe(),
+        }
+    }
+
+    /// Returns the number, or a `TypeMismatch` error for `op` if the value is not one.
+    pub fn into_num(self, op: &str) -> Result<N, InterpretError<'_>> {
+        match self {
+            Value::Num(n) => Ok(n),
+            value => Err(InterpretError::TypeMismatch(
+                op,
+                String::from("a number"),
+                value.describe(),
+                0..0,
+            )),
+        }
+    }
+
+    /// Returns the list, or a `TypeMismatch` error for `op` if the value is not one.
+    pub fn into_list(self, op: &str) -> Result<Vec<N>, InterpretError<'_>> {
+        match self {
+            Value::List(list) => Ok(list),
+            value => Err(InterpretError::TypeMismatch(
+                op,
+                String::from("a list"),
+                value.describe(),
+                0..0,
+            )),
+        }
+    }
+
+    /// Returns the matrix, or a `TypeMismatch` error for `op` if the value is not one.
+    pub fn into_matrix(self, op: &str) -> Result<Matrix<N>, InterpretError<'_>> {
+        match self {
+            Value::Matrix(matrix) => Ok(matrix),
+            value => Err(InterpretError::TypeMismatch(
+                op,
+                String::from("a matrix"),
+                value.describe(),
+                0..0,
+            )),
+        }
+    }
+
+    /// Applies `f` to the value, or to each element of a list or matrix.
+    pub fn map<'a>(
+        self,
+        mut f: impl FnMut(N) -> Result<N, InterpretError<'a>>,
+    ) -> Result<Value<N>, InterpretError<'a>> {
+        match self {
+            Value::Num(n) => f(n).map(Value::Num),
+            Value::List(list) => list
+                .into_iter()
+                .map(f)
+                .collect::<Result<_, _>>()
+                .map(Value::List),
+            Value::Matrix(matrix) => {
+                let elements = matrix
+                    .elements()
+                    .iter()
+                    .cloned()
+                    .map(f)
+                    .collect::<Result<_, _>>()?;
+                Ok(Value::Matrix(matrix.with_elements(elements)))
+            }
+        }
+    }
+
+    /// The element at `index` of a list, which is what `list[index]` evaluates to. Indexing a
+    /// matrix gives the row at `index` as a list.
+    pub fn index(self, index: Value<N>) -> Result<Value<N>, InterpretError<'static>> {
+        let items: Vec<Value<N>> = match self {
+            Value::Matrix(matrix) => (0..matrix.rows())
+                .map(|row| Value::List(matrix.row(row).to_vec()))
+                .collect(),
+            value => value.into_list("[]")?.into_iter().map(Value::Num).collect(),
+        };
+        let index = index.into_num("[]")?;
+        let len = items.len();
+        // Counting up to the index needs no conversion from N, and rejects fractions
+        let mut i = N::zero();
+        for item in items {
+            if i == index {
+                return Ok(item);
+            }
+            match i.arithmetic(OpVal::Add, N::one(), &Settings::default()) {
+                Ok(next) => i = next,
+                // The index fits in the type, so it has been passed
+                Err(_) => break,
+            }
+        }
+        Err(InterpretError::IndexOutOfRange(len, 0..0))
+    }
+}
+
+/// Builds the list `[items...]`, whose items must be numbers, or the matrix with the rows
+/// `items`, which must be non-empty lists of the same length.
+pub(crate) fn make_list<N: Num>(items: Vec<Value<N>>) -> Result<Value<N>, InterpretError<'static>> {
+    match items.first() {
+        Some(Value::List(row)) if row.is_empty() => Err(InterpretError::TypeMismatch(
+            "[]",
+            String::from("a number or a non-empty list"),
+            String::from("a list of 0"),
+            0..0,
+        )),
+        Some(first @ Value::List(_)) => {
+            if let Some(other) = items
+                .iter()
+                .find(|item| item.describe() != first.describe())
+            {
+                return Err(match other {
+                    Value::List(_) => InterpretError::ShapeMismatch(
+                        "[]",
+                        first.describe(),
+                        other.describe(),
+                        0..0,
+                    ),
+                    _ => InterpretError::TypeMismatch(
+                        "[]",
+                        String::from("a list"),
+                        other.describe(),
+                        0..0,
+                    ),
+                });
+            }
+            let rows = items
+                .into_iter()
+                .map(|item| item.into_list("[]"))
+                .collect::<Result<_, _>>()?;
+            // The rows have the same length, and there is at least one
+            Ok(Value::Matrix(Matrix::from_rows(rows).unwrap()))
+        }
+        _ => items
+            .into_iter()
+            .map(|item| item.into_num("[]"))
+            .collect::<Result<_, _>>()
+            .map(Value::List),
+    }
+}
+
+/// The product `lhs * rhs` if either is a matrix and the other is a matrix or list. A list on
+/// the right is a column and a list on the left is a row, and the product of either is a list.
+/// Other values are multiplied element by element, so this returns `None` for them.
+pub(crate) fn matrix_product<N: Num>(
+    lhs: &Value<N>,
+    rhs: &Value<N>,
+    settings: &Settings,
+) -> Option<Result<Value<N>, InterpretError<'static>>> {
+    let (a, b) = match (lhs, rhs) {
+        (Value::Matrix(a), Value::Matrix(b)) => {
+            return Some(a.mul(b, settings).map(Value::Matrix));
+        }
+        (Value::Matrix(a), Value::List(list)) => (a.clone(), Matrix::column(list.clone())),
+        (Value::List(list), Value::Matrix(b)) => {
+            (Matrix::column(list.clone()).transpose(), b.clone())
+        }
+        _ => return None,
+    };
+    if a.cols() != b.rows() {
+        return Some(Err(InterpretError::ShapeMismatch(
+            OpVal::Mul.symbol(),
+            lhs.describe(),
+            rhs.describe(),
+            0..0,
+        )));
+    }
+    Some(
+        a.mul(&b, settings)
+            .map(|product| Value::List(product.into_elements())),
+    )
+}
+
+/// Applies `f` to the numbers in `args`, broadcasting over lists and matrices: if any argument
+/// is one, `f` is applied to each position of its elements, and numbers are repeated for every
+/// position. Lists and matrices of different shapes are a `ShapeMismatch` error for `op`.
+pub(crate) fn broadcast<'a, N: Num>(
+    op: &'a str,
+    args: &[Value<N>],
+    mut f: impl FnMut(&[N]) -> Result<N, InterpretError<'a>>,
+) -> Result<Value<N>, InterpretError<'a>> {
+    let mut shaped = args.iter().filter(|arg| !matches!(arg, Value::Num(_)));
+    let first = match shaped.next() {
+        Some(first) => first,
+        None => {
+            let nums: Vec<N> = args.iter().map(|arg| element(arg, 0)).collect();
+            return f(&nums).map(Value::Num);
+        }
+    };
+    if let Some(other) = shaped.find(|other| other.describe() != first.describe()) {
+        return Err(InterpretError::ShapeMismatch(
+            op,
+            first.describe(),
+            other.describe(),
+            0..0,
+        ));
+    }
+    let len = match first {
+        Value::List(list) => list.len(),
+        Value::Matrix(matrix) => matrix.elements().len(),
+        Value::Num(_) => unreachable!(),
+    };
+    let mut nums = Vec::with_capacity(args.len());
+    let mut result = Vec::with_capacity(len);
+    for i in 0..len {
+        nums.clear();
+        nums.extend(args.iter().map(|arg| element(arg, i)));
+        result.push(f(&nums)?);
+    }
+    Ok(match first {
+        Value::Matrix(matrix) => Value::Matrix(matrix.with_elements(result)),
+        _ => Value::List(result),
+    })
+}
+
+/// The number at position `i` of a broadcast value.
+fn element<N: Num>(value: &Value<N>, i: usize) -> N {
+    match value {
+        Value::Num(n) => n.clone(),
+        Value::List(list) => list[i].clone(),
+        Value::Matrix(matrix) => matrix.elements()[i].clone(),
+    }
+}
+
+/// The numbers in `args`, with the elements of lists and matrices passed as numbers of their
+/// own.
+pub(crate) fn spread<N: Num>(args: &[Value<N>]) -> Vec<N> {
+    let mut nums = Vec::with_capacity(args.len());
+    for arg in args {
+        match arg {
+            Value::Num(n) => nums.push(n.clone()),
+            Value::List(list) => nums.extend(list.iter().cloned()),
+            Value::Matrix(matrix) => nums.extend(matrix.elements().iter().cloned()),
+        }
+    }
+    nums
+}
+
+/// Lists are written like `[1, 2, 3]`, and matrices like `[[1, 2], [3, 4]]`.
+impl<N: Display> Display for Value<N> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Value::Num(n) => write!(f, "{}", n),
+            Value::List(list) => {
+                write!(f, "[")?;
+                for (i, n) in list.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", n)?;
+                }
+                write!(f, "]")
+            }
+            Value::Matrix(matrix) => write!(f, "{}", matrix),
+        }
+    }
+}
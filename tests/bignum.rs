@@ -0,0 +1,111 @@
+#![cfg(feature = "num")]
+
+use rsc::{BigInt, Error, Interpreter, OwnedInterpretError, Rational};
+
+fn eval_int(input: &str) -> String {
+    Interpreter::<BigInt>::default()
+        .eval_str(input)
+        .unwrap_or_else(|e| panic!("{}: {}", input, e))
+        .to_string()
+}
+
+fn eval_rational(input: &str) -> String {
+    Interpreter::<Rational>::default()
+        .eval_str(input)
+        .unwrap_or_else(|e| panic!("{}: {}", input, e))
+        .to_string()
+}
+
+#[test]
+fn big_integers_are_exact() {
+    assert_eq!(
+        eval_int("100!"),
+        "93326215443944152681699238856266700490715968264381621468592963895217599993229915608941463976156518286253697920827223758251185210916864000000000000000000000000"
+    );
+    assert_eq!(
+        eval_int("2^512"),
+        "13407807929942597099574024998205846127479365820592393377723561443721764030073546976801874298166903427690031858186486050853753882811946569946433649006084096"
+    );
+    assert_eq!(
+        eval_int("123456789012345678901234567890 + 1"),
+        "123456789012345678901234567891"
+    );
+    assert_eq!(eval_int("-7 / 2"), "-3");
+    assert_eq!(eval_int("gcd(2^100, 6^50)"), eval_int("2^50"));
+    assert_eq!(eval_int("shl(1, 100)"), eval_int("2^100"));
+    assert_eq!(eval_int("(-1)^-3"), "-1");
+    assert_eq!(eval_int("isprime(2^127 - 1)"), "1");
+    assert_eq!(eval_int("factor((2^61 - 1) * (2^13 - 1))"), "8191");
+    assert_eq!(eval_int("powmod(3, 2^100, 1000000007)"), "870513414");
+}
+
+#[test]
+fn big_integer_errors() {
+    let mut interpreter = Interpreter::<BigInt>::default();
+    let cases = [
+        ("1 / 0", OwnedInterpretError::DivisionByZero),
+        ("1 % 0", OwnedInterpretError::DivisionByZero),
+        ("2^-1", OwnedInterpretError::NegativeExponent),
+        ("2^(2^40)", OwnedInterpretError::Overflow),
+        (
+            "(-1)!",
+            OwnedInterpretError::OutOfDomain(String::from("factorial")),
+        ),
+    ];
+    for (input, expected) in cases.iter() {
+        assert_eq!(
+            interpreter.eval_str(input),
+            Err(Error::Interpret(expected.clone())),
+            "{}",
+            input
+        );
+    }
+    assert!(interpreter.eval_str("0.5").is_err());
+}
+
+#[test]
+fn rationals_are_exact() {
+    assert_eq!(eval_rational("1/3 + 1/6"), "1/2");
+    assert_eq!(eval_rational("0.1 + 0.2"), "3/10");
+    assert_eq!(eval_rational(".5 * 5."), "5/2");
+    assert_eq!(eval_rational("(2/3)^-2"), "9/4");
+    assert_eq!(eval_rational("2^100"), eval_int("2^100"));
+    assert_eq!(
+        eval_rational("0.000000000000000000000000000001 * 10^30"),
+        "1"
+    );
+    assert_eq!(eval_rational("floor(-7/2)"), "-4");
+    assert_eq!(eval_rational("round(5/2)"), "3");
+    assert_eq!(eval_rational("numer(6/4) + denom(6/4)"), "5");
+    assert_eq!(eval_rational("abs(-1/2)"), "1/2");
+    assert_eq!(eval_rational("25!"), "15511210043330985984000000");
+}
+
+#[test]
+fn rational_errors() {
+    let mut interpreter = Interpreter::<Rational>::default();
+    let cases = [
+        ("1 / 0", OwnedInterpretError::DivisionByZero),
+        ("0^-1", OwnedInterpretError::DivisionByZero),
+        (
+            "2^(1/2)",
+            OwnedInterpretError::OutOfDomain(String::from("^")),
+        ),
+        ("2^(2^40)", OwnedInterpretError::Overflow),
+        (
+            "0.5!",
+            OwnedInterpretError::OutOfDomain(String::from("factorial")),
+        ),
+    ];
+    for (input, expected) in cases.iter() {
+        assert_eq!(
+            interpreter.eval_str(input),
+            Err(Error::Interpret(expected.clone())),
+            "{}",
+            input
+        );
+    }
+    assert!(".".parse::<Rational>().is_err());
+    assert!("1.2.3".parse::<Rational>().is_err());
+    assert!("1.-5".parse::<Rational>().is_err());
+}
@@ -0,0 +1,136 @@
+use rsc::{parse, tokenize, Complex, Error, Interpreter, Num, OwnedInterpretError, TokenValue};
+
+fn eval(input: &str) -> Complex {
+    Interpreter::<Complex>::default()
+        .eval_str(input)
+        .unwrap_or_else(|e| panic!("{}: {}", input, e))
+}
+
+fn assert_approx(input: &str, re: f64, im: f64) {
+    let result = eval(input);
+    assert!(
+        (result - Complex::new(re, im)).abs() <= 1e-12,
+        "{} = {}, expected {}",
+        input,
+        result,
+        Complex::new(re, im)
+    );
+}
+
+#[test]
+fn literals() {
+    assert_eq!(eval("4i"), Complex::new(0.0, 4.0));
+    assert_eq!(eval("2.5j"), Complex::new(0.0, 2.5));
+    assert_eq!(eval("3+4i"), Complex::new(3.0, 4.0));
+    assert_eq!(eval("3-i"), Complex::new(3.0, -1.0));
+    assert_eq!(eval("2^2i"), eval("2^(2i)"));
+    assert_eq!(eval("i = 2"), Complex::from(2.0));
+}
+
+#[test]
+fn arithmetic() {
+    assert_eq!(eval("(3+4i)(1-2j)"), Complex::new(11.0, -2.0));
+    assert_eq!(eval("(11-2i)/(1-2i)"), Complex::new(3.0, 4.0));
+    assert_eq!(eval("i^2"), Complex::from(-1.0));
+    assert_eq!(eval("i^-1"), Complex::new(0.0, -1.0));
+    assert_eq!(eval("7 % 3"), Complex::from(1.0));
+    assert_approx("(-8)^(1/3)", 1.0, 3f64.sqrt());
+}
+
+#[test]
+fn functions() {
+    assert_eq!(eval("sqrt(-1)"), Complex::I);
+    assert_eq!(eval("sqrt(-4)"), Complex::new(0.0, 2.0));
+    assert_eq!(eval("re(3+4i)"), Complex::from(3.0));
+    assert_eq!(eval("im(3+4i)"), Complex::from(4.0));
+    assert_eq!(eval("abs(3+4i)"), Complex::from(5.0));
+    assert_eq!(eval("conj(3+4i)"), Complex::new(3.0, -4.0));
+    assert_eq!(eval("arg(-1)"), Complex::from(std::f64::consts::PI));
+    assert_approx("polar(2, pi/2)", 0.0, 2.0);
+    assert_approx("exp(i*pi)", -1.0, 0.0);
+    assert_approx("ln(-1)", 0.0, std::f64::consts::PI);
+    assert_approx("sin(asin(2))", 2.0, 0.0);
+    assert_approx("cos(i)", 1f64.cosh(), 0.0);
+    assert_approx("atan(2i)", std::f64::consts::FRAC_PI_2, 3f64.ln() / 2.0);
+    assert_approx("log(-100)", 2.0, std::f64::consts::PI / 10f64.ln());
+    assert_eq!(eval("5!"), Complex::from(120.0));
+    assert_eq!(eval("max(1, 3, 2)"), Complex::from(3.0));
+}
+
+#[test]
+fn angles_are_in_the_angle_mode() {
+    let mut interpreter = Interpreter::<Complex>::default();
+    interpreter.settings.angle_mode = rsc::AngleMode::Degrees;
+    let mut eval = |input| interpreter.eval_str(input).unwrap();
+    assert_eq!(eval("arg(i)"), Complex::from(90.0));
+    assert!((eval("polar(2, 90)") - Complex::new(0.0, 2.0)).abs() < 1e-12);
+    assert!((eval("sin(30°)") - Complex::from(0.5)).abs() < 1e-12);
+    assert_eq!(eval("30°"), Complex::from(30.0));
+    assert_eq!(eval("deg(30)"), Complex::from(30.0));
+}
+
+#[test]
+fn ordering_needs_real_numbers() {
+    assert!(Complex::from(1.0) < Complex::from(2.0));
+    assert!(Complex::I.partial_cmp(&Complex::from(2.0)).is_none());
+    assert!(!Complex::I.is_whole());
+    assert!(Complex::from(-3.0).is_whole());
+
+    let mut interpreter = Interpreter::<Complex>::default();
+    for (input, id) in [
+        ("min(1, i)", "min"),
+        ("i!", "factorial"),
+        ("gamma(0)", "gamma"),
+    ]
+    .iter()
+    {
+        assert_eq!(
+            interpreter.eval_str(input),
+            Err(Error::Interpret(OwnedInterpretError::OutOfDomain(
+                String::from(*id)
+            ))),
+            "{}",
+            input
+        );
+    }
+}
+
+#[test]
+fn display() {
+    let cases = [
+        (Complex::new(3.0, 4.0), "3+4i"),
+        (Complex::new(3.0, -1.0), "3-i"),
+        (Complex::new(0.0, -2.5), "-2.5i"),
+        (Complex::new(0.0, 1.0), "i"),
+        (Complex::from(-2.0), "-2"),
+    ];
+    for (z, expected) in cases.iter() {
+        assert_eq!(z.to_string(), *expected);
+    }
+}
+
+#[test]
+fn partial_evaluation_parenthesizes_complex_numbers() {
+    let mut interpreter = Interpreter::<Complex>::default();
+    let input = "y * (3 + 4i) + (1 - i)^y";
+    let tokens = tokenize(input).unwrap();
+    let expr = parse(&tokens).unwrap();
+    let printed = interpreter.partial_eval(&expr).unwrap().to_string();
+    assert_eq!(printed, "y*(3+4i) + (1-i)^y");
+
+    // The printed residual means the same as the expression
+    interpreter.eval_str("y = 2").unwrap();
+    assert_eq!(interpreter.eval_str(&printed), Ok(Complex::new(6.0, 6.0)));
+    assert_eq!(interpreter.eval_str(input), Ok(Complex::new(6.0, 6.0)));
+}
+
+#[test]
+fn imaginary_suffix_is_only_read_by_complex_numbers() {
+    let mut interpreter = Interpreter::<f64>::default();
+    assert!(interpreter.eval_str("4i").is_err());
+    // "i" is only a suffix if it ends the word
+    let tokens = rsc::tokenize::<Complex>("4in").unwrap();
+    assert_eq!(tokens[0].value, TokenValue::Num(Complex::from(4.0)));
+    assert_eq!(tokens[1].value, TokenValue::Id("in".into()));
+    assert_eq!(tokens[1].span, 1..3);
+}
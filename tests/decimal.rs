@@ -0,0 +1,142 @@
+#![cfg(feature = "num")]
+
+use rsc::{Decimal, Error, Interpreter, OwnedInterpretError, RoundingMode};
+
+fn eval(input: &str) -> String {
+    Interpreter::<Decimal>::default()
+        .eval_str(input)
+        .unwrap_or_else(|e| panic!("{}: {}", input, e))
+        .to_string()
+}
+
+#[test]
+fn decimal_fractions_are_exact() {
+    assert_eq!(eval("0.1 + 0.2"), "0.3");
+    assert_eq!(eval("0.1 + 0.2 - 0.3"), "0");
+    assert_eq!(eval("1.10 * 3"), "3.3");
+    assert_eq!(eval("-7.5 % 2"), "-1.5");
+    assert_eq!(eval("2^-3"), "0.125");
+    assert_eq!(
+        eval("0.1234567890123456789012345678901234567890"),
+        "0.1234567890123456789012345678901234567890".trim_end_matches('0')
+    );
+}
+
+#[test]
+fn comparison_is_exact() {
+    let a: Decimal = "1.5".parse().unwrap();
+    let b: Decimal = "1.50000000000000000000000000000000000001".parse().unwrap();
+    assert!(a < b);
+    assert_eq!(a, "1.500".parse().unwrap());
+}
+
+#[test]
+fn results_are_rounded_to_the_precision() {
+    assert_eq!(eval("1/3"), "0.3333333333333333333333333333");
+    assert_eq!(eval("2/3"), "0.6666666666666666666666666667");
+    assert_eq!(eval("2^100"), "1267650600228229401496703205000");
+    assert_eq!(eval("10^30"), "1e+30");
+    assert_eq!(eval("10^-30"), "1e-30");
+
+    let mut interpreter = Interpreter::<Decimal>::default();
+    interpreter.settings.decimal.precision = 5;
+    let mut eval = |input| interpreter.eval_str(input).unwrap().to_string();
+    assert_eq!(eval("1/7"), "0.14286");
+    assert_eq!(eval("123456789 + 0"), "123460000");
+    assert_eq!(eval("1.00005 * 1"), "1");
+}
+
+#[test]
+fn rounding_modes() {
+    let cases = [
+        (RoundingMode::HalfEven, ["2", "4", "-2", "1"]),
+        (RoundingMode::HalfUp, ["3", "4", "-3", "1"]),
+        (RoundingMode::HalfDown, ["2", "3", "-2", "1"]),
+        (RoundingMode::Up, ["3", "4", "-3", "2"]),
+        (RoundingMode::Down, ["2", "3", "-2", "1"]),
+        (RoundingMode::Ceiling, ["3", "4", "-2", "2"]),
+        (RoundingMode::Floor, ["2", "3", "-3", "1"]),
+    ];
+    for (mode, expected) in cases.iter() {
+        let mut interpreter = Interpreter::<Decimal>::default();
+        interpreter.settings.decimal.precision = 1;
+        interpreter.settings.decimal.rounding = *mode;
+        for (input, expected) in ["2.5 * 1", "3.5 * 1", "(-2.5) * 1", "1.2 * 1"]
+            .iter()
+            .zip(expected.iter())
+        {
+            assert_eq!(
+                interpreter.eval_str(input).unwrap().to_string(),
+                *expected,
+                "{} in {:?}",
+                input,
+                mode
+            );
+        }
+    }
+}
+
+#[test]
+fn functions() {
+    assert_eq!(eval("sqrt(2)"), "1.414213562373095048801688724");
+    assert_eq!(eval("sqrt(16)"), "4");
+    assert_eq!(eval("sqrt(0.0001)"), "0.01");
+    assert_eq!(eval("2^0.5"), eval("sqrt(2)"));
+    assert_eq!(eval("exp(1)"), "2.718281828459045235360287471");
+    assert_eq!(eval("exp(-1)"), "0.3678794411714423215955237702");
+    assert_eq!(eval("ln(2)"), "0.6931471805599453094172321215");
+    assert_eq!(eval("ln(0.001)"), "-6.907755278982137052053974364");
+    assert_eq!(eval("log(1000)"), "3");
+    assert_eq!(eval("log(8, 2)"), "3");
+    assert_eq!(eval("1.1^50"), "117.390852879695316506666496");
+    assert_eq!(eval("20!"), "2432902008176640000");
+    assert_eq!(eval("round(2.675, 2)"), "2.68");
+    assert_eq!(eval("round(2.665, 2)"), "2.66");
+    assert_eq!(eval("round(1250, -2)"), "1200");
+    assert_eq!(eval("floor(-2.5)"), "-3");
+    assert_eq!(eval("ceil(2.1)"), "3");
+    assert_eq!(eval("trunc(-2.7)"), "-2");
+
+    let mut interpreter = Interpreter::<Decimal>::default();
+    interpreter.settings.decimal.precision = 50;
+    assert_eq!(
+        interpreter.eval_str("sqrt(2)").unwrap().to_string(),
+        "1.4142135623730950488016887242096980785696718753769"
+    );
+    assert_eq!(
+        interpreter.eval_str("exp(1)").unwrap().to_string(),
+        "2.7182818284590452353602874713526624977572470937"
+    );
+}
+
+#[test]
+fn errors() {
+    let mut interpreter = Interpreter::<Decimal>::default();
+    let cases = [
+        ("1 / 0", OwnedInterpretError::DivisionByZero),
+        ("1 % 0", OwnedInterpretError::DivisionByZero),
+        ("0^-1", OwnedInterpretError::DivisionByZero),
+        (
+            "(-8)^(1/3)",
+            OwnedInterpretError::OutOfDomain(String::from("^")),
+        ),
+        (
+            "sqrt(-1)",
+            OwnedInterpretError::OutOfDomain(String::from("sqrt")),
+        ),
+        (
+            "ln(0)",
+            OwnedInterpretError::OutOfDomain(String::from("ln")),
+        ),
+        ("exp(10^20)", OwnedInterpretError::Overflow),
+        ("2^(10^20)", OwnedInterpretError::Overflow),
+    ];
+    for (input, expected) in cases.iter() {
+        assert_eq!(
+            interpreter.eval_str(input),
+            Err(Error::Interpret(expected.clone())),
+            "{}",
+            input
+        );
+    }
+}
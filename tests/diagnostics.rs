@@ -0,0 +1,118 @@
+use rsc::{parse, tokenize, Diagnostic, Error, Interpreter};
+
+/// Renders the error of evaluating `input` without color.
+fn render(input: &str) -> String {
+    let err = Interpreter::<f64>::default().eval_str(input).unwrap_err();
+    Diagnostic::from(&err).render(input, false)
+}
+
+#[test]
+fn tokenize_error() {
+    let err = tokenize::<f64>("2 + 1.2.3 * 4").unwrap_err();
+    assert_eq!(
+        Diagnostic::from(&err).render("2 + 1.2.3 * 4", false),
+        "error: Invalid number \"1.2.3\".
+  |
+1 | 2 + 1.2.3 * 4
+  |     ^^^^^
+  = help: Numbers are written like 12, 0.5 or .5."
+    );
+    assert_eq!(
+        render("2 $ 3"),
+        "error: Unrecognized character '$'.
+  |
+1 | 2 $ 3
+  |   ^"
+    );
+}
+
+#[test]
+fn parse_error() {
+    let tokens = tokenize::<f64>("2 + 3)").unwrap();
+    let err = parse(&tokens).unwrap_err();
+    assert_eq!(
+        Diagnostic::from(&err).render("2 + 3)", false),
+        "error: Unexpected \")\".
+  |
+1 | 2 + 3)
+  |      ^"
+    );
+    // The end of the input is marked one past its last character
+    assert_eq!(
+        render("2 *"),
+        "error: Unexpected end of input.
+  |
+1 | 2 *
+  |    ^
+  = help: The expression is incomplete."
+    );
+}
+
+#[test]
+fn non_ascii_input_before_the_error() {
+    // Spans count characters, so each of "π", "µ" and "→" takes one column, not two or three
+    // bytes
+    assert_eq!(
+        render("π + µ → 1.2.3"),
+        "error: Unrecognized character '→'.
+  |
+1 | π + µ → 1.2.3
+  |       ^"
+    );
+    assert_eq!(
+        render("ππ * 1.2.3"),
+        "error: Invalid number \"1.2.3\".
+  |
+1 | ππ * 1.2.3
+  |      ^^^^^
+  = help: Numbers are written like 12, 0.5 or .5."
+    );
+    let input = "max(π, é))";
+    let tokens = tokenize::<f64>(input).unwrap();
+    let err = Error::from(parse(&tokens).unwrap_err());
+    assert_eq!(err.span(), Some(9..10));
+    assert_eq!(
+        Diagnostic::from(&err).render(input, false),
+        "error: Unexpected \")\".
+  |
+1 | max(π, é))
+  |          ^"
+    );
+}
+
+#[test]
+fn multiple_lines_and_labels() {
+    let input = "1 +\nπ * 1.2.3";
+    let diagnostic = Diagnostic::from(&tokenize::<f64>(input).unwrap_err())
+        .with_label(0..1, String::from("left operand"));
+    assert_eq!(
+        diagnostic.render(input, false),
+        "error: Invalid number \"1.2.3\".
+  |
+1 | 1 +
+  | - left operand
+2 | π * 1.2.3
+  |     ^^^^^
+  = help: Numbers are written like 12, 0.5 or .5."
+    );
+}
+
+#[test]
+fn color() {
+    let rendered = render("2 $ 3");
+    let colored = Diagnostic::from(&Interpreter::<f64>::default().eval_str("2 $ 3").unwrap_err())
+        .render("2 $ 3", true);
+    assert!(colored.contains("\x1b[1;31m^\x1b[0m"));
+    let stripped: String = colored
+        .split('\x1b')
+        .enumerate()
+        .map(|(i, part)| {
+            if i == 0 {
+                part
+            } else {
+                &part[part.find('m').unwrap() + 1..]
+            }
+        })
+        .collect();
+    assert_eq!(stripped, rendered);
+}
@@ -0,0 +1,127 @@
+use rsc::{ensure_arg_count, parse, tokenize, Dual, InterpretError, Interpreter, Real, Variant};
+
+fn interpreter() -> Interpreter<Dual> {
+    let mut interpreter = Interpreter::<Dual>::default();
+    interpreter.eval_str("x = 2").unwrap();
+    interpreter.eval_str("y = 3").unwrap();
+    interpreter
+}
+
+fn gradient(interpreter: &mut Interpreter<Dual>, input: &str, vars: &[&str]) -> Dual {
+    let tokens = tokenize(input).unwrap();
+    let expr = parse(&tokens).unwrap();
+    interpreter
+        .gradient(&expr, vars)
+        .unwrap_or_else(|e| panic!("{}: {}", input, e))
+}
+
+fn assert_gradient(input: &str, value: f64, grad: &[f64]) {
+    let result = gradient(&mut interpreter(), input, &["x", "y"]);
+    assert!(
+        (result.value - value).abs() <= 1e-12
+            && result.grad.len() == grad.len()
+            && result
+                .grad
+                .iter()
+                .zip(grad)
+                .all(|(a, b)| (a - b).abs() <= 1e-12),
+        "{} = {}, expected {} with gradient {:?}",
+        input,
+        result,
+        value,
+        grad
+    );
+}
+
+#[test]
+fn arithmetic() {
+    assert_gradient("x + y", 5.0, &[1.0, 1.0]);
+    assert_gradient("x - y", -1.0, &[1.0, -1.0]);
+    assert_gradient("x * y", 6.0, &[3.0, 2.0]);
+    assert_gradient("x / y", 2.0 / 3.0, &[1.0 / 3.0, -2.0 / 9.0]);
+    assert_gradient("x^2 * y", 12.0, &[12.0, 4.0]);
+    assert_gradient("x^y", 8.0, &[12.0, 8.0 * 2f64.ln()]);
+    assert_gradient("-x", -2.0, &[-1.0, 0.0]);
+    assert_gradient("5", 5.0, &[0.0, 0.0]);
+}
+
+#[test]
+fn functions() {
+    assert_gradient(
+        "sin(x) * y",
+        2f64.sin() * 3.0,
+        &[2f64.cos() * 3.0, 2f64.sin()],
+    );
+    assert_gradient(
+        "exp(x * y)",
+        6f64.exp(),
+        &[3.0 * 6f64.exp(), 2.0 * 6f64.exp()],
+    );
+    assert_gradient(
+        "ln(x) + sqrt(y)",
+        2f64.ln() + 3f64.sqrt(),
+        &[0.5, 0.5 / 3f64.sqrt()],
+    );
+    assert_gradient(
+        "hypot(x, y)",
+        13f64.sqrt(),
+        &[2.0 / 13f64.sqrt(), 3.0 / 13f64.sqrt()],
+    );
+    assert_gradient("atan2(y, x)", 1.5f64.atan(), &[-3.0 / 13.0, 2.0 / 13.0]);
+    // The derivative of ln(gamma(x)) is digamma(x), 1 - γ at 2
+    assert_gradient("lgamma(x)", 0.0, &[1.0 - 0.577_215_664_901_532_9, 0.0]);
+    assert_gradient("abs(-x * y)", 6.0, &[3.0, 2.0]);
+}
+
+#[test]
+fn registered_functions() {
+    let mut interpreter = interpreter();
+    interpreter.set_var(
+        String::from("f"),
+        Variant::Function(|id, args, _| {
+            ensure_arg_count(2, 2, args.len(), id)?;
+            Ok(args[0].clone().sin() * args[1].clone().exp())
+        }),
+    );
+    let result = gradient(&mut interpreter, "f(x, y)", &["y", "x"]);
+    let f = 2f64.sin() * 3f64.exp();
+    assert!((result.value - f).abs() < 1e-12);
+    assert!((result.grad[0] - f).abs() < 1e-12);
+    assert!((result.grad[1] - 2f64.cos() * 3f64.exp()).abs() < 1e-12);
+}
+
+#[test]
+fn seeding() {
+    let mut interpreter = interpreter();
+    // Variables not asked for are constants
+    let result = gradient(&mut interpreter, "x * y", &["x"]);
+    assert_eq!(result.grad, vec![3.0]);
+    // The variables are restored
+    assert_eq!(interpreter.eval_str("x").unwrap(), Dual::constant(2.0));
+
+    let tokens = tokenize("z + 1").unwrap();
+    let expr = parse(&tokens).unwrap();
+    assert!(matches!(
+        interpreter.gradient(&expr, &["z"]),
+        Err(InterpretError::VarDoesNotExist("z"))
+    ));
+    assert!(matches!(
+        interpreter.gradient(&expr, &["sin"]),
+        Err(InterpretError::FunctionNameUsedLikeVar("sin"))
+    ));
+
+    // A variable cannot be seeded twice
+    let tokens = tokenize("x * y").unwrap();
+    let expr = parse(&tokens).unwrap();
+    assert!(matches!(
+        interpreter.gradient(&expr, &["x", "y", "x"]),
+        Err(InterpretError::TypeMismatch("gradient", ..))
+    ));
+    assert_eq!(interpreter.eval_str("x").unwrap(), Dual::constant(2.0));
+}
+
+#[test]
+fn display() {
+    assert_eq!(Dual::constant(2.5).to_string(), "2.5");
+    assert_eq!(Dual::variable(2.0, 1, 2).to_string(), "2 + [0, 1]ε");
+}
@@ -0,0 +1,87 @@
+use rsc::{
+    parse, tokenize, Error, InterpretError, Interpreter, OwnedInterpretError, OwnedParseError,
+    OwnedParseErrorCode, OwnedTokenizeError, OwnedTokenizeErrorCode,
+};
+use std::error::Error as _;
+
+fn eval(input: &str) -> Error {
+    Interpreter::<f64>::default().eval_str(input).unwrap_err()
+}
+
+#[test]
+fn converts_each_stage_to_owned() {
+    let err = tokenize::<f64>("2 + 1.2.3").unwrap_err();
+    assert_eq!(
+        Error::from(err),
+        Error::Tokenize(OwnedTokenizeError {
+            code: OwnedTokenizeErrorCode::InvalidNumber(String::from("1.2.3")),
+            span: 4..9,
+        })
+    );
+
+    let tokens = tokenize::<f64>("2 + 3 x)").unwrap();
+    let err = parse(&tokens).unwrap_err();
+    assert_eq!(
+        Error::from(err),
+        Error::Parse(OwnedParseError {
+            code: OwnedParseErrorCode::UnexpectedToken(String::from("x")),
+            span: 6..7,
+        })
+    );
+
+    assert_eq!(
+        Error::from(InterpretError::VarDoesNotExist("y")),
+        Error::Interpret(OwnedInterpretError::VarDoesNotExist(String::from("y")))
+    );
+    assert_eq!(
+        eval("unknown + 1"),
+        Error::Interpret(OwnedInterpretError::VarDoesNotExist(String::from(
+            "unknown"
+        )))
+    );
+}
+
+#[test]
+fn display_matches_the_stage_error() {
+    for input in [
+        "2 + 1.2.3",
+        "2 $ 3",
+        "(1 + 2",
+        "2 + 3 x)",
+        "sqrt(1, 2)",
+        "1 / x",
+    ] {
+        let err = eval(input);
+        let expected = match tokenize::<f64>(input) {
+            Err(err) => err.to_string(),
+            Ok(tokens) => match parse(&tokens) {
+                Err(err) => err.to_string(),
+                Ok(expr) => Interpreter::<f64>::default()
+                    .eval(&expr)
+                    .unwrap_err()
+                    .to_string(),
+            },
+        };
+        assert_eq!(err.to_string(), expected, "{}", input);
+    }
+    assert_eq!(eval("2 + 1.2.3").to_string(), "Invalid number \"1.2.3\".");
+    assert_eq!(eval("2 $ 3").to_string(), "Unrecognized character '$'.");
+    assert_eq!(eval("2 + 3 x)").to_string(), "Unexpected \"x\".");
+    assert_eq!(
+        eval("sqrt(1, 2)").to_string(),
+        "Function \"sqrt\" received more than the maximum 1 argument."
+    );
+}
+
+#[test]
+fn error_chains_print_the_message_once() {
+    let err = eval("2 + 1.2.3");
+    assert!(err.source().is_none());
+    let mut chain = vec![err.to_string()];
+    let mut source = err.source();
+    while let Some(err) = source {
+        chain.push(err.to_string());
+        source = err.source();
+    }
+    assert_eq!(chain, ["Invalid number \"1.2.3\"."]);
+}
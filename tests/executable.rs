@@ -0,0 +1,66 @@
+#![cfg(feature = "executable")]
+
+use std::process::{Command, Output};
+
+fn rsc(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_rsc"))
+        .arg("--no-color")
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+/// Runs the executable on an expression and returns what it prints.
+fn calculate(args: &[&str]) -> String {
+    let output = rsc(args);
+    assert!(output.status.success(), "{:?}: {:?}", args, output);
+    String::from_utf8(output.stdout)
+        .unwrap()
+        .trim_end()
+        .to_owned()
+}
+
+/// Runs the executable on an expression that fails and returns its error message.
+fn fail(args: &[&str]) -> String {
+    let output = rsc(args);
+    assert!(!output.status.success(), "{:?}: {:?}", args, output);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    stderr.lines().next().unwrap_or_default().to_owned()
+}
+
+#[test]
+fn number_types() {
+    assert_eq!(calculate(&["1/4"]), "0.25");
+    assert_eq!(calculate(&["--type", "i64", "7/2"]), "3");
+    assert_eq!(calculate(&["--type", "rational", "1/3 + 1/6"]), "1/2");
+    assert_eq!(calculate(&["--type", "wrapping-u8", "250 + 10"]), "4");
+    assert_eq!(
+        calculate(&["--type", "wrapping-i64", "2^63"]),
+        "-9223372036854775808"
+    );
+    assert_eq!(calculate(&["--type", "saturating-i8", "100 + 100"]), "127");
+    assert_eq!(calculate(&["--type", "saturating-u64", "0 - 1"]), "0");
+    assert!(fail(&["--type", "wrapping", "1"]).contains("Unknown number type \"wrapping\""));
+}
+
+#[test]
+fn arithmetic_modes() {
+    assert_eq!(
+        fail(&["--type", "u8", "200 + 56"]),
+        "error: The result is too large for the number type."
+    );
+    assert_eq!(
+        calculate(&["--type", "u8", "--mode", "checked", "200 + 55"]),
+        "255"
+    );
+    assert_eq!(
+        calculate(&["--type", "u8", "--mode", "wrapping", "200 + 56"]),
+        "0"
+    );
+    assert_eq!(
+        calculate(&["--type", "u8", "--mode", "saturating", "200 + 56"]),
+        "255"
+    );
+    assert_eq!(calculate(&["-i", "--mode", "wrapping", "2^64"]), "0");
+    assert!(fail(&["--mode", "clamping", "1"]).contains("Unknown arithmetic mode \"clamping\""));
+}
@@ -0,0 +1,111 @@
+use rsc::{best_fraction, Fraction, Notation, NumberFormat, ParseNotationError};
+use std::f64::consts::PI;
+
+fn fraction(x: f64, max_denominator: u64, tolerance: f64) -> String {
+    best_fraction(x, max_denominator, tolerance)
+        .unwrap_or_else(|| panic!("no fraction for {}", x))
+        .to_string()
+}
+
+#[test]
+fn best_fractions() {
+    assert_eq!(fraction(0.333333, 1000, 1e-6), "1/3");
+    assert_eq!(fraction(0.125, 1000, 0.0), "1/8");
+    assert_eq!(fraction(0.1 + 0.2, 1000, 1e-9), "3/10");
+    assert_eq!(fraction(-2.5, 10, 0.0), "-5/2");
+    assert_eq!(fraction(3.0, 1, 0.0), "3");
+    assert_eq!(fraction(PI, 100, 0.01), "22/7");
+    assert_eq!(fraction(PI, 1000, 1e-6), "355/113");
+    assert_eq!(fraction(PI, 100_000, 1e-9), "103993/33102");
+    // A semiconvergent between the convergents 3/1 and 22/7
+    assert_eq!(fraction(PI, 1000, 0.05), "19/6");
+    assert_eq!(
+        best_fraction(0.125, 1000, 0.0),
+        Some(Fraction {
+            numer: 1,
+            denom: 8,
+            exact: true
+        })
+    );
+    assert!(!best_fraction(0.333333, 1000, 1e-6).unwrap().exact);
+}
+
+#[test]
+fn no_fraction_is_close_enough() {
+    assert_eq!(best_fraction(PI, 1000, 1e-9), None);
+    assert_eq!(best_fraction(0.5, 1, 0.1), None);
+    assert_eq!(best_fraction(f64::NAN, 1000, 1.0), None);
+    assert_eq!(best_fraction(f64::INFINITY, 1000, 1.0), None);
+    assert_eq!(best_fraction(1e30, 10, 1.0), None);
+}
+
+fn format(notation: &str, x: &str) -> String {
+    let format = NumberFormat {
+        notation: notation.parse().unwrap(),
+        ..NumberFormat::default()
+    };
+    format.format(&x)
+}
+
+#[test]
+fn notations() {
+    assert_eq!(format("plain", "1234.5"), "1234.5");
+    assert_eq!(format("fix2", "0.30000000000000004"), "0.30");
+    assert_eq!(format("fix2", "2.675"), "2.68");
+    assert_eq!(format("fix0", "-2.5"), "-3");
+    assert_eq!(format("fix2", "-0.0004"), "0.00");
+    assert_eq!(format("sig3", "1234567"), "1230000");
+    assert_eq!(format("sig3", "1.5"), "1.50");
+    assert_eq!(format("sig2", "0.000123456"), "0.00012");
+    assert_eq!(format("sig3", "999.96"), "1000");
+    assert_eq!(format("sci", "1234.5"), "1.2345e3");
+    assert_eq!(format("sci2", "0.00012345"), "1.23e-4");
+    assert_eq!(format("sci1", "9.96"), "1.0e1");
+    assert_eq!(format("sci", "0"), "0e0");
+    assert_eq!(format("eng", "1234.5"), "1.2345k");
+    assert_eq!(format("eng1", "0.0000125"), "12.5µ");
+    assert_eq!(format("eng", "0.5"), "500m");
+    assert_eq!(format("eng2", "999999"), "1.00M");
+    assert_eq!(format("eng", "1e40"), "10e39");
+    // Every digit of a big integer counts
+    assert_eq!(
+        format("sig20", "123456789012345678901234567890"),
+        "123456789012345678900000000000"
+    );
+    // Numbers that aren't plain decimals are left alone
+    assert_eq!(format("fix2", "inf"), "inf");
+    assert_eq!(format("fix2", "1+2i"), "1+2i");
+}
+
+#[test]
+fn grouping_and_decimal_marks() {
+    let mut format = NumberFormat {
+        group_separator: Some(','),
+        ..NumberFormat::default()
+    };
+    assert_eq!(format.format(&1234567.5), "1,234,567.5");
+    assert_eq!(format.format(&-123456), "-123,456");
+    assert_eq!(format.format(&123), "123");
+    format.group_separator = Some('.');
+    format.decimal_mark = ',';
+    format.notation = Notation::Fixed(2);
+    assert_eq!(format.format(&1234567.5), "1.234.567,50");
+    format.notation = Notation::Engineering(None);
+    assert_eq!(format.format(&1234567.5), "1,2345675M");
+}
+
+#[test]
+fn notation_names() {
+    for name in [
+        "plain", "fix0", "fix4", "sig6", "sci", "sci3", "eng", "eng2",
+    ] {
+        assert_eq!(name.parse::<Notation>().unwrap().to_string(), name);
+    }
+    assert_eq!("sig3".parse(), Ok(Notation::Significant(3)));
+    for name in ["", "sig", "sig0", "fix", "plain2", "exp", "sci-1"] {
+        assert_eq!(
+            name.parse::<Notation>(),
+            Err(ParseNotationError(name.to_owned()))
+        );
+    }
+}
@@ -0,0 +1,231 @@
+use rsc::{
+    ArithmeticMode, Error, InterpretError, Interpreter, Num, OpVal, OwnedInterpretError, Settings,
+};
+use std::num::{Saturating, Wrapping};
+
+fn error(err: OwnedInterpretError) -> Error {
+    Error::Interpret(err)
+}
+
+#[test]
+fn unsigned_integers() {
+    let mut interpreter = Interpreter::<u8>::default();
+    assert_eq!(interpreter.eval_str("200 + 55"), Ok(255));
+    assert_eq!(interpreter.eval_str("7 / 2"), Ok(3));
+    assert_eq!(interpreter.eval_str("2^7"), Ok(128));
+    assert_eq!(interpreter.eval_str("-0"), Ok(0));
+    assert_eq!(interpreter.eval_str("gcd(12, 18)"), Ok(6));
+    assert_eq!(interpreter.eval_str("isprime(251)"), Ok(1));
+    for input in ["200 + 56", "3 - 4", "-1", "2^8", "6!"] {
+        assert_eq!(
+            interpreter.eval_str(input),
+            Err(error(OwnedInterpretError::Overflow)),
+            "{}",
+            input
+        );
+    }
+    assert_eq!(
+        interpreter.eval_str("1 / 0"),
+        Err(error(OwnedInterpretError::DivisionByZero))
+    );
+
+    interpreter.settings.arithmetic_mode = ArithmeticMode::Wrapping;
+    assert_eq!(interpreter.eval_str("factorial(10)"), Ok(0));
+    assert_eq!(interpreter.eval_str("factorial(12)"), Ok(0));
+    assert_eq!(interpreter.eval_str("-1"), Ok(255));
+    assert_eq!(interpreter.eval_str("3 - 4"), Ok(255));
+    assert_eq!(interpreter.eval_str("2^9"), Ok(0));
+    interpreter.settings.arithmetic_mode = ArithmeticMode::Saturating;
+    assert_eq!(interpreter.eval_str("-1"), Ok(0));
+    assert_eq!(interpreter.eval_str("3 - 4"), Ok(0));
+    assert_eq!(interpreter.eval_str("2^9"), Ok(255));
+
+    let mut interpreter = Interpreter::<u64>::default();
+    assert_eq!(interpreter.eval_str("18446744073709551615"), Ok(u64::MAX));
+    assert_eq!(interpreter.eval_str("1^(2^40)"), Ok(1));
+    assert_eq!(interpreter.eval_str("isprime(18446744073709551557)"), Ok(1));
+}
+
+#[test]
+fn signed_integers_in_every_mode() {
+    let mut interpreter = Interpreter::<i8>::default();
+    interpreter.eval_str("low = 0 - 127 - 1").unwrap();
+    for (mode, results) in [
+        (
+            ArithmeticMode::Checked,
+            [None, None, None, None, None, Some(0)],
+        ),
+        (
+            ArithmeticMode::Wrapping,
+            [
+                Some(-128),
+                Some(127),
+                Some(-56),
+                Some(-128),
+                Some(-128),
+                Some(0),
+            ],
+        ),
+        (
+            ArithmeticMode::Saturating,
+            [
+                Some(127),
+                Some(-128),
+                Some(127),
+                Some(127),
+                Some(127),
+                Some(0),
+            ],
+        ),
+    ] {
+        interpreter.settings.arithmetic_mode = mode;
+        let inputs = [
+            "127 + 1", "low - 1", "100 * 2", "low / -1", "-low", "low % -1",
+        ];
+        for (input, result) in inputs.iter().zip(results) {
+            assert_eq!(
+                interpreter.eval_str(input),
+                result.ok_or(error(OwnedInterpretError::Overflow)),
+                "{} in {:?}",
+                input,
+                mode
+            );
+        }
+        // Division by zero is an error whatever the mode
+        for input in ["1 / 0", "low / 0", "1 % 0", "low % (1 - 1)"] {
+            assert_eq!(
+                interpreter.eval_str(input),
+                Err(error(OwnedInterpretError::DivisionByZero)),
+                "{} in {:?}",
+                input,
+                mode
+            );
+        }
+    }
+
+    for mode in [
+        ArithmeticMode::Checked,
+        ArithmeticMode::Wrapping,
+        ArithmeticMode::Saturating,
+    ] {
+        let settings = Settings {
+            arithmetic_mode: mode,
+            ..Settings::default()
+        };
+        assert!(matches!(
+            i64::MIN.arithmetic(OpVal::Div, 0, &settings),
+            Err(InterpretError::DivisionByZero)
+        ));
+        assert!(matches!(
+            0u64.arithmetic(OpVal::Mod, 0, &settings),
+            Err(InterpretError::DivisionByZero)
+        ));
+        let quotient = i64::MIN.arithmetic(OpVal::Div, -1, &settings).ok();
+        let expected = match mode {
+            ArithmeticMode::Checked => None,
+            ArithmeticMode::Wrapping => Some(i64::MIN),
+            ArithmeticMode::Saturating => Some(i64::MAX),
+        };
+        assert_eq!(quotient, expected, "{:?}", mode);
+    }
+}
+
+#[test]
+fn wrapping_integers_always_wrap() {
+    let mut interpreter = Interpreter::<Wrapping<u8>>::default();
+    assert_eq!(interpreter.eval_str("250 + 10"), Ok(Wrapping(4)));
+    assert_eq!(interpreter.eval_str("-1"), Ok(Wrapping(255)));
+    assert_eq!(interpreter.eval_str("16 * 17"), Ok(Wrapping(16)));
+    assert_eq!(interpreter.eval_str("3^5"), Ok(Wrapping(243)));
+    assert_eq!(interpreter.eval_str("3^6"), Ok(Wrapping(217)));
+    assert_eq!(interpreter.eval_str("not(0)"), Ok(Wrapping(255)));
+    // 10! and 12! are multiples of 256, though the products before them repeat
+    assert_eq!(interpreter.eval_str("factorial(10)"), Ok(Wrapping(0)));
+    assert_eq!(interpreter.eval_str("factorial(12)"), Ok(Wrapping(0)));
+    assert_eq!(interpreter.eval_str("factorial(9)"), Ok(Wrapping(128)));
+    assert_eq!(interpreter.eval_str("factorial(2^7)"), Ok(Wrapping(0)));
+    // The settings don't change the type's arithmetic
+    interpreter.settings.arithmetic_mode = ArithmeticMode::Checked;
+    assert_eq!(interpreter.eval_str("255 + 1"), Ok(Wrapping(0)));
+    assert_eq!(
+        interpreter.eval_str("1 % 0"),
+        Err(error(OwnedInterpretError::DivisionByZero))
+    );
+
+    let mut interpreter = Interpreter::<Wrapping<i32>>::default();
+    assert_eq!(
+        interpreter.eval_str("2147483647 + 1"),
+        Ok(Wrapping(i32::MIN))
+    );
+    assert_eq!(interpreter.eval_str("(-1)^-3"), Ok(Wrapping(-1)));
+    assert_eq!(
+        interpreter.eval_str("2^-1"),
+        Err(error(OwnedInterpretError::NegativeExponent))
+    );
+    // Products that wrap don't spoil modular arithmetic
+    assert_eq!(
+        interpreter.eval_str("(0 - 2147483647 - 1) / -1"),
+        Ok(Wrapping(i32::MIN))
+    );
+    assert_eq!(
+        interpreter.eval_str("5 / 0"),
+        Err(error(OwnedInterpretError::DivisionByZero))
+    );
+    assert_eq!(
+        interpreter.eval_str("powmod(3, 2147483628, 2147483629)"),
+        Ok(Wrapping(1))
+    );
+    assert_eq!(
+        interpreter.eval_str("factor(2147483629 * 1)"),
+        Ok(Wrapping(2147483629))
+    );
+}
+
+#[test]
+fn saturating_integers_always_saturate() {
+    let mut interpreter = Interpreter::<Saturating<i8>>::default();
+    assert_eq!(interpreter.eval_str("100 + 100"), Ok(Saturating(127)));
+    assert_eq!(interpreter.eval_str("0 - 100 - 100"), Ok(Saturating(-128)));
+    assert_eq!(interpreter.eval_str("-(0 - 127 - 1)"), Ok(Saturating(127)));
+    assert_eq!(interpreter.eval_str("2^(2^40)"), Ok(Saturating(127)));
+    assert_eq!(
+        interpreter.eval_str("(-2)^(2^40 + 1)"),
+        Ok(Saturating(-128))
+    );
+    assert_eq!(interpreter.eval_str("6!"), Ok(Saturating(127)));
+    assert_eq!(interpreter.eval_str("factorial(2^40)"), Ok(Saturating(127)));
+    assert_eq!(
+        interpreter.eval_str("(0 - 127 - 1) / -1"),
+        Ok(Saturating(127))
+    );
+    assert_eq!(
+        interpreter.eval_str("5 % 0"),
+        Err(error(OwnedInterpretError::DivisionByZero))
+    );
+
+    let mut interpreter = Interpreter::<Saturating<u16>>::default();
+    assert_eq!(interpreter.eval_str("3 - 5"), Ok(Saturating(0)));
+    assert_eq!(interpreter.eval_str("-7"), Ok(Saturating(0)));
+    assert_eq!(interpreter.eval_str("300 * 300"), Ok(Saturating(u16::MAX)));
+}
+
+#[test]
+fn plain_operations() {
+    let settings = Settings::default();
+    assert!(matches!(
+        5u32.negate(&settings),
+        Err(InterpretError::Overflow)
+    ));
+    assert_eq!(0u32.negate(&settings).ok(), Some(0));
+    assert_eq!(
+        Wrapping(5u32).negate(&settings).ok(),
+        Some(Wrapping(u32::MAX - 4))
+    );
+    assert_eq!(Num::pow(2u8, 9), 0);
+    assert_eq!(Num::pow(Saturating(2u8), Saturating(9)), Saturating(255));
+    assert_eq!(
+        <Wrapping<u8> as Num>::parse_literal("255"),
+        Some(Wrapping(255))
+    );
+    assert_eq!(<u8 as Num>::parse_literal("256"), None);
+}
@@ -0,0 +1,190 @@
+use rsc::{parse, tokenize, Error, Interpreter, Interval, OwnedInterpretError, Real};
+
+fn eval(input: &str) -> Interval {
+    Interpreter::<Interval>::default()
+        .eval_str(input)
+        .unwrap_or_else(|e| panic!("{}: {}", input, e))
+}
+
+/// Asserts that `input` encloses `x` in an interval no wider than `width`.
+fn assert_encloses(input: &str, x: f64, width: f64) {
+    let result = eval(input);
+    assert!(
+        result.contains(x) && result.width() <= width,
+        "{} = {}, expected a tight interval around {}",
+        input,
+        result,
+        x
+    );
+}
+
+#[test]
+fn exact_results_are_points() {
+    assert_eq!(eval("2 + 3"), Interval::point(5.0));
+    assert_eq!(eval("1.5 * 4"), Interval::point(6.0));
+    assert_eq!(eval("1 / 4"), Interval::point(0.25));
+    assert_eq!(eval("sqrt(16)"), Interval::point(4.0));
+    assert_eq!(eval("2^10"), Interval::point(1024.0));
+    assert_eq!(eval("factorial(5)"), Interval::point(120.0));
+    assert_eq!(eval("sin(0)"), Interval::point(0.0));
+}
+
+#[test]
+fn rounding_is_outward() {
+    // 0.1 cannot be represented, so it becomes the interval around it
+    let tenth = eval("0.1");
+    assert!(tenth.contains(0.1) && tenth.lo < tenth.hi);
+    let sum = eval("0.1 + 0.2");
+    assert!(sum.contains(0.3) && sum.contains(0.1 + 0.2));
+    let third = eval("1 / 3");
+    assert!(third.lo < third.hi && third.lo * 3.0 <= 1.0 && third.hi * 3.0 >= 1.0);
+    let root = eval("sqrt(2)");
+    assert!(root.lo * root.lo <= 2.0 && root.hi * root.hi >= 2.0);
+    assert_eq!(root.hi, root.lo.next_up());
+}
+
+#[test]
+fn ranges() {
+    assert_encloses("interval(1.9, 2.1)^2", 4.0, 0.81);
+    let square = eval("interval(1.9, 2.1)^2");
+    assert!(square.contains(1.9 * 1.9) && square.contains(2.1 * 2.1));
+    assert_eq!(eval("interval(2, 1)"), Interval::new(1.0, 2.0));
+    assert_eq!(eval("interval(-1, 2)^2"), Interval::new(0.0, 4.0));
+    assert_eq!(eval("interval(-2, 1)^3"), Interval::new(-8.0, 1.0));
+    assert_eq!(
+        eval("interval(-1, 2) * interval(3, 4)"),
+        Interval::new(-4.0, 8.0)
+    );
+    assert_eq!(
+        eval("interval(1, 2) - interval(1, 2)"),
+        Interval::new(-1.0, 1.0)
+    );
+    assert_eq!(eval("-interval(1, 2)"), Interval::new(-2.0, -1.0));
+}
+
+#[test]
+fn division_by_intervals_containing_zero() {
+    let inf = f64::INFINITY;
+    assert_eq!(eval("1 / interval(-1, 1)"), Interval::new(-inf, inf));
+    assert_eq!(eval("1 / interval(0, 2)"), Interval::new(0.5, inf));
+    assert_eq!(eval("1 / interval(-2, 0)"), Interval::new(-inf, -0.5));
+    assert_eq!(eval("-1 / interval(0, 2)"), Interval::new(-inf, -0.5));
+    assert_eq!(
+        eval("interval(0, 1) / interval(0, 1)"),
+        Interval::new(-inf, inf)
+    );
+    assert_eq!(
+        Interpreter::<Interval>::default().eval_str("1 / 0"),
+        Err(Error::Interpret(OwnedInterpretError::DivisionByZero))
+    );
+    assert_eq!(
+        Interpreter::<Interval>::default().eval_str("1 % interval(0, 0)"),
+        Err(Error::Interpret(OwnedInterpretError::DivisionByZero))
+    );
+}
+
+#[test]
+fn functions() {
+    assert_eq!(eval("sin(interval(0, 3))"), Interval::new(0.0, 1.0));
+    assert_eq!(eval("cos(interval(-1, 7))"), Interval::new(-1.0, 1.0));
+    assert_eq!(eval("tan(interval(1, 2))"), Interval::entire());
+    assert_encloses("sin(pi/6)", 0.5, 1e-15);
+    assert_encloses("cos(pi)", -1.0, 1e-15);
+    assert_encloses("exp(1)", std::f64::consts::E, 1e-15);
+    assert_encloses("ln(e)", 1.0, 1e-15);
+    assert_encloses("log(1000)", 3.0, 1e-15);
+    assert_eq!(eval("sqrt(interval(-1, 4))"), Interval::new(0.0, 2.0));
+    assert!(eval("sqrt(-1)").is_nan());
+    assert_eq!(
+        eval("ln(interval(0, 1))"),
+        Interval::new(-f64::INFINITY, 0.0)
+    );
+    assert_eq!(eval("abs(interval(-3, 2))"), Interval::new(0.0, 3.0));
+    assert_eq!(eval("sign(interval(-1, 2))"), Interval::new(-1.0, 1.0));
+    assert_eq!(
+        eval("min(interval(2, 4), interval(1, 3))"),
+        Interval::new(1.0, 3.0)
+    );
+    assert_eq!(
+        eval("max(interval(2, 4), interval(1, 5))"),
+        Interval::new(2.0, 5.0)
+    );
+    assert_eq!(eval("floor(interval(1.5, 3.5))"), Interval::new(1.0, 3.0));
+    let gamma = eval("gamma(interval(1, 3))");
+    assert!(gamma.lo <= 0.8856031944108887 && gamma.hi == 2.0);
+    assert_eq!(eval("lo(interval(1, 2))"), Interval::point(1.0));
+    assert_eq!(eval("hi(interval(1, 2))"), Interval::point(2.0));
+    assert_eq!(eval("mid(interval(1, 2))"), Interval::point(1.5));
+    assert_eq!(eval("width(interval(1, 2))"), Interval::point(1.0));
+    assert!(eval("pi").contains(std::f64::consts::PI));
+}
+
+#[test]
+fn angles() {
+    let mut interpreter = Interpreter::<Interval>::default();
+    interpreter.settings.angle_mode = rsc::AngleMode::Degrees;
+    let sine = interpreter.eval_str("sin(30)").unwrap();
+    assert!(sine.contains(0.5) && sine.width() < 1e-15);
+    let angle = interpreter.eval_str("atan2(1, 1)").unwrap();
+    assert!(angle.contains(45.0) && angle.width() < 1e-13);
+}
+
+#[test]
+fn ordering() {
+    let (a, b) = (Interval::new(1.0, 2.0), Interval::new(3.0, 4.0));
+    assert!(a < b);
+    assert_eq!(b.partial_cmp(&a), Some(std::cmp::Ordering::Greater));
+    let overlapping = Interval::new(1.5, 3.5);
+    assert_eq!(a.partial_cmp(&overlapping), None);
+    assert_eq!(
+        Interval::point(1.0).partial_cmp(&Interval::point(1.0)),
+        Some(std::cmp::Ordering::Equal)
+    );
+    assert_eq!(a.partial_cmp(&a), None);
+}
+
+#[test]
+fn display() {
+    assert_eq!(Interval::point(2.5).to_string(), "2.5");
+    assert_eq!(Interval::new(1.0, 2.0).to_string(), "interval(1, 2)");
+    assert_eq!(Interval::entire().to_string(), "interval(-inf, inf)");
+    assert_eq!(Interval::nan().to_string(), "NaN");
+    assert_eq!(Interval::from_f64(0.5), Interval::around(0.5));
+}
+
+#[test]
+fn display_reads_back() {
+    // Bounds that are written exactly read back as the same interval
+    for interval in [
+        Interval::new(1.0, 2.0),
+        Interval::new(-0.5, 0.25),
+        Interval::entire(),
+    ] {
+        assert_eq!(eval(&interval.to_string()), interval);
+    }
+    // Others read back as an interval around it
+    let interval = eval("interval(1.9, 2.1)^2");
+    let again = eval(&interval.to_string());
+    assert!(again.contains(interval.lo) && again.contains(interval.hi));
+    assert!(again.width() <= interval.width() + 1e-14);
+
+    // An interval in a residual expression reads back as one, not as a list
+    let mut interpreter = Interpreter::<Interval>::default();
+    interpreter.eval_str("a = interval(1, 2)").unwrap();
+    let tokens = tokenize("x * a + [a][0]").unwrap();
+    let expr = parse(&tokens).unwrap();
+    let residual = interpreter.partial_eval(&expr).unwrap().to_string();
+    assert_eq!(residual, "x*interval(1, 2) + interval(1, 2)");
+    let tokens = tokenize(&residual).unwrap();
+    let reparsed = parse(&tokens).unwrap();
+    assert_eq!(
+        interpreter.partial_eval(&reparsed).unwrap().to_string(),
+        residual
+    );
+    interpreter.eval_str("x = 3").unwrap();
+    assert_eq!(
+        interpreter.eval(&reparsed).unwrap(),
+        interpreter.eval(&expr).unwrap()
+    );
+    assert_eq!(interpreter.eval(&expr).unwrap(), Interval::new(4.0, 8.0));
+}
@@ -0,0 +1,190 @@
+use rsc::{
+    diagnose, parse, tokenize, Complex, Error, Interpreter, OwnedInterpretError, Value, Variant,
+};
+
+fn eval(input: &str) -> Value<f64> {
+    Interpreter::<f64>::default()
+        .eval_value_str(input)
+        .unwrap_or_else(|e| panic!("{}: {}", input, e))
+}
+
+fn error(input: &str) -> OwnedInterpretError {
+    match Interpreter::<f64>::default().eval_value_str(input) {
+        Err(Error::Interpret(err)) => err,
+        result => panic!("{}: expected an interpret error, got {:?}", input, result),
+    }
+}
+
+#[test]
+fn literals_and_indexing() {
+    assert_eq!(eval("[1, 2, 3]"), Value::List(vec![1.0, 2.0, 3.0]));
+    assert_eq!(eval("[]"), Value::List(vec![]));
+    assert_eq!(eval("[1 + 1, -2, 3!]"), Value::List(vec![2.0, -2.0, 6.0]));
+    assert_eq!(eval("[4, 5, 6][0]"), Value::Num(4.0));
+    assert_eq!(eval("[4, 5, 6][1 + 1] * 2"), Value::Num(12.0));
+    assert_eq!(eval("sqrt([4, 9])[1]"), Value::Num(3.0));
+
+    let mut interpreter = Interpreter::<f64>::default();
+    assert_eq!(
+        interpreter.eval_value_str("v = [1, 2, 3]"),
+        Ok(Value::List(vec![1.0, 2.0, 3.0]))
+    );
+    assert_eq!(interpreter.eval_str("v[2]"), Ok(3.0));
+    assert_eq!(interpreter.eval_str("len(v)"), Ok(3.0));
+    assert_eq!(
+        interpreter.eval_value_str("v(2)"),
+        Ok(Value::List(vec![2.0, 4.0, 6.0]))
+    );
+    // eval only returns numbers, but still assigns
+    assert_eq!(
+        interpreter.eval_str("w = v"),
+        Err(Error::Interpret(OwnedInterpretError::NotANumber(
+            String::from("a list of 3")
+        )))
+    );
+    assert!(matches!(interpreter.vars.get("w"), Some(Variant::List(_))));
+}
+
+#[test]
+fn operators_broadcast() {
+    assert_eq!(eval("[1, 2, 3] * 2"), Value::List(vec![2.0, 4.0, 6.0]));
+    assert_eq!(eval("10 - [1, 2]"), Value::List(vec![9.0, 8.0]));
+    assert_eq!(eval("[1, 2] + [10, 20]"), Value::List(vec![11.0, 22.0]));
+    assert_eq!(eval("[2, 3]^2"), Value::List(vec![4.0, 9.0]));
+    assert_eq!(eval("-[1, -2]"), Value::List(vec![-1.0, 2.0]));
+    assert_eq!(eval("max([1, 2], [3, 0])"), Value::Num(3.0));
+    assert_eq!(
+        eval("atan2([0, 1], 1)"),
+        Value::List(vec![0.0, 1f64.atan2(1.0)])
+    );
+    assert_eq!(eval("[] * 2"), Value::List(vec![]));
+}
+
+#[test]
+fn aggregates() {
+    assert_eq!(eval("sum([1, 2, 3])"), Value::Num(6.0));
+    assert_eq!(eval("sum(1, [2, 3], 4)"), Value::Num(10.0));
+    assert_eq!(eval("sum([])"), Value::Num(0.0));
+    assert_eq!(eval("product([2, 3, 4])"), Value::Num(24.0));
+    assert_eq!(eval("mean([1, 2, 3, 4])"), Value::Num(2.5));
+    assert_eq!(eval("min([3, 1, 2])"), Value::Num(1.0));
+    assert_eq!(eval("max([3, 1, 2], 5)"), Value::Num(5.0));
+    assert_eq!(eval("dot([1, 2, 3], [4, 5, 6])"), Value::Num(32.0));
+    assert_eq!(eval("len([])"), Value::Num(0.0));
+
+    let mut interpreter = Interpreter::<i64>::default();
+    assert_eq!(interpreter.eval_str("gcd([12, 18, 30])"), Ok(6));
+    assert_eq!(interpreter.eval_str("mean([1, 2])"), Ok(1));
+    let mut interpreter = Interpreter::<u8>::default();
+    assert_eq!(
+        interpreter.eval_str("sum([200, 100])"),
+        Err(Error::Interpret(OwnedInterpretError::Overflow))
+    );
+    let mut interpreter = Interpreter::<Complex>::default();
+    assert_eq!(
+        interpreter.eval_str("dot([i, 1], [i, 2])"),
+        Ok(Complex::from(1.0))
+    );
+    assert_eq!(
+        interpreter.eval_str("max([1, 3, 2])"),
+        Ok(Complex::from(3.0))
+    );
+}
+
+#[test]
+fn errors_are_located() {
+    assert_eq!(
+        error("[1, 2] + [1, 2, 3]"),
+        OwnedInterpretError::ShapeMismatch(
+            String::from("+"),
+            String::from("a list of 2"),
+            String::from("a list of 3"),
+            7..8
+        )
+    );
+    assert_eq!(
+        error("atan2([1, 2], [1])"),
+        OwnedInterpretError::ShapeMismatch(
+            String::from("atan2"),
+            String::from("a list of 2"),
+            String::from("a list of 1"),
+            0..18
+        )
+    );
+    assert_eq!(
+        error("dot([1], 2)"),
+        OwnedInterpretError::TypeMismatch(
+            String::from("dot"),
+            String::from("a list"),
+            String::from("a number"),
+            0..11
+        )
+    );
+    assert_eq!(
+        error("[1, 2][2]"),
+        OwnedInterpretError::IndexOutOfRange(2, 6..9)
+    );
+    assert_eq!(
+        error("[1, 2][0.5]"),
+        OwnedInterpretError::IndexOutOfRange(2, 6..11)
+    );
+    assert_eq!(
+        error("5[0]"),
+        OwnedInterpretError::TypeMismatch(
+            String::from("[]"),
+            String::from("a list"),
+            String::from("a number"),
+            1..4
+        )
+    );
+    assert_eq!(
+        error("[1, [2]]"),
+        OwnedInterpretError::TypeMismatch(
+            String::from("[]"),
+            String::from("a number"),
+            String::from("a list of 1"),
+            0..8
+        )
+    );
+    assert_eq!(
+        error("len(sum)"),
+        OwnedInterpretError::FunctionNameUsedLikeVar(String::from("sum"))
+    );
+}
+
+#[test]
+fn parsing() {
+    let tokens = tokenize::<f64>("[1, x][0] + f(2)[1]").unwrap();
+    let expr = parse(&tokens).unwrap();
+    assert_eq!(expr.to_string(), "[1, x][0] + f(2)[1]");
+    assert!(parse(&tokenize::<f64>("[1, 2").unwrap()).is_err());
+    assert!(parse(&tokenize::<f64>("[1 2]").unwrap()).is_err());
+    assert_eq!(diagnose::<f64>("[1, ] + [2").len(), 2);
+}
+
+#[test]
+fn partial_evaluation_folds_lists() {
+    let mut interpreter = Interpreter::<f64>::default();
+    interpreter.eval_value_str("v = [1, 2]").unwrap();
+    let tokens = tokenize("v * 2 + x").unwrap();
+    let expr = parse(&tokens).unwrap();
+    let folded = interpreter.partial_eval(&expr).unwrap();
+    assert_eq!(folded.to_string(), "[2, 4] + x");
+    let tokens = tokenize("sum([a, 1])").unwrap();
+    let expr = parse(&tokens).unwrap();
+    assert_eq!(
+        interpreter.partial_eval(&expr).unwrap().to_string(),
+        "sum([a, 1])"
+    );
+}
+
+#[test]
+fn state_keeps_lists() {
+    let mut interpreter = Interpreter::<f64>::default();
+    interpreter.eval_value_str("v = [1, 2]").unwrap();
+    let state = interpreter.state();
+    assert_eq!(state.lists.get("v"), Some(&vec![1.0, 2.0]));
+    assert!(state.functions.contains(&String::from("sum")));
+    let mut restored = Interpreter::from_state(&state, &Interpreter::default()).unwrap();
+    assert_eq!(restored.eval_str("sum(v)"), Ok(3.0));
+}
@@ -0,0 +1,293 @@
+use rsc::{parse, tokenize, Error, Interpreter, Matrix, OwnedInterpretError, Value, Variant};
+
+fn matrix(rows: &[&[f64]]) -> Value<f64> {
+    Value::Matrix(Matrix::from_rows(rows.iter().map(|row| row.to_vec()).collect()).unwrap())
+}
+
+fn eval(input: &str) -> Value<f64> {
+    Interpreter::<f64>::default()
+        .eval_value_str(input)
+        .unwrap_or_else(|e| panic!("{}: {}", input, e))
+}
+
+fn error(input: &str) -> OwnedInterpretError {
+    match Interpreter::<f64>::default().eval_value_str(input) {
+        Err(Error::Interpret(err)) => err,
+        result => panic!("{}: expected an interpret error, got {:?}", input, result),
+    }
+}
+
+#[test]
+fn literals_and_indexing() {
+    assert_eq!(
+        eval("[[1, 2], [3, 4]]"),
+        matrix(&[&[1.0, 2.0], &[3.0, 4.0]])
+    );
+    assert_eq!(eval("[[1, 2, 3]]"), matrix(&[&[1.0, 2.0, 3.0]]));
+    assert_eq!(eval("[[1, 2], [3, 4]][1]"), Value::List(vec![3.0, 4.0]));
+    assert_eq!(eval("[[1, 2], [3, 4]][1][0]"), Value::Num(3.0));
+    assert_eq!(eval("[[1, 2], [3, 4]]").to_string(), "[[1, 2], [3, 4]]");
+    assert!(Matrix::<f64>::from_rows(vec![vec![1.0], vec![]]).is_none());
+
+    let mut interpreter = Interpreter::<f64>::default();
+    interpreter.eval_value_str("A = [[1, 2], [3, 4]]").unwrap();
+    assert!(matches!(
+        interpreter.vars.get("A"),
+        Some(Variant::Matrix(_))
+    ));
+    assert_eq!(interpreter.eval_str("det(A)"), Ok(-2.0));
+    assert_eq!(
+        interpreter.eval_str("A"),
+        Err(Error::Interpret(OwnedInterpretError::NotANumber(
+            String::from("a 2×2 matrix")
+        )))
+    );
+}
+
+#[test]
+fn operators() {
+    assert_eq!(
+        eval("[[1, 2], [3, 4]] * [[5, 6], [7, 8]]"),
+        matrix(&[&[19.0, 22.0], &[43.0, 50.0]])
+    );
+    assert_eq!(eval("[[1, 2, 3]] * [[1], [2], [3]]"), matrix(&[&[14.0]]));
+    assert_eq!(
+        eval("[[1, 2], [3, 4]] * [1, 1]"),
+        Value::List(vec![3.0, 7.0])
+    );
+    assert_eq!(
+        eval("[1, 1] * [[1, 2], [3, 4]]"),
+        Value::List(vec![4.0, 6.0])
+    );
+    assert_eq!(
+        eval("[[1, 2], [3, 4]] * 2 + 1"),
+        matrix(&[&[3.0, 5.0], &[7.0, 9.0]])
+    );
+    assert_eq!(
+        eval("[[1, 2], [3, 4]] - [[1, 1], [1, 1]]"),
+        matrix(&[&[0.0, 1.0], &[2.0, 3.0]])
+    );
+    assert_eq!(
+        eval("-sqrt([[1, 4], [9, 16]])"),
+        matrix(&[&[-1.0, -2.0], &[-3.0, -4.0]])
+    );
+    assert_eq!(
+        eval("[[1, 2], [3, 4]]^3"),
+        matrix(&[&[37.0, 54.0], &[81.0, 118.0]])
+    );
+    assert_eq!(
+        eval("[[2, 0], [0, 4]]^-1"),
+        matrix(&[&[0.5, 0.0], &[0.0, 0.25]])
+    );
+    assert_eq!(eval("[[1, 2], [3, 4]]^0"), eval("identity(2)"));
+    assert_eq!(eval("sum([[1, 2], [3, 4]])"), Value::Num(10.0));
+}
+
+#[test]
+fn functions() {
+    assert_eq!(
+        eval("transpose([[1, 2, 3], [4, 5, 6]])"),
+        matrix(&[&[1.0, 4.0], &[2.0, 5.0], &[3.0, 6.0]])
+    );
+    assert_eq!(
+        eval("det([[2, -3, 1], [2, 0, -1], [1, 4, 5]])"),
+        Value::Num(49.0)
+    );
+    assert_eq!(eval("det([[0, 1], [1, 0]])"), Value::Num(-1.0));
+    assert_eq!(eval("det([[1, 2], [2, 4]])"), Value::Num(0.0));
+    assert_eq!(
+        eval("inv([[4, 7], [2, 6]])"),
+        matrix(&[&[0.6, -0.7], &[-0.2, 0.4]])
+    );
+    assert_eq!(
+        eval("identity(3)"),
+        matrix(&[&[1.0, 0.0, 0.0], &[0.0, 1.0, 0.0], &[0.0, 0.0, 1.0]])
+    );
+    assert_eq!(
+        eval("solve([[2, 1], [1, 3]], [3, 5])"),
+        Value::List(vec![0.8, 1.4])
+    );
+    assert_eq!(
+        eval("solve([[0, 1], [1, 0]], [[1, 2], [3, 4]])"),
+        matrix(&[&[3.0, 4.0], &[1.0, 2.0]])
+    );
+
+    // Elimination divides exactly, so whole results are exact for integers
+    let mut interpreter = Interpreter::<i64>::default();
+    assert_eq!(
+        interpreter.eval_str("det([[2, -3, 1], [2, 0, -1], [1, 4, 5]])"),
+        Ok(49)
+    );
+    assert_eq!(
+        interpreter
+            .eval_value_str("inv([[2, 1], [1, 1]])")
+            .unwrap()
+            .to_string(),
+        "[[1, -1], [-1, 2]]"
+    );
+    assert_eq!(
+        interpreter.eval_value_str("solve([[2, 1], [1, 1]], [3, 2])"),
+        Ok(Value::List(vec![1, 1]))
+    );
+    assert_eq!(
+        interpreter
+            .eval_value_str("[[2, 1], [1, 1]]^-2")
+            .unwrap()
+            .to_string(),
+        "[[2, -3], [-3, 5]]"
+    );
+    // Integers cannot represent the inverse of a matrix whose determinant is -2
+    assert_eq!(
+        interpreter.eval_value_str("inv([[1, 2], [3, 4]])"),
+        Err(Error::Interpret(OwnedInterpretError::OutOfDomain(
+            String::from("inv")
+        )))
+    );
+    assert_eq!(
+        interpreter.eval_value_str("[[1, 2], [3, 4]]^-1"),
+        Err(Error::Interpret(OwnedInterpretError::OutOfDomain(
+            String::from("^")
+        )))
+    );
+    assert_eq!(
+        interpreter.eval_value_str("solve([[2, 1], [1, 3]], [3, 5])"),
+        Err(Error::Interpret(OwnedInterpretError::OutOfDomain(
+            String::from("solve")
+        )))
+    );
+    assert_eq!(
+        interpreter.eval_value_str("solve([[1, 2], [3, 4]], [5, 11])"),
+        Ok(Value::List(vec![1, 2]))
+    );
+    let mut interpreter = Interpreter::<u8>::default();
+    assert_eq!(
+        interpreter.eval_value_str("[[200]] * [[2]]"),
+        Err(Error::Interpret(OwnedInterpretError::Overflow))
+    );
+}
+
+#[test]
+fn errors_are_located() {
+    assert_eq!(
+        error("[[1, 2], [3, 4]] * [[1, 2, 3]]"),
+        OwnedInterpretError::ShapeMismatch(
+            String::from("*"),
+            String::from("a 2×2 matrix"),
+            String::from("a 1×3 matrix"),
+            17..18
+        )
+    );
+    assert_eq!(
+        error("[[1, 2]] * [1, 2, 3]"),
+        OwnedInterpretError::ShapeMismatch(
+            String::from("*"),
+            String::from("a 1×2 matrix"),
+            String::from("a list of 3"),
+            9..10
+        )
+    );
+    assert_eq!(
+        error("[[1, 2]] + [1, 2]"),
+        OwnedInterpretError::ShapeMismatch(
+            String::from("+"),
+            String::from("a 1×2 matrix"),
+            String::from("a list of 2"),
+            9..10
+        )
+    );
+    assert_eq!(
+        error("[[1, 2], [3]]"),
+        OwnedInterpretError::ShapeMismatch(
+            String::from("[]"),
+            String::from("a list of 2"),
+            String::from("a list of 1"),
+            0..13
+        )
+    );
+    assert_eq!(
+        error("[[1], 2]"),
+        OwnedInterpretError::TypeMismatch(
+            String::from("[]"),
+            String::from("a list"),
+            String::from("a number"),
+            0..8
+        )
+    );
+    assert_eq!(
+        error("det([[1, 2, 3]])"),
+        OwnedInterpretError::TypeMismatch(
+            String::from("det"),
+            String::from("a square matrix"),
+            String::from("a 1×3 matrix"),
+            0..16
+        )
+    );
+    assert_eq!(
+        error("[[1, 2]]^2"),
+        OwnedInterpretError::TypeMismatch(
+            String::from("^"),
+            String::from("a square matrix"),
+            String::from("a 1×2 matrix"),
+            8..9
+        )
+    );
+    assert_eq!(
+        error("solve(identity(2), [1, 2, 3])"),
+        OwnedInterpretError::ShapeMismatch(
+            String::from("solve"),
+            String::from("a 2×2 matrix"),
+            String::from("a list of 3"),
+            0..29
+        )
+    );
+    assert_eq!(
+        error("transpose([1, 2])"),
+        OwnedInterpretError::TypeMismatch(
+            String::from("transpose"),
+            String::from("a matrix"),
+            String::from("a list of 2"),
+            0..17
+        )
+    );
+    assert_eq!(
+        error("[[1, 2]][1]"),
+        OwnedInterpretError::IndexOutOfRange(1, 8..11)
+    );
+    assert_eq!(
+        error("inv([[1, 2], [2, 4]])"),
+        OwnedInterpretError::OutOfDomain(String::from("inv"))
+    );
+    assert_eq!(
+        error("identity(0)"),
+        OwnedInterpretError::OutOfDomain(String::from("identity"))
+    );
+}
+
+#[test]
+fn partial_evaluation_folds_matrices() {
+    let mut interpreter = Interpreter::<f64>::default();
+    interpreter.eval_value_str("A = [[1, 2], [3, 4]]").unwrap();
+    let tokens = tokenize("A * [1, 0] + x").unwrap();
+    let expr = parse(&tokens).unwrap();
+    let folded = interpreter.partial_eval(&expr).unwrap();
+    assert_eq!(folded.to_string(), "[1, 3] + x");
+    let tokens = tokenize("[[1, 2], [3, 4]] * [[a], [1]]").unwrap();
+    let expr = parse(&tokens).unwrap();
+    assert_eq!(
+        interpreter.partial_eval(&expr).unwrap().to_string(),
+        "[[1, 2], [3, 4]]*[[a], [1]]"
+    );
+}
+
+#[test]
+fn state_keeps_matrices() {
+    let mut interpreter = Interpreter::<f64>::default();
+    interpreter.eval_value_str("A = [[1, 2], [3, 4]]").unwrap();
+    let state = interpreter.state();
+    assert_eq!(
+        state.matrices.get("A").map(Matrix::describe),
+        Some(String::from("a 2×2 matrix"))
+    );
+    let mut restored = Interpreter::from_state(&state, &Interpreter::default()).unwrap();
+    assert_eq!(restored.eval_str("det(A)"), Ok(-2.0));
+}
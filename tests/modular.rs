@@ -0,0 +1,102 @@
+use rsc::{Error, Interpreter, Modular, OwnedInterpretError};
+
+type Mod7 = Modular<7>;
+
+fn eval<const M: u64>(input: &str) -> u64 {
+    Interpreter::<Modular<M>>::default()
+        .eval_str(input)
+        .unwrap_or_else(|e| panic!("{}: {}", input, e))
+        .value()
+}
+
+#[test]
+fn arithmetic() {
+    assert_eq!(eval::<7>("5 + 4"), 2);
+    assert_eq!(eval::<7>("2 - 5"), 4);
+    assert_eq!(eval::<7>("-3"), 4);
+    assert_eq!(eval::<7>("3 * 5"), 1);
+    assert_eq!(eval::<7>("1 / 3"), 5);
+    assert_eq!(eval::<7>("6 / 3"), 2);
+    assert_eq!(eval::<7>("12"), 5);
+    assert_eq!(eval::<7>("modinv(3)"), 5);
+    assert_eq!(eval::<7>("6 % 4"), 2);
+    // Products don't overflow
+    assert_eq!(
+        eval::<18446744073709551557>("18446744073709551556 * 18446744073709551556"),
+        1
+    );
+}
+
+#[test]
+fn exponents_are_not_reduced() {
+    assert_eq!(eval::<7>("2^10"), 2);
+    assert_eq!(eval::<7>("3^6"), 1);
+    assert_eq!(eval::<1000000007>("2^100"), 976371285);
+    // Fermat's little theorem
+    assert_eq!(eval::<1000000007>("12345^1000000006"), 1);
+    // Exponents that are results keep their whole value too
+    assert_eq!(eval::<7>("2^(50 + 50)"), 2);
+    assert_eq!(eval::<7>("2^(10 * 10)"), 2);
+    assert_eq!(eval::<7>("2^(200 / 2)"), 2);
+    assert_eq!(eval::<7>("2^(101 - 1)"), 2);
+    assert_eq!(eval::<7>("2^(2^3)"), 4);
+    let mut interpreter = Interpreter::<Mod7>::default();
+    interpreter.eval_str("x = 100").unwrap();
+    assert_eq!(interpreter.eval_str("2^x").unwrap().value(), 2);
+}
+
+#[test]
+fn negative_exponents_raise_the_inverse() {
+    assert_eq!(eval::<7>("2^-1"), 4);
+    assert_eq!(eval::<7>("3^-2"), 4);
+    assert_eq!(eval::<7>("2^(1 - 101)"), 4);
+    let mut interpreter = Interpreter::<Modular<12>>::default();
+    assert_eq!(interpreter.eval_str("5^-1").unwrap().value(), 5);
+    assert_eq!(
+        interpreter.eval_str("4^-1"),
+        Err(Error::Interpret(OwnedInterpretError::OutOfDomain(
+            String::from("^")
+        )))
+    );
+    // An exponent that is only known by its residue
+    assert_eq!(
+        interpreter.eval_str("2^(1 / 5)"),
+        Err(Error::Interpret(OwnedInterpretError::OutOfDomain(
+            String::from("^")
+        )))
+    );
+}
+
+#[test]
+fn division_needs_an_inverse() {
+    let mut interpreter = Interpreter::<Modular<12>>::default();
+    assert_eq!(
+        interpreter.eval_str("1 / 12"),
+        Err(Error::Interpret(OwnedInterpretError::DivisionByZero))
+    );
+    assert_eq!(
+        interpreter.eval_str("1 / 4"),
+        Err(Error::Interpret(OwnedInterpretError::OutOfDomain(
+            String::from("/")
+        )))
+    );
+    assert_eq!(
+        interpreter.eval_str("modinv(4)"),
+        Err(Error::Interpret(OwnedInterpretError::OutOfDomain(
+            String::from("modinv")
+        )))
+    );
+    assert_eq!(interpreter.eval_str("1 / 5").unwrap().value(), 5);
+    assert_eq!(Modular::<12>::new(4).inverse(), None);
+}
+
+#[test]
+fn numbers_are_residues() {
+    assert_eq!(Mod7::new(10), Mod7::new(3));
+    assert_eq!("10".parse::<Mod7>().unwrap(), Mod7::new(3));
+    assert!(Mod7::new(10) < Mod7::new(4));
+    assert_eq!(Mod7::new(10).to_string(), "3");
+    assert_eq!(Mod7::new(3).pow(6), Mod7::new(1));
+    assert_eq!(Mod7::new(2) / Mod7::new(4), Mod7::new(4));
+    assert_eq!(Modular::<1>::new(5).value(), 0);
+}
@@ -0,0 +1,106 @@
+use rsc::{parse, tokenize, Expr, Interpreter};
+
+/// Partially evaluates `input` and prints the residual expression.
+fn residual(interpreter: &mut Interpreter<f64>, input: &str) -> String {
+    let tokens = tokenize(input).unwrap();
+    let expr = parse(&tokens).unwrap();
+    interpreter.partial_eval(&expr).unwrap().to_string()
+}
+
+/// The expression with every span emptied, since reparsing printed text moves them.
+fn without_spans<'a>(expr: &Expr<'a, f64>) -> Expr<'a, f64> {
+    let boxed = |expr: &Expr<'a, f64>| Box::new(without_spans(expr));
+    match expr {
+        Expr::Eq(lhs, rhs) => Expr::Eq(boxed(lhs), boxed(rhs)),
+        Expr::FuncOrVarMul(id, args, _) => {
+            Expr::FuncOrVarMul(id.clone(), args.iter().map(without_spans).collect(), 0..0)
+        }
+        Expr::Index(list, index, _) => Expr::Index(boxed(list), boxed(index), 0..0),
+        Expr::List(items, _) => Expr::List(items.iter().map(without_spans).collect(), 0..0),
+        Expr::Neg(expr, _) => Expr::Neg(boxed(expr), 0..0),
+        Expr::Degrees(expr, _) => Expr::Degrees(boxed(expr), 0..0),
+        Expr::Op(op, lhs, rhs, _) => Expr::Op(*op, boxed(lhs), boxed(rhs), 0..0),
+        expr => expr.clone(),
+    }
+}
+
+#[test]
+fn folds_known_subtrees() {
+    let mut interpreter = Interpreter::<f64>::default();
+    interpreter.eval_str("a = 3").unwrap();
+    interpreter.eval_str("b = 2").unwrap();
+    interpreter.eval_str("c = 1").unwrap();
+    assert_eq!(
+        residual(&mut interpreter, "a*x^2 + b*x + c"),
+        "3*x^2 + 2*x + 1"
+    );
+    assert_eq!(residual(&mut interpreter, "sqrt(a + c) * y"), "2*y");
+    assert_eq!(residual(&mut interpreter, "(a + b) * (c + 1)"), "10");
+    // A negation applies to the rest of the expression
+    assert_eq!(residual(&mut interpreter, "-a + |b - 5|"), "-6");
+}
+
+#[test]
+fn keeps_unknown_variables() {
+    let mut interpreter = Interpreter::<f64>::default();
+    assert_eq!(residual(&mut interpreter, "x + 1 + 2"), "x + 1 + 2");
+    assert_eq!(residual(&mut interpreter, "x + (1 + 2)"), "x + 3");
+    assert_eq!(residual(&mut interpreter, "f(1 + 1, y)"), "f(2, y)");
+    assert_eq!(residual(&mut interpreter, "sqrt(x)^2"), "sqrt(x)^2");
+    // Binding a variable later lets the residual fold further
+    let tokens = tokenize("x * 2 + y").unwrap();
+    let expr = parse(&tokens).unwrap();
+    let folded = interpreter.partial_eval(&expr).unwrap();
+    interpreter.eval_str("x = 4").unwrap();
+    assert_eq!(
+        interpreter.partial_eval(&folded).unwrap().to_string(),
+        "8 + y"
+    );
+}
+
+#[test]
+fn assignments() {
+    let mut interpreter = Interpreter::<f64>::default();
+    interpreter.eval_str("x = 2").unwrap();
+    // The target stays a name, rather than becoming its old value
+    assert_eq!(residual(&mut interpreter, "x = x + y"), "x = 2 + y");
+    assert_eq!(residual(&mut interpreter, "z = x * 3"), "6");
+    assert_eq!(interpreter.eval_str("z"), Ok(6.0));
+}
+
+#[test]
+fn display_parses_back_into_the_same_tree() {
+    for input in [
+        "3*x^2 + 2*x + 1",
+        "a - (b - c)",
+        "(a + b)*c",
+        "a/(b*c)",
+        "2^3^x",
+        "(2^3)^x",
+        "-(x + 1)",
+        "-x^2",
+        "(-2)*x",
+        "x!",
+        "(x + 1)!",
+        "|x - 1|",
+        "f(x, y + 1)*g()",
+        "y = (x = 2) + 1",
+        "[1, x][0]",
+        "30°",
+        "(x + 1)°",
+        "-x°^2",
+    ] {
+        let tokens = tokenize::<f64>(input).unwrap();
+        let expr = parse(&tokens).unwrap();
+        let printed = expr.to_string();
+        let reparsed_tokens = tokenize::<f64>(&printed).unwrap();
+        let reparsed = parse(&reparsed_tokens).unwrap();
+        assert_eq!(
+            without_spans(&reparsed),
+            without_spans(&expr),
+            "{} printed as {}",
+            input,
+            printed
+        );
+    }
+}
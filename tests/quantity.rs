@@ -0,0 +1,286 @@
+use rsc::{
+    parse, tokenize, Diagnostic, Error, InterpretError, Interpreter, OpVal, OwnedInterpretError,
+    Quantity, Real, TokenValue,
+};
+
+fn eval(input: &str) -> Quantity {
+    Interpreter::<Quantity>::default()
+        .eval_str(input)
+        .unwrap_or_else(|e| panic!("{}: {}", input, e))
+}
+
+fn assert_eval(input: &str, display: &str) {
+    assert_eq!(eval(input).to_string(), display, "{}", input);
+}
+
+fn mismatch(input: &str) -> OwnedInterpretError {
+    match Interpreter::<Quantity>::default().eval_str(input) {
+        Err(Error::Interpret(err @ OwnedInterpretError::DimensionMismatch(..))) => err,
+        result => panic!("{}: expected a dimension mismatch, got {:?}", input, result),
+    }
+}
+
+#[test]
+fn literals() {
+    assert_eval("5 km", "5 km");
+    assert_eval("5km", "5 km");
+    assert_eval("3 m^2", "3 m^2");
+    assert_eval("2 s^-1", "2 s^-1");
+    assert_eval("1.5 µs", "1.5 µs");
+    assert_eval("1.5 us", "1.5 us");
+    assert_eval("20 min", "20 min");
+    assert_eval("9.81 m/s^2", "9.81 m/s^2");
+    assert_eval("2.5", "2.5");
+
+    let tokens = tokenize::<Quantity>("5 km / 20 min").unwrap();
+    assert_eq!(
+        tokens[0].value,
+        TokenValue::Num(Quantity::new(5.0, "km").unwrap())
+    );
+    assert_eq!(tokens[0].span, 0..4);
+    assert_eq!(tokens[2].span, 7..13);
+    // Other number types don't read units
+    let tokens = tokenize::<f64>("5 km").unwrap();
+    assert_eq!(tokens[1].value, TokenValue::Id("km".into()));
+}
+
+#[test]
+fn arithmetic() {
+    assert_eval("5 km / 20 min", "0.25 km/min");
+    assert_eval("5 km + 300 m", "5.3 km");
+    assert_eval("300 m + 5 km", "5300 m");
+    assert_eval("2 m * 3 m", "6 m^2");
+    assert_eval("80 kg * 9.81 m/s^2", "784.8000000000001 kg*m/s^2");
+    assert_eval("1 / (2 s)", "0.5 s^-1");
+    assert_eval("(3 m)^2", "9 m^2");
+    assert_eval("-(3 m)", "-3 m");
+    assert_eval("7 m % 2 m", "1 m");
+    // Units that cancel leave a plain number
+    assert_eval("1 km / 1 m", "1000");
+    assert_eval("6 m / 2 m", "3");
+    assert_eval("3 Hz * 2 s", "6");
+}
+
+#[test]
+fn conversions() {
+    assert_eval("5 km / 20 min to km/h", "15 km/h");
+    assert_eval("1 h to min", "60 min");
+    assert_eval("2 us to ns", "2000 ns");
+    assert_eval("1 L to cm^3", "1000 cm^3");
+    assert_eval("1 mi to km", "1.609344 km");
+    assert_eval("5 kg*m^2/s^2 to J", "5 J");
+    assert_eval("36 km/h to m/s", "10 m/s");
+    let speed = eval("100 km/h to mph");
+    assert!((speed.value() - 62.137_119_223_733_4).abs() < 1e-12);
+    assert_eq!(speed.unit(), "mph");
+
+    let mut interpreter = Interpreter::<Quantity>::default();
+    interpreter.eval_str("d = 42.195 km").unwrap();
+    let pace = interpreter.eval_str("2 h / d to min/km").unwrap();
+    assert!((pace.value() - 2.843_938_855_314_611).abs() < 1e-12);
+}
+
+#[test]
+fn dimension_mismatches() {
+    assert_eq!(
+        mismatch("1 m + 1 s"),
+        OwnedInterpretError::DimensionMismatch(
+            String::from("+"),
+            String::from("m"),
+            String::from("s"),
+            4..5
+        )
+    );
+    assert_eq!(
+        mismatch("5 km to s"),
+        OwnedInterpretError::DimensionMismatch(
+            String::from("to"),
+            String::from("m"),
+            String::from("s"),
+            5..7
+        )
+    );
+    assert_eq!(
+        mismatch("1 N - 2 kg*m/s"),
+        OwnedInterpretError::DimensionMismatch(
+            String::from("-"),
+            String::from("m*kg/s^2"),
+            String::from("m*kg/s"),
+            4..5
+        )
+    );
+    assert!(matches!(
+        mismatch("2 ^ (1 s)"),
+        OwnedInterpretError::DimensionMismatch(op, lhs, _, _) if op == "^" && lhs == "1"
+    ));
+    assert!(matches!(
+        mismatch("3 + 1 m"),
+        OwnedInterpretError::DimensionMismatch(..)
+    ));
+
+    let diagnostic = Diagnostic::from(&Error::Interpret(mismatch("(1 m + 1 s) * 2")));
+    assert_eq!(diagnostic.span, Some(5..6));
+
+    // The plain operators give NaN instead
+    let sum = Quantity::new(1.0, "m").unwrap() + Quantity::new(1.0, "s").unwrap();
+    assert!(sum.value().is_nan());
+}
+
+#[test]
+fn partial_eval() {
+    let tokens = tokenize::<Quantity>("x + 1 m + 1 s").unwrap();
+    let expr = parse(&tokens).unwrap();
+    let mut interpreter = Interpreter::<Quantity>::default();
+    assert!(interpreter.partial_eval(&expr).is_ok());
+    interpreter.eval_str("x = 2 m").unwrap();
+    assert!(matches!(
+        interpreter.partial_eval(&expr),
+        Err(InterpretError::DimensionMismatch("+", _, _, span)) if span == (8..9)
+    ));
+}
+
+#[test]
+fn functions() {
+    assert_eval("sqrt(9 m^2)", "3 m");
+    assert_eval("cbrt(8 m^3)", "2 m");
+    assert_eval("abs(-3 m)", "3 m");
+    assert_eval("round(2.6 km)", "3 km");
+    assert_eval("hypot(3 m, 400 cm)", "5 m");
+    assert_eval("sin(pi / 2)", "1");
+    assert_eval("value(5 km)", "5");
+    assert_eval("km/h", "1 km/h");
+    assert_eval("min", "1 min");
+    assert_eval("root(-8 m^3, 3)", "-2 m");
+    assert_eval("root(16, 4)", "2");
+    assert_eval("atan2(1 m, 100 cm)", &eval("pi / 4").to_string());
+    assert_eval("ln(e)", "1");
+    assert_eval("3!", "6");
+    assert_eq!(
+        Interpreter::<Quantity>::default().eval_str("(2 m)^0.5"),
+        Err(Error::Interpret(OwnedInterpretError::OutOfDomain(
+            String::from("^")
+        )))
+    );
+}
+
+#[test]
+fn functions_of_plain_numbers_reject_dimensions() {
+    assert_eq!(
+        mismatch("1 + sin(3 m)"),
+        OwnedInterpretError::DimensionMismatch(
+            String::from("sin"),
+            String::from("1"),
+            String::from("m"),
+            4..12
+        )
+    );
+    assert_eq!(
+        mismatch("ln(1 s)"),
+        OwnedInterpretError::DimensionMismatch(
+            String::from("ln"),
+            String::from("1"),
+            String::from("s"),
+            0..7
+        )
+    );
+    for input in [
+        "exp(2 kg)",
+        "log(10 m, 10)",
+        "(2 m)!",
+        "gamma(1 h)",
+        "atan(1 m)",
+    ] {
+        mismatch(input);
+    }
+    assert_eq!(
+        mismatch("hypot(3 m, 4 s)"),
+        OwnedInterpretError::DimensionMismatch(
+            String::from("hypot"),
+            String::from("m"),
+            String::from("s"),
+            0..15
+        )
+    );
+    mismatch("atan2(1 m, 1 s)");
+    // Roots need powers of the unit that they divide
+    for input in [
+        "sqrt(2 m)",
+        "cbrt(1 m^2)",
+        "root(8 m^3, 2)",
+        "root(8 m^3, 1.5)",
+    ] {
+        assert!(
+            matches!(
+                Interpreter::<Quantity>::default().eval_str(input),
+                Err(Error::Interpret(OwnedInterpretError::OutOfDomain(_)))
+            ),
+            "{}",
+            input
+        );
+    }
+    // The Real methods themselves give NaN
+    assert!(Quantity::new(3.0, "m").unwrap().sin().value().is_nan());
+}
+
+#[test]
+fn zero_has_every_dimension() {
+    assert_eval("0 + 3 m", "3 m");
+    assert_eval("3 m + 0", "3 m");
+    assert_eval("0 - 3 m", "-3 m");
+    assert_eval("3 m - 0", "3 m");
+    assert_eval("0 to km", "0 km");
+    assert_eval("sum([0, 1 m, 50 cm])", "1.5 m");
+    assert_eval("abs(-3 m)", "3 m");
+    assert_eval("sign(-3 m)", "-1");
+    assert!(Quantity::number(0.0) < Quantity::new(1.0, "km").unwrap());
+    mismatch("1 + 3 m");
+    mismatch("0 m + 1 s");
+}
+
+#[test]
+fn min_is_both_a_function_and_a_unit() {
+    assert_eval("min(1, 2)", "1");
+    assert_eval("min(1 h, 50 min)", "50 min");
+    assert_eval("max(2 min, 90 s)", "2 min");
+    assert_eval("90 s to min", "1.5 min");
+    assert_eval("3 km/min", "3 km/min");
+    assert_eval("2 * min", "2 min");
+
+    let tokens = tokenize::<Quantity>("x / min").unwrap();
+    let expr = parse(&tokens).unwrap();
+    assert_eq!(
+        Interpreter::<Quantity>::default()
+            .partial_eval(&expr)
+            .unwrap()
+            .to_string(),
+        "x/(1 min)"
+    );
+}
+
+#[test]
+fn comparison() {
+    let km = Quantity::new(1.0, "km").unwrap();
+    let m = Quantity::new(1000.0, "m").unwrap();
+    assert_eq!(km, m);
+    assert!(Quantity::new(1.0, "mi").unwrap() > km);
+    assert_eq!(km.partial_cmp(&Quantity::new(1.0, "s").unwrap()), None);
+    // Zero has every dimension
+    assert!(Quantity::number(0.0) < km);
+    assert_eq!(km.dimension(), [1, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(km.si_value(), 1000.0);
+    assert!(Quantity::new(1.0, "parsec").is_none());
+}
+
+#[test]
+fn plain_numbers_have_no_units() {
+    assert_eq!(
+        Interpreter::<f64>::default().eval_str("2 to 3"),
+        Err(Error::Interpret(OwnedInterpretError::OutOfDomain(
+            String::from("to")
+        )))
+    );
+    let tokens = tokenize::<f64>("1 to 2").unwrap();
+    let expr = parse(&tokens).unwrap();
+    assert_eq!(expr.to_string(), "1 to 2");
+    assert!(matches!(expr, rsc::Expr::Op(OpVal::To, ..)));
+}
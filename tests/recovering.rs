@@ -0,0 +1,131 @@
+use rsc::{
+    diagnose, parse, parse_recovering, tokenize, tokenize_recovering, InterpretError, Interpreter,
+    OpVal, TokenValue, TokenizeErrorCode, TokenizeOptions,
+};
+
+/// The messages and spans of every error found in `input`.
+fn errors(input: &str) -> Vec<(String, std::ops::Range<usize>)> {
+    diagnose::<f64>(input)
+        .into_iter()
+        .map(|d| (d.message, d.span.unwrap()))
+        .collect()
+}
+
+#[test]
+fn tokenizing_reports_every_error() {
+    let input = "2 $ 3 + 1.2.3 # 4";
+    let (tokens, errs) = tokenize_recovering::<f64>(input, TokenizeOptions::default());
+    let codes: Vec<_> = errs
+        .iter()
+        .map(|err| (err.code.clone(), err.span.clone()))
+        .collect();
+    assert_eq!(
+        codes,
+        [
+            (TokenizeErrorCode::UnrecognizedChar('$'), 2..3),
+            (TokenizeErrorCode::InvalidNumber("1.2.3".into()), 8..13),
+            (TokenizeErrorCode::UnrecognizedChar('#'), 14..15),
+        ]
+    );
+    // Unrecognized characters are skipped, and the invalid number is kept as an error token
+    let values: Vec<_> = tokens.iter().map(|tok| tok.value.clone()).collect();
+    assert_eq!(
+        values,
+        [
+            TokenValue::Num(2.0),
+            TokenValue::Num(3.0),
+            TokenValue::Op(OpVal::Add),
+            TokenValue::Error,
+            TokenValue::Num(4.0),
+        ]
+    );
+    assert_eq!(tokens[3].span, 8..13);
+
+    // Without errors, the result is the same as tokenize
+    let (tokens, errs) = tokenize_recovering::<f64>("2 + x", TokenizeOptions::default());
+    assert!(errs.is_empty());
+    assert_eq!(tokens, tokenize::<f64>("2 + x").unwrap());
+}
+
+#[test]
+fn parsing_reports_every_error() {
+    let tokens = tokenize::<f64>("f(1, , 3) + [1, 2").unwrap();
+    let (expr, errs) = parse_recovering(&tokens);
+    let errs: Vec<_> = errs
+        .iter()
+        .map(|err| (err.to_string(), err.span.clone()))
+        .collect();
+    assert_eq!(
+        errs,
+        [
+            (String::from("Unexpected \",\"."), 5..6),
+            (String::from("Unexpected end of input."), 17..18),
+        ]
+    );
+    // The missing argument is an error node, and the rest of the expression is intact
+    assert_eq!(expr.to_string(), "f(1, ?, 3) + [1, 2]");
+
+    let tokens = tokenize::<f64>("(1 + * 2").unwrap();
+    let (expr, errs) = parse_recovering(&tokens);
+    assert_eq!(errs.len(), 2);
+    assert_eq!(expr.to_string(), "1 + 2");
+
+    // Without errors, the result is the same as parse
+    let tokens = tokenize::<f64>("2 * (x + 1)").unwrap();
+    let (expr, errs) = parse_recovering(&tokens);
+    assert!(errs.is_empty());
+    assert_eq!(expr, parse(&tokens).unwrap());
+}
+
+#[test]
+fn stray_tokens_do_not_hide_the_rest() {
+    let tokens = tokenize::<f64>("1 + 2) * 3").unwrap();
+    let (expr, errs) = parse_recovering(&tokens);
+    assert_eq!(errs.len(), 1);
+    assert_eq!(errs[0].span, 5..6);
+    assert_eq!(expr.to_string(), "(1 + 2)*3");
+    assert!(matches!(Interpreter::default().eval(&expr), Ok(n) if n == 9.0));
+}
+
+#[test]
+fn invalid_numbers_become_error_nodes() {
+    let input = "1 + 2.3.4 * (5 + x";
+    let (tokens, tokenize_errs) = tokenize_recovering::<f64>(input, TokenizeOptions::default());
+    let (expr, parse_errs) = parse_recovering(&tokens);
+    assert_eq!(tokenize_errs.len(), 1);
+    assert_eq!(parse_errs.len(), 1);
+    assert_eq!(expr.to_string(), "1 + ?*(5 + x)");
+    assert!(matches!(
+        Interpreter::<f64>::default().eval(&expr),
+        Err(InterpretError::InvalidExpr)
+    ));
+    // Partial evaluation leaves the error node in place
+    let mut interpreter = Interpreter::<f64>::default();
+    interpreter.eval_str("x = 1").unwrap();
+    assert_eq!(
+        interpreter.partial_eval(&expr).unwrap().to_string(),
+        "1 + ?*6"
+    );
+}
+
+#[test]
+fn diagnose_orders_errors_of_both_stages() {
+    assert_eq!(
+        errors("2 $ 3 + 1.2.3 # 4"),
+        [
+            (String::from("Unrecognized character '$'."), 2..3),
+            (String::from("Unexpected \"3\"."), 4..5),
+            (String::from("Invalid number \"1.2.3\"."), 8..13),
+            (String::from("Unrecognized character '#'."), 14..15),
+            (String::from("Unexpected \"4\"."), 16..17),
+        ]
+    );
+    assert_eq!(
+        errors("1 + 2.3.4 * (5 + x"),
+        [
+            (String::from("Invalid number \"2.3.4\"."), 4..9),
+            (String::from("Unexpected end of input."), 18..19),
+        ]
+    );
+    assert!(errors("2 * (x + 1)").is_empty());
+}
@@ -0,0 +1,113 @@
+#![cfg(feature = "serde")]
+
+use rsc::{
+    parse, tokenize, Error, Expr, Interpreter, InterpreterState, ParseError, ParseErrorCode, Token,
+    TokenValue, TokenizeError, TokenizeErrorCode,
+};
+use std::borrow::Cow;
+
+#[test]
+fn tokens_round_trip() {
+    let tokens = tokenize::<f64>("x = sqrt(2) * |y - 1|").unwrap();
+    let json = serde_json::to_string(&tokens).unwrap();
+    let back: Vec<Token<f64>> = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, tokens);
+}
+
+#[test]
+fn escaped_identifiers_are_owned() {
+    let json = r#"{"value":{"Id":"\u00b5s"},"span":{"start":0,"end":3}}"#;
+    let token: Token<f64> = serde_json::from_str(json).unwrap();
+    assert!(matches!(token.value, TokenValue::Id(Cow::Owned(ref id)) if id == "µs"));
+
+    let json = r#"{"code":{"InvalidNumber":"1.2.\"3"},"span":{"start":0,"end":6}}"#;
+    let err: TokenizeError = serde_json::from_str(json).unwrap();
+    assert_eq!(err.code, TokenizeErrorCode::InvalidNumber("1.2.\"3".into()));
+}
+
+#[test]
+fn tokenize_errors_round_trip() {
+    let err = tokenize::<f64>("2 + 1.2.3").unwrap_err();
+    let json = serde_json::to_string(&err).unwrap();
+    let back: TokenizeError = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, err);
+}
+
+#[test]
+fn parse_errors_round_trip() {
+    for input in ["2 + 3 x)", "(1 + 2", "2 *", "f(1, 2"] {
+        let tokens = tokenize::<f64>(input).unwrap();
+        let err = parse(&tokens).unwrap_err();
+        let json = serde_json::to_string(&err).unwrap();
+        let back: ParseError<f64> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.code, err.code, "{}", input);
+        assert_eq!(back.span, err.span, "{}", input);
+    }
+
+    let json = r#"{"code":{"UnexpectedToken":{"value":{"Id":"caf\u00e9"},"span":{"start":2,"end":6}}},"span":{"start":2,"end":6}}"#;
+    let err: ParseError<f64> = serde_json::from_str(json).unwrap();
+    assert_eq!(
+        err.code,
+        ParseErrorCode::UnexpectedToken(Token {
+            value: TokenValue::Id("café".into()),
+            span: 2..6,
+        })
+    );
+    assert_eq!(err.to_string(), "Unexpected \"café\".");
+}
+
+#[test]
+fn expressions_round_trip() {
+    let tokens = tokenize::<f64>("y = 2 * f(x, [1, 2][0]) + -3!").unwrap();
+    let expr = parse(&tokens).unwrap();
+    let json = serde_json::to_string(&expr).unwrap();
+    let back: Expr<f64> = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, expr);
+}
+
+#[test]
+fn expressions_read_from_a_reader_are_owned() {
+    let tokens = tokenize::<f64>("area = pi * r^2 + max(r, 1)").unwrap();
+    let expr = parse(&tokens).unwrap();
+    let json = serde_json::to_vec(&expr).unwrap();
+    // A reader can't lend out its contents, so every name is deserialized owned
+    let back: Expr<'static, f64> = serde_json::from_reader(json.as_slice()).unwrap();
+    assert_eq!(back, expr);
+    let value = serde_json::to_value(&expr).unwrap();
+    let back: Expr<'static, f64> = serde_json::from_value(value).unwrap();
+    assert_eq!(back, expr);
+
+    let mut interpreter = Interpreter::<f64>::default();
+    interpreter.eval_str("r = 2").unwrap();
+    let area = std::f64::consts::PI * 4.0 + 2.0;
+    assert_eq!(interpreter.eval(&back).unwrap(), area);
+    assert_eq!(interpreter.eval_str("area"), Ok(area));
+}
+
+#[test]
+fn owned_errors_round_trip() {
+    let mut interpreter = Interpreter::<f64>::default();
+    for input in ["2 + 1.2.3", "2 + 3 x)", "unknown + 1", "sqrt(1, 2)"] {
+        let err = interpreter.eval_str(input).unwrap_err();
+        let json = serde_json::to_string(&err).unwrap();
+        let back: Error = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, err, "{}", input);
+    }
+}
+
+#[test]
+fn interpreter_state_round_trips() {
+    let mut interpreter = Interpreter::<f64>::default();
+    // JSON has no NaN or infinities
+    interpreter.vars.remove("inf");
+    interpreter.vars.remove("nan");
+    interpreter.eval_str("a = 2.5").unwrap();
+    interpreter.eval_value_str("l = [1, 2, 3]").unwrap();
+    interpreter.eval_value_str("m = [[1, 2], [3, 4]]").unwrap();
+    let json = serde_json::to_string(&interpreter.state()).unwrap();
+    let state: InterpreterState<f64> = serde_json::from_str(&json).unwrap();
+    assert_eq!(state, interpreter.state());
+
+    let mut restored = Interpreter::from_state(&state, &Interpreter::default()).unwrap();
+    assert_eq!(restored.eval_str("a * sum(l) + det(m) + sqrt(4)"), Ok(15.0));
+}
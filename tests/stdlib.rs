@@ -0,0 +1,289 @@
+use rsc::{AngleMode, Error, Interpreter, OwnedInterpretError};
+
+fn eval(input: &str) -> f64 {
+    Interpreter::<f64>::default()
+        .eval_str(input)
+        .unwrap_or_else(|e| panic!("{}: {}", input, e))
+}
+
+fn assert_approx(input: &str, expected: f64) {
+    let result = eval(input);
+    assert!(
+        (result - expected).abs() <= 1e-12 * expected.abs().max(1.0),
+        "{} = {}, expected {}",
+        input,
+        result,
+        expected
+    );
+}
+
+#[test]
+fn trigonometry() {
+    assert_approx("sin(0)", 0.0);
+    assert_approx("sin(pi/6)", 0.5);
+    assert_approx("cos(pi/3)", 0.5);
+    assert_approx("cos(pi)", -1.0);
+    assert_approx("tan(pi/4)", 1.0);
+    assert_approx("asin(1)", std::f64::consts::FRAC_PI_2);
+    assert_approx("acos(0.5)", std::f64::consts::FRAC_PI_3);
+    assert_approx("atan(1)", std::f64::consts::FRAC_PI_4);
+    assert_approx("atan2(1, -1)", 3.0 * std::f64::consts::FRAC_PI_4);
+    assert_approx("atan2(-1, 0)", -std::f64::consts::FRAC_PI_2);
+}
+
+#[test]
+fn degree_literals() {
+    let mut interpreter = Interpreter::<f64>::default();
+    interpreter.settings.angle_mode = AngleMode::Degrees;
+    // An angle in the angle mode is not converted, so it stays exact
+    assert_eq!(interpreter.eval_str("30°"), Ok(30.0));
+    assert_eq!(interpreter.eval_str("30deg"), Ok(30.0));
+    assert_eq!(interpreter.eval_str("deg(30)"), Ok(30.0));
+    assert_eq!(
+        interpreter.eval_str("asin(sin(30°))").map(f64::round),
+        Ok(30.0)
+    );
+    interpreter.settings.angle_mode = AngleMode::Gradians;
+    assert_eq!(interpreter.eval_str("grad(50)"), Ok(50.0));
+    assert!((interpreter.eval_str("90°").unwrap() - 100.0).abs() < 1e-12);
+    interpreter.settings.angle_mode = AngleMode::Radians;
+    assert!((interpreter.eval_str("30°").unwrap() - std::f64::consts::FRAC_PI_6).abs() < 1e-15);
+    assert_approx("sin(30°)", 0.5);
+    assert_approx("cos(60deg)", 0.5);
+}
+
+#[test]
+fn degree_literals_ignore_a_variable_named_deg() {
+    let mut interpreter = Interpreter::<f64>::default();
+    interpreter.eval_str("deg = 2").unwrap();
+    assert_eq!(interpreter.eval_str("deg * 3"), Ok(6.0));
+    assert!((interpreter.eval_str("180°").unwrap() - std::f64::consts::PI).abs() < 1e-15);
+    interpreter.settings.angle_mode = AngleMode::Degrees;
+    assert_eq!(interpreter.eval_str("30°"), Ok(30.0));
+    assert_eq!(interpreter.eval_str("30deg + deg"), Ok(32.0));
+
+    // Types without trigonometry only have degrees in degree mode
+    let mut interpreter = Interpreter::<i64>::default();
+    assert_eq!(
+        interpreter.eval_str("30°"),
+        Err(Error::Interpret(OwnedInterpretError::OutOfDomain(
+            String::from("°")
+        )))
+    );
+    interpreter.settings.angle_mode = AngleMode::Degrees;
+    assert_eq!(interpreter.eval_str("30°"), Ok(30));
+}
+
+#[test]
+fn hyperbolic() {
+    assert_approx("sinh(0)", 0.0);
+    assert_approx("cosh(0)", 1.0);
+    assert_approx("sinh(1)", 1.1752011936438014);
+    assert_approx("cosh(1)", 1.5430806348152437);
+    assert_approx("tanh(1)", 0.7615941559557649);
+    assert_approx("asinh(sinh(2))", 2.0);
+    assert_approx("acosh(cosh(2))", 2.0);
+    assert_approx("atanh(0.5)", 0.5493061443340549);
+}
+
+#[test]
+fn exponents_and_logarithms() {
+    assert_approx("exp(0)", 1.0);
+    assert_approx("exp(1)", std::f64::consts::E);
+    assert_approx("ln(e^3)", 3.0);
+    assert_approx("log(1000)", 3.0);
+    assert_approx("log(8, 2)", 3.0);
+    assert_approx("log(81, 3)", 4.0);
+    assert_approx("log2(1024)", 10.0);
+    assert_approx("log10(0.01)", -2.0);
+}
+
+#[test]
+fn roots() {
+    assert_approx("sqrt(16)", 4.0);
+    assert_approx("cbrt(27)", 3.0);
+    assert_approx("cbrt(-8)", -2.0);
+    assert_approx("root(16, 4)", 2.0);
+    assert_approx("root(-32, 5)", -2.0);
+    assert_approx("hypot(3, 4)", 5.0);
+    assert!(eval("root(-16, 4)").is_nan());
+}
+
+#[test]
+fn rounding() {
+    assert_eq!(eval("floor(2.7)"), 2.0);
+    assert_eq!(eval("floor(-2.5)"), -3.0);
+    assert_eq!(eval("ceil(2.1)"), 3.0);
+    assert_eq!(eval("ceil(-2.5)"), -2.0);
+    assert_eq!(eval("round(2.5)"), 3.0);
+    assert_eq!(eval("round(-2.5)"), -3.0);
+    assert_eq!(eval("round(2.4)"), 2.0);
+    assert_eq!(eval("trunc(-2.7)"), -2.0);
+}
+
+#[test]
+fn min_max_and_sign() {
+    assert_eq!(eval("min(3)"), 3.0);
+    assert_eq!(eval("min(3, -1, 2)"), -1.0);
+    assert_eq!(eval("max(3, -1, 2, 7.5)"), 7.5);
+    assert_eq!(eval("sign(-4)"), -1.0);
+    assert_eq!(eval("sign(0)"), 0.0);
+    assert_eq!(eval("sign(0.1)"), 1.0);
+    assert_eq!(eval("abs(-3)"), 3.0);
+}
+
+#[test]
+fn arity_is_checked() {
+    let mut interpreter = Interpreter::<f64>::default();
+    let cases = [
+        (
+            "sin()",
+            OwnedInterpretError::TooFewArgs(String::from("sin"), 1),
+        ),
+        (
+            "sin(1, 2)",
+            OwnedInterpretError::TooManyArgs(String::from("sin"), 1),
+        ),
+        (
+            "atan2(1)",
+            OwnedInterpretError::TooFewArgs(String::from("atan2"), 2),
+        ),
+        (
+            "root(1, 2, 3)",
+            OwnedInterpretError::TooManyArgs(String::from("root"), 2),
+        ),
+        (
+            "log(1, 2, 3)",
+            OwnedInterpretError::TooManyArgs(String::from("log"), 2),
+        ),
+        (
+            "max()",
+            OwnedInterpretError::TooFewArgs(String::from("max"), 1),
+        ),
+    ];
+    for (input, expected) in cases.iter() {
+        assert_eq!(
+            interpreter.eval_str(input),
+            Err(Error::Interpret(expected.clone())),
+            "{}",
+            input
+        );
+    }
+}
+
+#[test]
+fn factorial_and_gamma() {
+    assert_eq!(eval("0!"), 1.0);
+    assert_eq!(eval("5!"), 120.0);
+    assert_eq!(eval("20!"), 2432902008176640000.0);
+    assert_approx("170!", 7.257415615307994e306);
+    assert_approx("0.5!", std::f64::consts::PI.sqrt() / 2.0);
+    assert_approx("(-0.5)!", std::f64::consts::PI.sqrt());
+    assert_eq!(eval("gamma(5)"), 24.0);
+    assert_approx("gamma(0.5)", std::f64::consts::PI.sqrt());
+    assert_approx("gamma(-1.5)", 4.0 / 3.0 * std::f64::consts::PI.sqrt());
+    assert_approx("gamma(7.3)", 1271.423633663909);
+    assert_approx("lgamma(100)", 359.1342053695754);
+    assert_approx("lgamma(0.5)", std::f64::consts::PI.sqrt().ln());
+    assert_approx("beta(2, 3)", 1.0 / 12.0);
+    assert_approx("beta(0.5, 0.5)", std::f64::consts::PI);
+}
+
+#[test]
+fn factorial_of_negative_integer_is_an_error() {
+    let mut interpreter = Interpreter::<f64>::default();
+    for (input, id) in [
+        ("(-1)!", "factorial"),
+        ("gamma(0)", "gamma"),
+        ("gamma(-3)", "gamma"),
+    ]
+    .iter()
+    {
+        assert_eq!(
+            interpreter.eval_str(input),
+            Err(Error::Interpret(OwnedInterpretError::OutOfDomain(
+                String::from(*id)
+            ))),
+            "{}",
+            input
+        );
+    }
+}
+
+#[test]
+fn f32_has_the_real_stdlib() {
+    let mut interpreter = Interpreter::<f32>::default();
+    let mut eval = |input| interpreter.eval_str(input).unwrap();
+    assert_eq!(eval("sqrt(16)"), 4.0);
+    assert!((eval("sin(pi/2)") - 1.0).abs() < 1e-6);
+    assert!((eval("0.5!") - std::f32::consts::PI.sqrt() / 2.0).abs() < 1e-6);
+    assert_eq!(eval("max(abs(-3), 2)"), 3.0);
+}
+
+#[test]
+fn integers_have_the_integer_stdlib() {
+    let mut interpreter = Interpreter::<i64>::default();
+    let mut eval = |input| interpreter.eval_str(input);
+    assert_eq!(eval("abs(-5)"), Ok(5));
+    assert_eq!(eval("sign(-5)"), Ok(-1));
+    assert_eq!(eval("min(3, 1, 2)"), Ok(1));
+    assert_eq!(eval("5!"), Ok(120));
+    assert_eq!(eval("20!"), Ok(2432902008176640000));
+    assert_eq!(
+        eval("21!"),
+        Err(Error::Interpret(OwnedInterpretError::Overflow))
+    );
+    assert_eq!(eval("gcd(12, 18)"), Ok(6));
+    assert_eq!(eval("gcd(-4, 6, 10)"), Ok(2));
+    assert_eq!(eval("lcm(4, 6)"), Ok(12));
+    assert_eq!(eval("lcm(4, 6, 10)"), Ok(60));
+    assert_eq!(eval("and(12, 10)"), Ok(8));
+    assert_eq!(eval("or(12, 10)"), Ok(14));
+    assert_eq!(eval("xor(12, 10)"), Ok(6));
+    assert_eq!(eval("not(0)"), Ok(-1));
+    assert_eq!(eval("shl(1, 62)"), Ok(1 << 62));
+    assert_eq!(eval("shr(-8, 1)"), Ok(-4));
+    assert_eq!(
+        eval("shl(1, 64)"),
+        Err(Error::Interpret(OwnedInterpretError::OutOfDomain(
+            String::from("shl")
+        )))
+    );
+    assert!(eval("sqrt(4)").is_err());
+}
+
+#[test]
+fn number_theory() {
+    let mut interpreter = Interpreter::<i64>::default();
+    let mut eval = |input| interpreter.eval_str(input);
+    assert_eq!(eval("modinv(3, 7)"), Ok(5));
+    assert_eq!(eval("modinv(-3, 7)"), Ok(2));
+    assert_eq!(eval("powmod(2, 100, 1000000007)"), Ok(976371285));
+    assert_eq!(eval("powmod(-2, 3, 5)"), Ok(2));
+    assert_eq!(eval("isprime(97)"), Ok(1));
+    assert_eq!(eval("isprime(91)"), Ok(0));
+    assert_eq!(eval("isprime(1)"), Ok(0));
+    assert_eq!(eval("isprime(2305843009213693951)"), Ok(1));
+    assert_eq!(eval("factor(91)"), Ok(7));
+    assert_eq!(eval("factor(97)"), Ok(97));
+    assert_eq!(eval("factor(1000000016000000063)"), Ok(1000000007));
+    // Large moduli don't overflow
+    assert_eq!(
+        eval("powmod(3, 9223372036854775782, 9223372036854775783)"),
+        Ok(1)
+    );
+    for input in ["modinv(2, 4)", "modinv(3, 0)", "factor(1)"] {
+        assert!(
+            matches!(
+                eval(input),
+                Err(Error::Interpret(OwnedInterpretError::OutOfDomain(_)))
+            ),
+            "{}",
+            input
+        );
+    }
+    assert_eq!(
+        eval("powmod(2, -1, 5)"),
+        Err(Error::Interpret(OwnedInterpretError::NegativeExponent))
+    );
+}
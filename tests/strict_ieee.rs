@@ -0,0 +1,91 @@
+use rsc::{parse, tokenize, Error, InterpretError, Interpreter, OwnedInterpretError};
+
+fn strict() -> Interpreter<f64> {
+    let mut interpreter = Interpreter::default();
+    interpreter.settings.strict_ieee = true;
+    interpreter
+}
+
+fn non_finite(op: &str, span: std::ops::Range<usize>) -> Result<f64, Error> {
+    Err(Error::Interpret(OwnedInterpretError::NonFinite(
+        String::from(op),
+        span,
+    )))
+}
+
+#[test]
+fn non_finite_results_are_errors() {
+    let mut interpreter = strict();
+    assert_eq!(interpreter.eval_str("1 / 0"), non_finite("/", 2..3));
+    assert_eq!(interpreter.eval_str("0 / 0"), non_finite("/", 2..3));
+    assert_eq!(interpreter.eval_str("1e308 * 10"), non_finite("*", 6..7));
+    assert_eq!(interpreter.eval_str("10^400"), non_finite("^", 2..3));
+    assert_eq!(interpreter.eval_str("0 * inf"), non_finite("*", 2..3));
+    assert_eq!(interpreter.eval_str("1 + nan"), non_finite("+", 2..3));
+    assert_eq!(interpreter.eval_str("-inf"), non_finite("-", 0..1));
+    // A function call's span covers its arguments
+    assert_eq!(
+        interpreter.eval_str("2 + sqrt(-1)"),
+        non_finite("sqrt", 4..12)
+    );
+    assert_eq!(interpreter.eval_str("ln(0)"), non_finite("ln", 0..5));
+    assert_eq!(interpreter.eval_str("exp(1000)"), non_finite("exp", 0..9));
+    // The innermost operation is reported
+    assert_eq!(interpreter.eval_str("-(1 / 0)"), non_finite("/", 4..5));
+    assert_eq!(
+        interpreter.eval_value_str("[1, 2] / 0"),
+        Err(Error::Interpret(OwnedInterpretError::NonFinite(
+            String::from("/"),
+            7..8
+        )))
+    );
+
+    // A failed assignment leaves the variable unset
+    assert_eq!(interpreter.eval_str("x = 1 / 0"), non_finite("/", 6..7));
+    assert!(interpreter.eval_str("x").is_err());
+
+    // The constants themselves are not operations
+    assert_eq!(interpreter.eval_str("inf"), Ok(f64::INFINITY));
+    assert!(interpreter.eval_str("nan").unwrap().is_nan());
+    assert_eq!(interpreter.eval_str("1 / 4"), Ok(0.25));
+}
+
+#[test]
+fn spans_point_into_the_input() {
+    let input = "2 * (1 + 3 / (2 - 2))";
+    let err = strict().eval_str(input).unwrap_err();
+    assert_eq!(err.span(), Some(11..12));
+    assert_eq!(&input[err.span().unwrap()], "/");
+    assert_eq!(err.to_string(), "\"/\" produced NaN or infinity.");
+}
+
+#[test]
+fn partial_evaluation_is_strict_too() {
+    let tokens = tokenize::<f64>("y + 1/0").unwrap();
+    let expr = parse(&tokens).unwrap();
+    assert!(matches!(
+        strict().partial_eval(&expr),
+        Err(InterpretError::NonFinite("/", ref span)) if *span == (5..6)
+    ));
+    assert_eq!(
+        Interpreter::default()
+            .partial_eval(&expr)
+            .unwrap()
+            .to_string(),
+        "y + inf"
+    );
+}
+
+#[test]
+fn non_finite_results_pass_through_when_off() {
+    let mut interpreter = Interpreter::<f64>::default();
+    assert!(!interpreter.settings.strict_ieee);
+    assert_eq!(interpreter.eval_str("1 / 0"), Ok(f64::INFINITY));
+    assert_eq!(interpreter.eval_str("-1 / 0"), Ok(f64::NEG_INFINITY));
+    assert!(interpreter.eval_str("0 / 0").unwrap().is_nan());
+    assert!(interpreter.eval_str("sqrt(-1)").unwrap().is_nan());
+    assert_eq!(interpreter.eval_str("ln(0)"), Ok(f64::NEG_INFINITY));
+    assert_eq!(interpreter.eval_str("10^400"), Ok(f64::INFINITY));
+    assert_eq!(interpreter.eval_str("x = 1 / 0"), Ok(f64::INFINITY));
+    assert_eq!(interpreter.eval_str("x - 1"), Ok(f64::INFINITY));
+}
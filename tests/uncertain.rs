@@ -0,0 +1,111 @@
+use rsc::{tokenize, Error, Interpreter, OpVal, OwnedInterpretError, TokenValue, Uncertain};
+
+fn eval(input: &str) -> Uncertain {
+    Interpreter::<Uncertain>::default()
+        .eval_str(input)
+        .unwrap_or_else(|e| panic!("{}: {}", input, e))
+}
+
+fn assert_approx(input: &str, value: f64, uncertainty: f64) {
+    let result = eval(input);
+    assert!(
+        (result.value() - value).abs() <= 1e-12
+            && (result.uncertainty() - uncertainty).abs() <= 1e-12,
+        "{} = {}, expected {} ± {}",
+        input,
+        result,
+        value,
+        uncertainty
+    );
+}
+
+#[test]
+fn literals() {
+    assert_approx("9.81 +- 0.02", 9.81, 0.02);
+    assert_approx("9.81 ± 0.02", 9.81, 0.02);
+    assert_approx("9.81+-0.02", 9.81, 0.02);
+    assert_approx("2 +- 0.1 * 3", 2.0, 0.3);
+    assert_approx("1 + 2 +- 0.5", 3.0, 0.5);
+    assert_approx("5 +- -0.5", 5.0, 0.5);
+    assert!(eval("2.5").is_exact());
+}
+
+#[test]
+fn plus_minus_tokens() {
+    let tokens = tokenize::<Uncertain>("1 +- 2 ± 3").unwrap();
+    assert_eq!(tokens[1].value, TokenValue::Op(OpVal::PlusMinus));
+    assert_eq!(tokens[1].span, 2..4);
+    assert_eq!(tokens[3].value, TokenValue::Op(OpVal::PlusMinus));
+    assert_eq!(tokens[3].span, 7..8);
+
+    // Number types without uncertainty read "+-" as a plus and a minus
+    let tokens = tokenize::<f64>("2+-3").unwrap();
+    assert_eq!(tokens[1].value, TokenValue::Op(OpVal::Add));
+    assert_eq!(Interpreter::<f64>::default().eval_str("2+-3"), Ok(-1.0));
+    assert_eq!(
+        Interpreter::<f64>::default().eval_str("2 ± 3"),
+        Err(Error::Interpret(OwnedInterpretError::OutOfDomain(
+            String::from("±")
+        )))
+    );
+}
+
+#[test]
+fn propagation() {
+    let mut interpreter = Interpreter::<Uncertain>::default();
+    interpreter.eval_str("x = 2 +- 0.1").unwrap();
+    interpreter.eval_str("y = 3 +- 0.2").unwrap();
+    let mut eval = |input: &str| interpreter.eval_str(input).unwrap();
+
+    let sum = eval("x + y");
+    assert_eq!(sum.value(), 5.0);
+    assert!((sum.uncertainty() - 0.05f64.sqrt()).abs() < 1e-15);
+    let product = eval("x * y");
+    assert_eq!(product.value(), 6.0);
+    assert!((product.uncertainty() - 0.5).abs() < 1e-15);
+    let quotient = eval("x / y");
+    assert!((quotient.uncertainty() - 0.5 / 9.0).abs() < 1e-15);
+    let power = eval("x^3");
+    assert!((power.uncertainty() - 3.0 * 4.0 * 0.1).abs() < 1e-12);
+    let scaled = eval("-2 * x");
+    assert_eq!(scaled.value(), -4.0);
+    assert!((scaled.uncertainty() - 0.2).abs() < 1e-15);
+}
+
+#[test]
+fn correlation() {
+    let mut interpreter = Interpreter::<Uncertain>::default();
+    interpreter.eval_str("x = 9.81 +- 0.02").unwrap();
+    interpreter.eval_str("y = 9.81 +- 0.02").unwrap();
+    let mut eval = |input: &str| interpreter.eval_str(input).unwrap();
+
+    assert_eq!(eval("x - x"), Uncertain::exact(0.0));
+    assert!(eval("x / x").is_exact());
+    assert!(eval("(x + 1) - x").is_exact());
+    assert!((eval("x + x").uncertainty() - 0.04).abs() < 1e-15);
+    // Independent measurements add in quadrature
+    assert!((eval("x - y").uncertainty() - 0.0008f64.sqrt()).abs() < 1e-15);
+    assert!((eval("(x - y) + y").uncertainty() - 0.02).abs() < 1e-15);
+}
+
+#[test]
+fn functions() {
+    assert_approx("sqrt(4 +- 0.4)", 2.0, 0.1);
+    assert_approx("ln(2 +- 0.1)", 2f64.ln(), 0.05);
+    assert_approx("exp(0 +- 0.1)", 1.0, 0.1);
+    assert_approx("sin(0 +- 0.1)", 0.0, 0.1);
+    assert_approx("cos(0 +- 0.1)", 1.0, 0.0);
+    assert_approx("abs(-3 +- 0.1)", 3.0, 0.1);
+    assert_approx("floor(2.5 +- 0.1)", 2.0, 0.0);
+    assert_approx("lgamma(1 +- 0.1)", 0.0, 0.057_721_566_490_153_29);
+    assert_approx("hypot(3 +- 0.1, 4)", 5.0, 0.06);
+    assert_approx("value(2 +- 0.1)", 2.0, 0.0);
+    assert_approx("uncertainty(2 +- 0.1)", 0.1, 0.0);
+}
+
+#[test]
+fn display() {
+    assert_eq!(Uncertain::exact(2.5).to_string(), "2.5");
+    assert_eq!(Uncertain::new(9.81, 0.02).to_string(), "9.81 ± 0.02");
+    assert_eq!(Uncertain::new(1.0, -0.5).to_string(), "1 ± 0.5");
+}